@@ -0,0 +1,108 @@
+// Download the PDFium library for a build target into src-tauri/pdfium, where
+// tauri.conf.json bundles it as a resource. Archives come from a pinned
+// pdfium-binaries release and must match the SHA-256 recorded below.
+//
+// Usage: node scripts/fetch-pdfium.mjs [--target <rust target triple>]
+//        node scripts/fetch-pdfium.mjs --print-checksums
+
+import { execFileSync } from 'child_process';
+import { createHash } from 'crypto';
+import { copyFileSync, existsSync, mkdtempSync, readdirSync, rmSync, unlinkSync, writeFileSync } from 'fs';
+import { tmpdir } from 'os';
+import { join, dirname } from 'path';
+import { fileURLToPath } from 'url';
+
+const __dirname = dirname(fileURLToPath(import.meta.url));
+const pdfiumDir = join(__dirname, '..', 'src-tauri', 'pdfium');
+
+// The PDFium build pdfium-render's default `pdfium_latest` feature targets.
+// When bumping it, run with --print-checksums and review the new hashes.
+const PDFIUM_TAG = 'chromium/7543';
+const RELEASE = `https://github.com/bblanchon/pdfium-binaries/releases/download/${encodeURIComponent(PDFIUM_TAG)}`;
+
+// SHA-256 of each archive at PDFIUM_TAG. Downloads without a recorded hash
+// are refused rather than bundled unverified.
+const CHECKSUMS = {
+  'pdfium-mac-arm64.tgz': null,
+  'pdfium-mac-x64.tgz': null,
+  'pdfium-linux-x64.tgz': null,
+  'pdfium-linux-arm64.tgz': null,
+  'pdfium-win-x64.tgz': null,
+  'pdfium-win-arm64.tgz': null,
+};
+
+// Rust target triple -> pdfium-binaries archive and the library inside it
+const PLATFORMS = {
+  'aarch64-apple-darwin': { archive: 'pdfium-mac-arm64.tgz', library: 'lib/libpdfium.dylib' },
+  'x86_64-apple-darwin': { archive: 'pdfium-mac-x64.tgz', library: 'lib/libpdfium.dylib' },
+  'x86_64-unknown-linux-gnu': { archive: 'pdfium-linux-x64.tgz', library: 'lib/libpdfium.so' },
+  'aarch64-unknown-linux-gnu': { archive: 'pdfium-linux-arm64.tgz', library: 'lib/libpdfium.so' },
+  'x86_64-pc-windows-msvc': { archive: 'pdfium-win-x64.tgz', library: 'bin/pdfium.dll' },
+  'aarch64-pc-windows-msvc': { archive: 'pdfium-win-arm64.tgz', library: 'bin/pdfium.dll' },
+};
+
+function hostTarget() {
+  const arch = { x64: 'x86_64', arm64: 'aarch64' }[process.arch];
+  const os = {
+    darwin: 'apple-darwin',
+    linux: 'unknown-linux-gnu',
+    win32: 'pc-windows-msvc',
+  }[process.platform];
+  return `${arch}-${os}`;
+}
+
+async function download(archive) {
+  const response = await fetch(`${RELEASE}/${archive}`);
+  if (!response.ok) {
+    console.error(`Failed to download ${archive}: ${response.status} ${response.statusText}`);
+    process.exit(1);
+  }
+  const bytes = Buffer.from(await response.arrayBuffer());
+  return { bytes, sha256: createHash('sha256').update(bytes).digest('hex') };
+}
+
+if (process.argv.includes('--print-checksums')) {
+  for (const archive of Object.keys(CHECKSUMS)) {
+    const { sha256 } = await download(archive);
+    console.log(`  '${archive}': '${sha256}',`);
+  }
+  process.exit(0);
+}
+
+const targetFlag = process.argv.indexOf('--target');
+const target = targetFlag === -1 ? hostTarget() : process.argv[targetFlag + 1];
+const platform = PLATFORMS[target];
+if (!platform) {
+  console.error(`No PDFium build for target '${target}'`);
+  process.exit(1);
+}
+
+const expected = CHECKSUMS[platform.archive];
+if (!expected) {
+  console.error(`No SHA-256 recorded for ${platform.archive} at ${PDFIUM_TAG}; run with --print-checksums`);
+  process.exit(1);
+}
+const { bytes, sha256 } = await download(platform.archive);
+if (sha256 !== expected) {
+  console.error(`${platform.archive} has SHA-256 ${sha256}, expected ${expected}`);
+  process.exit(1);
+}
+
+const workDir = mkdtempSync(join(tmpdir(), 'pdfium-'));
+const archivePath = join(workDir, platform.archive);
+writeFileSync(archivePath, bytes);
+execFileSync('tar', ['-xzf', archivePath, '-C', workDir]);
+
+// Only one platform's library is bundled at a time
+for (const file of readdirSync(pdfiumDir)) {
+  if (file !== 'README.md') unlinkSync(join(pdfiumDir, file));
+}
+const library = join(workDir, platform.library);
+if (!existsSync(library)) {
+  console.error(`${platform.archive} has no ${platform.library}`);
+  process.exit(1);
+}
+copyFileSync(library, join(pdfiumDir, platform.library.split('/').pop()));
+rmSync(workDir, { recursive: true, force: true });
+
+console.log(`PDFium ${PDFIUM_TAG} for ${target} -> src-tauri/pdfium/${platform.library.split('/').pop()}`);
@@ -0,0 +1,111 @@
+use super::{digit_runs, push_runs};
+
+/// Run widths of symbol values 0 to 105, bar first (ISO/IEC 15417)
+const PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312",
+    "132212", "221213", "221312", "231212", "112232", "122132", "122231", "113222",
+    "123122", "123221", "223211", "221132", "221231", "213212", "223112", "312131",
+    "311222", "321122", "321221", "312212", "322112", "322211", "212123", "212321",
+    "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+    "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121",
+    "313121", "211331", "231131", "213113", "213311", "213131", "311123", "311321",
+    "331121", "312113", "312311", "332111", "314111", "221411", "431111", "111224",
+    "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+    "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112",
+    "421211", "212141", "214121", "412121", "111143", "111341", "131141", "114113",
+    "114311", "411113", "411311", "113141", "114131", "311141", "411131", "211412",
+    "211214", "211232",
+];
+
+/// Stop pattern, including the final bar
+const STOP: &str = "2331112";
+
+const CODE_C: usize = 99;
+const CODE_B: usize = 100; // FNC4 in code set B
+const CODE_A: usize = 101; // FNC4 in code set A
+const START_A: usize = 103;
+const START_B: usize = 104;
+const START_C: usize = 105;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CodeSet {
+    A, // upper case and control characters
+    B, // all printable ASCII
+    C, // digit pairs
+}
+
+/// Modules of a Code 128 symbol for ASCII content. Runs of digits are packed
+/// in pairs with code set C; control characters switch to code set A.
+pub(super) fn encode(content: &str) -> Result<Vec<bool>, String> {
+    if let Some(c) = content.chars().find(|c| !c.is_ascii()) {
+        return Err(format!("Code 128 can't encode '{}'", c));
+    }
+    let bytes = content.as_bytes();
+    let digit_run = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let text_set = |b: u8| if b < 32 { CodeSet::A } else { CodeSet::B };
+
+    let first_run = digit_run(0);
+    let mut set = if first_run >= 4 || (first_run == 2 && bytes.len() == 2) {
+        CodeSet::C
+    } else {
+        text_set(bytes[0])
+    };
+    let mut values = vec![match set {
+        CodeSet::A => START_A,
+        CodeSet::B => START_B,
+        CodeSet::C => START_C,
+    }];
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match set {
+            CodeSet::C if digit_run(i) >= 2 => {
+                values.push(((b - b'0') * 10 + bytes[i + 1] - b'0') as usize);
+                i += 2;
+            }
+            CodeSet::C => {
+                set = text_set(b);
+                values.push(if set == CodeSet::A { CODE_A } else { CODE_B });
+            }
+            _ => {
+                // Digit pairs pay for the switch when there are enough of them
+                let run = digit_run(i);
+                if run >= 6 || (run >= 4 && i + run == bytes.len()) {
+                    if run % 2 == 1 {
+                        values.push((b - 32) as usize);
+                        i += 1;
+                    }
+                    values.push(CODE_C);
+                    set = CodeSet::C;
+                    continue;
+                }
+
+                let fits = if set == CodeSet::A { b < 96 } else { b >= 32 };
+                if !fits {
+                    set = text_set(b);
+                    values.push(if set == CodeSet::A { CODE_A } else { CODE_B });
+                    continue;
+                }
+                values.push((if b < 32 { b + 64 } else { b - 32 }) as usize);
+                i += 1;
+            }
+        }
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(position, &value)| position.max(1) * value)
+        .sum::<usize>()
+        % 103;
+    values.push(checksum);
+
+    let mut modules = Vec::new();
+    for value in values {
+        push_runs(&mut modules, digit_runs(PATTERNS[value]), true);
+    }
+    push_runs(&mut modules, digit_runs(STOP), true);
+    Ok(modules)
+}
@@ -0,0 +1,50 @@
+use super::push_runs;
+
+const ALPHABET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+
+/// Wide (1) and narrow (0) elements of each character, bar first, most
+/// significant bit for the first element (ISO/IEC 16388)
+const ENCODINGS: [u16; 43] = [
+    0x034, 0x121, 0x061, 0x160, 0x031, 0x130, 0x070, 0x025, 0x124, 0x064, // 0-9
+    0x109, 0x049, 0x148, 0x019, 0x118, 0x058, 0x00D, 0x10C, 0x04C, 0x01C, // A-J
+    0x103, 0x043, 0x142, 0x013, 0x112, 0x052, 0x007, 0x106, 0x046, 0x016, // K-T
+    0x181, 0x0C1, 0x1C0, 0x091, 0x190, 0x0D0, 0x085, 0x184, 0x0C4, 0x0A8, // U-$
+    0x0A2, 0x08A, 0x02A, // /+%
+];
+
+/// The start and stop character, "*"
+const ASTERISK: u16 = 0x094;
+
+/// Wide elements are three modules, narrow ones and gaps one
+const WIDE: u8 = 3;
+
+fn value(c: char) -> Result<usize, String> {
+    ALPHABET
+        .find(c)
+        .ok_or_else(|| format!("Code 39 can't encode '{}': use digits, A-Z, space and -.$/+%", c))
+}
+
+/// Optional mod 43 check character
+pub(super) fn check_character(content: &str) -> Result<char, String> {
+    let sum = content.chars().map(value).sum::<Result<usize, String>>()?;
+    Ok(ALPHABET.as_bytes()[sum % 43] as char)
+}
+
+/// Modules of a Code 39 symbol, with the start and stop asterisks added
+pub(super) fn encode(data: &str) -> Result<Vec<bool>, String> {
+    let mut patterns = vec![ASTERISK];
+    for c in data.chars() {
+        patterns.push(ENCODINGS[value(c)?]);
+    }
+    patterns.push(ASTERISK);
+
+    let mut modules = Vec::new();
+    for (i, pattern) in patterns.into_iter().enumerate() {
+        if i > 0 {
+            modules.push(false);
+        }
+        let runs = (0..9).rev().map(|bit| if pattern >> bit & 1 == 1 { WIDE } else { 1 });
+        push_runs(&mut modules, runs, true);
+    }
+    Ok(modules)
+}
@@ -0,0 +1,36 @@
+use super::{digit_runs, push_runs};
+
+/// Run widths of each digit's "L" pattern, space first (ISO/IEC 15420)
+const L_PATTERNS: [&str; 10] = [
+    "3211", "2221", "2122", "1411", "1132", "1231", "1114", "1312", "1213", "3112",
+];
+
+/// Which of digits 2 to 7 use "G" patterns, keyed by the first digit, most
+/// significant bit for digit 2
+const FIRST_DIGIT_PARITY: [u8; 10] = [0x00, 0x0B, 0x0D, 0x0E, 0x13, 0x19, 0x1C, 0x15, 0x16, 0x1A];
+
+/// Modules of an EAN-13 symbol for 13 digits, check digit included
+pub(super) fn encode(digits: &str) -> Vec<bool> {
+    let digits: Vec<usize> = digits.bytes().map(|b| (b - b'0') as usize).collect();
+    let parity = FIRST_DIGIT_PARITY[digits[0]];
+
+    let mut modules = Vec::with_capacity(95);
+    push_runs(&mut modules, digit_runs("111"), true);
+    for (i, &digit) in digits[1..7].iter().enumerate() {
+        let runs: Vec<u8> = digit_runs(L_PATTERNS[digit]).collect();
+        // Left-hand digits start with a space; "G" patterns are "L" mirrored
+        let runs = if parity >> (5 - i) & 1 == 1 {
+            runs.into_iter().rev().collect()
+        } else {
+            runs
+        };
+        push_runs(&mut modules, runs, false);
+    }
+    push_runs(&mut modules, digit_runs("11111"), false);
+    // Right-hand digits are "L" patterns with bars and spaces swapped
+    for &digit in &digits[7..] {
+        push_runs(&mut modules, digit_runs(L_PATTERNS[digit]), true);
+    }
+    push_runs(&mut modules, digit_runs("111"), true);
+    modules
+}
@@ -0,0 +1,25 @@
+use super::{digit_runs, push_runs};
+
+/// Wide (1) and narrow (0) elements of each digit, first element in the most
+/// significant bit (ISO/IEC 16390)
+const PATTERNS: [u8; 10] = [
+    0b00110, 0b10001, 0b01001, 0b11000, 0b00101, 0b10100, 0b01100, 0b00011, 0b10010, 0b01010,
+];
+
+/// Wide elements are three modules, narrow ones one
+const WIDE: u8 = 3;
+
+/// Modules of an Interleaved 2 of 5 symbol for an even number of digits.
+/// Each pair of digits interleaves the first one's bars with the second's spaces.
+pub(super) fn encode(digits: &str) -> Vec<bool> {
+    let mut modules = Vec::new();
+    push_runs(&mut modules, digit_runs("1111"), true);
+    for pair in digits.as_bytes().chunks(2) {
+        let (bars, spaces) = (PATTERNS[(pair[0] - b'0') as usize], PATTERNS[(pair[1] - b'0') as usize]);
+        let width = |pattern: u8, bit: usize| if pattern >> (4 - bit) & 1 == 1 { WIDE } else { 1 };
+        let runs = (0..5).flat_map(|bit| [width(bars, bit), width(spaces, bit)]);
+        push_runs(&mut modules, runs, true);
+    }
+    push_runs(&mut modules, [WIDE, 1, 1], true);
+    modules
+}
@@ -0,0 +1,265 @@
+mod code128;
+mod code39;
+mod ean;
+mod itf;
+
+/// Names accepted wherever a linear barcode type can be chosen
+pub const TYPES: &[&str] = &["ean-13", "upc-a", "code128", "code39", "itf-14"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarcodeType {
+    Ean13,
+    UpcA,
+    Code128,
+    Code39,
+    Itf14,
+}
+
+impl BarcodeType {
+    /// Parse one of `TYPES`
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "ean-13" | "ean13" => Ok(Self::Ean13),
+            "upc-a" | "upca" | "upc" => Ok(Self::UpcA),
+            "code128" | "code-128" => Ok(Self::Code128),
+            "code39" | "code-39" => Ok(Self::Code39),
+            "itf-14" | "itf14" => Ok(Self::Itf14),
+            other => Err(format!("Unknown barcode type '{}'", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ean13 => "ean-13",
+            Self::UpcA => "upc-a",
+            Self::Code128 => "code128",
+            Self::Code39 => "code39",
+            Self::Itf14 => "itf-14",
+        }
+    }
+
+    /// Name for messages, e.g. "EAN-13"
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ean13 => "EAN-13",
+            Self::UpcA => "UPC-A",
+            Self::Code128 => "Code 128",
+            Self::Code39 => "Code 39",
+            Self::Itf14 => "ITF-14",
+        }
+    }
+
+    /// Light margin each side of the bars, in modules
+    pub fn quiet_zone(self) -> usize {
+        match self {
+            Self::Ean13 => 11,
+            Self::UpcA => 9,
+            Self::Code128 | Self::Code39 | Self::Itf14 => 10,
+        }
+    }
+}
+
+/// An encoded linear barcode
+#[derive(Debug, Clone, PartialEq)]
+pub struct Barcode {
+    pub kind: BarcodeType,
+    pub data: String,    // what the bars encode, including any check digit
+    pub text: String,    // human-readable line printed under the bars
+    pub bars: Vec<bool>, // one entry per module, true for a bar
+}
+
+/// Encode content as a linear barcode. EAN-13, UPC-A and ITF-14 take their
+/// digits with or without the check digit; a given check digit is verified.
+/// Code 39 gets the optional mod 43 check character when `code39_check` is set.
+pub fn encode(kind: BarcodeType, content: &str, code39_check: bool) -> Result<Barcode, String> {
+    if content.is_empty() {
+        return Err("Content is empty".to_string());
+    }
+
+    let (data, bars) = match kind {
+        BarcodeType::Ean13 => {
+            let data = complete_gtin(content, 13, kind)?;
+            let bars = ean::encode(&data);
+            (data, bars)
+        }
+        BarcodeType::UpcA => {
+            let data = complete_gtin(content, 12, kind)?;
+            // UPC-A is an EAN-13 with a leading zero
+            let bars = ean::encode(&format!("0{}", data));
+            (data, bars)
+        }
+        BarcodeType::Itf14 => {
+            let data = complete_gtin(content, 14, kind)?;
+            let bars = itf::encode(&data);
+            (data, bars)
+        }
+        BarcodeType::Code128 => (content.to_string(), code128::encode(content)?),
+        BarcodeType::Code39 => {
+            let data = if code39_check {
+                format!("{}{}", content, code39::check_character(content)?)
+            } else {
+                content.to_string()
+            };
+            let bars = code39::encode(&data)?;
+            (data, bars)
+        }
+    };
+
+    Ok(Barcode {
+        kind,
+        // Control characters have no printable form
+        text: data.chars().map(|c| if c.is_control() { ' ' } else { c }).collect(),
+        data,
+        bars,
+    })
+}
+
+/// GS1 mod 10 check digit for a run of digits that doesn't include one
+pub fn gs1_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| (d - b'0') as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+/// Append the check digit to a GS1 number one digit short of `length`, or
+/// verify the check digit of one that is `length` digits long
+pub fn complete_gtin(content: &str, length: usize, kind: BarcodeType) -> Result<String, String> {
+    let digits = content.trim();
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("{} holds digits only", kind.label()));
+    }
+
+    if digits.len() == length - 1 {
+        return Ok(format!("{}{}", digits, gs1_check_digit(digits)));
+    }
+    if digits.len() != length {
+        return Err(format!(
+            "{} needs {} digits, or {} without the check digit; got {}",
+            kind.label(),
+            length,
+            length - 1,
+            digits.len()
+        ));
+    }
+
+    let (body, check) = digits.split_at(length - 1);
+    let expected = gs1_check_digit(body);
+    if check != expected.to_string() {
+        return Err(format!(
+            "Invalid {} check digit: expected {}, got {}",
+            kind.label(),
+            expected,
+            check
+        ));
+    }
+    Ok(digits.to_string())
+}
+
+/// Append alternating bar and space runs of the given widths to `modules`
+fn push_runs(modules: &mut Vec<bool>, runs: impl IntoIterator<Item = u8>, bar_first: bool) {
+    for (i, width) in runs.into_iter().enumerate() {
+        modules.extend(std::iter::repeat_n((i % 2 == 0) == bar_first, width as usize));
+    }
+}
+
+/// Run widths written as a string of digits, e.g. "212222"
+fn digit_runs(pattern: &str) -> impl Iterator<Item = u8> + '_ {
+    pattern.bytes().map(|b| b - b'0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::ModuleMatrix;
+    use image::GrayImage;
+
+    /// Render bars at `px` pixels per module, 40 pixels high
+    fn bars_image(barcode: &Barcode, px: u32) -> GrayImage {
+        let matrix = ModuleMatrix::new(barcode.bars.len(), 1, barcode.bars.clone());
+        let image = crate::render::render_matrix_image(&matrix, barcode.kind.quiet_zone(), px);
+        image::imageops::resize(&image, image.width(), 40, image::imageops::FilterType::Nearest)
+    }
+
+    #[test]
+    fn test_parse_type_names() {
+        for name in TYPES {
+            assert_eq!(BarcodeType::parse(name).unwrap().name(), *name);
+        }
+        assert_eq!(BarcodeType::parse("EAN13").unwrap(), BarcodeType::Ean13);
+        assert!(BarcodeType::parse("codabar").is_err());
+    }
+
+    #[test]
+    fn test_gtin_check_digits() {
+        assert_eq!(complete_gtin("400638133393", 13, BarcodeType::Ean13).unwrap(), "4006381333931");
+        assert_eq!(complete_gtin("4006381333931", 13, BarcodeType::Ean13).unwrap(), "4006381333931");
+        assert_eq!(complete_gtin("03600029145", 12, BarcodeType::UpcA).unwrap(), "036000291452");
+        assert_eq!(complete_gtin("1540014128876", 14, BarcodeType::Itf14).unwrap(), "15400141288763");
+
+        let err = complete_gtin("4006381333932", 13, BarcodeType::Ean13).unwrap_err();
+        assert_eq!(err, "Invalid EAN-13 check digit: expected 1, got 2");
+        assert!(complete_gtin("40063813", 13, BarcodeType::Ean13).is_err());
+        assert!(complete_gtin("40063813339A", 13, BarcodeType::Ean13).is_err());
+    }
+
+    #[test]
+    fn test_symbol_widths() {
+        let width = |kind, content: &str| encode(kind, content, false).unwrap().bars.len();
+        assert_eq!(width(BarcodeType::Ean13, "400638133393"), 95);
+        assert_eq!(width(BarcodeType::UpcA, "03600029145"), 95);
+        // Start, 7 digit pairs at 18 modules each, stop
+        assert_eq!(width(BarcodeType::Itf14, "1540014128876"), 4 + 7 * 18 + 5);
+        // Start, two characters, check, stop
+        assert_eq!(width(BarcodeType::Code128, "Ab"), 4 * 11 + 13);
+        // Two characters and the start/stop asterisks, 16 modules each less the last gap
+        assert_eq!(width(BarcodeType::Code39, "AB"), 4 * 16 - 1);
+    }
+
+    #[test]
+    fn test_round_trip_every_type() {
+        for (kind, content, data) in [
+            (BarcodeType::Ean13, "400638133393", "4006381333931"),
+            (BarcodeType::UpcA, "036000291452", "036000291452"),
+            (BarcodeType::Itf14, "1540014128876", "15400141288763"),
+            (BarcodeType::Code128, "Order #A-1042/b", "Order #A-1042/b"),
+            (BarcodeType::Code128, "00123456789012", "00123456789012"),
+            (BarcodeType::Code128, "LOT\t2026-10", "LOT\t2026-10"),
+            (BarcodeType::Code39, "PART-42 X", "PART-42 X"),
+        ] {
+            let barcode = encode(kind, content, false).unwrap();
+            assert_eq!(barcode.data, data);
+            for px in [2, 3] {
+                assert_eq!(
+                    crate::symbology::read(&bars_image(&barcode, px)),
+                    vec![(kind.name(), data.to_string())],
+                    "{} at {}px",
+                    kind.label(),
+                    px
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_rotated_barcode() {
+        let barcode = encode(BarcodeType::Code128, "Order #A-1042/b", false).unwrap();
+        let image = bars_image(&barcode, 3);
+        for rotated in [image::imageops::rotate90(&image), image::imageops::rotate270(&image)] {
+            assert_eq!(
+                crate::symbology::read(&rotated),
+                vec![(BarcodeType::Code128.name(), "Order #A-1042/b".to_string())]
+            );
+        }
+    }
+
+    #[test]
+    fn test_code39_check_character() {
+        let barcode = encode(BarcodeType::Code39, "CODE39", true).unwrap();
+        assert_eq!(barcode.data, "CODE39W");
+        assert!(encode(BarcodeType::Code39, "lower", false).is_err());
+    }
+}
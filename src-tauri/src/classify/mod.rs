@@ -0,0 +1,429 @@
+use crate::payload;
+use serde::{Deserialize, Serialize};
+
+pub mod rules;
+
+pub use rules::*;
+
+/// Confidence for content whose structure was fully validated by its parser
+const CONFIDENCE_VALIDATED: f32 = 1.0;
+
+/// Confidence for content matched by a distinctive prefix or shape alone
+const CONFIDENCE_PREFIX: f32 = 0.9;
+
+/// Confidence for content that looks like a type but failed to parse as one
+const CONFIDENCE_MALFORMED: f32 = 0.6;
+
+/// Confidence for the plain-text fallback
+const CONFIDENCE_FALLBACK: f32 = 0.5;
+
+/// The detected type of decoded QR content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Classification {
+    pub qr_type: String,
+    pub sub_type: Option<String>,
+    pub confidence: f32, // 0.0 to 1.0
+    pub rule: Option<String>, // name of the custom rule that matched, if any
+}
+
+impl Classification {
+    fn new(qr_type: &str, sub_type: Option<&str>, confidence: f32) -> Self {
+        Self {
+            qr_type: qr_type.to_string(),
+            sub_type: sub_type.map(str::to_string),
+            confidence,
+            rule: None,
+        }
+    }
+}
+
+/// Classify content, trying custom rules in order before the built-in types
+pub fn classify(content: &str, rules: &[ClassifierRule]) -> Classification {
+    rules
+        .iter()
+        .find(|rule| rule.matches(content))
+        .map(ClassifierRule::classification)
+        .unwrap_or_else(|| classify_builtin(content))
+}
+
+/// Classify content using only the built-in types
+pub fn classify_builtin(content: &str) -> Classification {
+    let lower = content.to_lowercase();
+    let has = |prefix: &str| lower.starts_with(prefix);
+
+    let (qr_type, sub_type) = if has("wifi:") {
+        ("wifi", None)
+    } else if has("begin:vcard") {
+        ("vcard", Some("vcard"))
+    } else if has("mecard:") {
+        ("vcard", Some("mecard"))
+    } else if has("mailto:") {
+        ("email", Some("mailto"))
+    } else if has("matmsg:") {
+        ("email", Some("matmsg"))
+    } else if has("sms:") || has("smsto:") || has("mmsto:") {
+        ("sms", None)
+    } else if has("tel:") {
+        ("phone", None)
+    } else if has("geo:") {
+        ("geo", None)
+    } else if has("begin:vcalendar") || has("begin:vevent") {
+        ("calendar", None)
+    } else if has("bitcoin:") {
+        let unified = lower.contains("lightning=");
+        ("bitcoin", unified.then_some("unified"))
+    } else if has("lightning:") || has("lnurl1") {
+        let lnurl = lower.trim_start_matches("lightning:").starts_with("lnurl");
+        ("lightning", Some(if lnurl { "lnurl" } else { "bolt11" }))
+    } else if has("ln") && payload::parse_bolt11(content.trim()).is_ok() {
+        ("lightning", Some("bolt11"))
+    } else if has("ethereum:") {
+        ("ethereum", None)
+    } else if has("otpauth://totp") {
+        ("otp", Some("totp"))
+    } else if has("otpauth://") {
+        ("otp", Some("hotp"))
+    } else if has("bcd\n") || has("bcd\r\n") {
+        ("epc", None)
+    } else if has("spc\n") || has("spc\r\n") {
+        ("swiss-qr-bill", None)
+    } else if has("upi://pay") {
+        ("upi", None)
+    } else if payload::is_emv_payload(content) {
+        if lower.contains(payload::PIX_GUI) {
+            ("pix", None)
+        } else {
+            ("emvco", None)
+        }
+    } else if let Some(format) = payload::gs1_format(content) {
+        ("gs1", Some(format))
+    } else if payload::is_app_link(content) {
+        let store = payload::parse_app_link(content).map(|link| link.store).ok();
+        return classify_parsed("app-link", store.as_deref(), content);
+    } else if is_google_review(&lower) {
+        ("google-review", None)
+    } else if has("http://") || has("https://") {
+        let confidence = if url_host(&lower).is_some_and(|h| !h.is_empty()) {
+            CONFIDENCE_PREFIX
+        } else {
+            CONFIDENCE_MALFORMED
+        };
+        return Classification::new("url", url_sub_type(&lower), confidence);
+    } else {
+        return Classification::new("text", None, CONFIDENCE_FALLBACK);
+    };
+
+    classify_parsed(qr_type, sub_type, content)
+}
+
+/// Shorthand for callers that only need the type
+pub fn detect_qr_type(content: &str, rules: &[ClassifierRule]) -> String {
+    classify(content, rules).qr_type
+}
+
+/// Score a prefix match by whether the content parses as that type
+fn classify_parsed(qr_type: &str, sub_type: Option<&str>, content: &str) -> Classification {
+    let confidence = if !has_parser(qr_type) {
+        CONFIDENCE_PREFIX
+    } else if payload::parse_payload(qr_type, content).is_some() {
+        CONFIDENCE_VALIDATED
+    } else {
+        CONFIDENCE_MALFORMED
+    };
+    Classification::new(qr_type, sub_type, confidence)
+}
+
+/// Types that have a structured parser in the payload module
+fn has_parser(qr_type: &str) -> bool {
+    !matches!(
+        qr_type,
+        "phone" | "geo" | "calendar" | "google-review" | "url" | "text"
+    )
+}
+
+fn is_google_review(lower: &str) -> bool {
+    lower.starts_with("https://search.google.com/local/writereview")
+        || (url_host(lower) == Some("g.page") && lower.contains("/review"))
+}
+
+/// Host of an http(s) URL, without any "www." or "m." prefix
+fn url_host(lower: &str) -> Option<&str> {
+    let rest = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    let host = host.split(':').next().unwrap_or("");
+    Some(
+        host.strip_prefix("www.")
+            .or_else(|| host.strip_prefix("m."))
+            .unwrap_or(host),
+    )
+}
+
+/// Well-known destinations worth calling out for a URL
+fn url_sub_type(lower: &str) -> Option<&'static str> {
+    let host = url_host(lower)?;
+    let path = lower.split_once(host).map_or("", |(_, path)| path);
+    let is = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+    if is("youtube.com") || is("youtu.be") {
+        Some("youtube")
+    } else if is("wa.me") || is("whatsapp.com") {
+        Some("whatsapp")
+    } else if is("maps.app.goo.gl")
+        || host.starts_with("maps.google.")
+        || (host.starts_with("google.") && path.starts_with("/maps"))
+    {
+        Some("google-maps")
+    } else if is("instagram.com") {
+        Some("instagram")
+    } else if is("facebook.com") || is("fb.me") {
+        Some("facebook")
+    } else if is("linkedin.com") {
+        Some("linkedin")
+    } else if is("x.com") || is("twitter.com") {
+        Some("x")
+    } else if is("tiktok.com") {
+        Some("tiktok")
+    } else if is("t.me") {
+        Some("telegram")
+    } else if is("spotify.com") {
+        Some("spotify")
+    } else if is("zoom.us") {
+        Some("zoom")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qr_type(content: &str) -> String {
+        detect_qr_type(content, &[])
+    }
+
+    fn sub_type(content: &str) -> Option<String> {
+        classify(content, &[]).sub_type
+    }
+
+    #[test]
+    fn test_classify_wifi() {
+        assert_eq!(qr_type("WIFI:T:WPA;S:Network;;"), "wifi");
+        assert_eq!(qr_type("wifi:T:WEP;S:test;;"), "wifi");
+        assert_eq!(qr_type("WIFI:S:Net;P:pw;T:WPA;;"), "wifi");
+    }
+
+    #[test]
+    fn test_classify_contacts() {
+        assert_eq!(qr_type("BEGIN:VCARD\nVERSION:3.0"), "vcard");
+        assert_eq!(qr_type("begin:vcard"), "vcard");
+        assert_eq!(qr_type("MECARD:N:Doe,John;TEL:+15551234567;;"), "vcard");
+        assert_eq!(sub_type("MECARD:N:Doe,John;;"), Some("mecard".to_string()));
+    }
+
+    #[test]
+    fn test_classify_email() {
+        assert_eq!(qr_type("mailto:test@example.com"), "email");
+        assert_eq!(qr_type("MAILTO:test@example.com?subject=Hi"), "email");
+        assert_eq!(qr_type("MATMSG:TO:alice@example.com;SUB:Hi;BODY:Hello;;"), "email");
+    }
+
+    #[test]
+    fn test_classify_sms() {
+        assert_eq!(qr_type("sms:+15551234567"), "sms");
+        assert_eq!(qr_type("smsto:+15551234567"), "sms");
+        assert_eq!(qr_type("SMS:+15551234567?body=Hello"), "sms");
+        assert_eq!(qr_type("SMSTO:+15551234567:Hello: there"), "sms");
+        assert_eq!(qr_type("MMSTO:+15551234567:Hello"), "sms");
+    }
+
+    #[test]
+    fn test_classify_phone_geo_calendar() {
+        assert_eq!(qr_type("tel:+15551234567"), "phone");
+        assert_eq!(qr_type("TEL:5551234567"), "phone");
+        assert_eq!(qr_type("geo:37.7749,-122.4194"), "geo");
+        assert_eq!(qr_type("GEO:0,0"), "geo");
+        assert_eq!(qr_type("BEGIN:VCALENDAR\r\nVERSION:2.0"), "calendar");
+        assert_eq!(qr_type("BEGIN:VEVENT\nSUMMARY:Meeting"), "calendar");
+    }
+
+    #[test]
+    fn test_classify_crypto() {
+        let invoice = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+
+        assert_eq!(qr_type("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"), "bitcoin");
+        assert_eq!(qr_type("BITCOIN:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=0.5"), "bitcoin");
+        // A BIP21 URI with a Lightning fallback is still a bitcoin payment
+        assert_eq!(qr_type(&format!("bitcoin:?lightning={}", invoice)), "bitcoin");
+        assert_eq!(sub_type(&format!("bitcoin:?lightning={}", invoice)), Some("unified".to_string()));
+
+        assert_eq!(qr_type(invoice), "lightning");
+        assert_eq!(qr_type(&format!("LIGHTNING:{}", invoice.to_uppercase())), "lightning");
+        assert_eq!(sub_type(invoice), Some("bolt11".to_string()));
+        let lnurl = "LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS";
+        assert_eq!(qr_type(lnurl), "lightning");
+        assert_eq!(sub_type(&format!("lightning:{}", lnurl)), Some("lnurl".to_string()));
+        // Words that merely start with "ln" stay text
+        assert_eq!(qr_type("lnbc is not an invoice"), "text");
+
+        assert_eq!(qr_type("ethereum:0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"), "ethereum");
+        assert_eq!(qr_type("ETHEREUM:pay-alice.eth@1?value=1e18"), "ethereum");
+    }
+
+    #[test]
+    fn test_classify_otp() {
+        assert_eq!(qr_type("otpauth://totp/ACME:alice?secret=JBSWY3DPEHPK3PXP"), "otp");
+        assert_eq!(qr_type("OTPAUTH://HOTP/alice?secret=JBSWY3DPEHPK3PXP&counter=0"), "otp");
+        assert_eq!(sub_type("OTPAUTH://HOTP/alice?secret=JBSWY3DPEHPK3PXP&counter=0"), Some("hotp".to_string()));
+    }
+
+    #[test]
+    fn test_classify_bank_payments() {
+        assert_eq!(qr_type("BCD\n002\n1\nSCT\n\nName\nDE89370400440532013000"), "epc");
+        assert_eq!(qr_type("bcd\r\n001\r\n1\r\nSCT"), "epc");
+        // "BCD" on its own is just text
+        assert_eq!(qr_type("BCD"), "text");
+
+        assert_eq!(qr_type("SPC\n0200\n1\nCH4431999123000889012"), "swiss-qr-bill");
+        assert_eq!(qr_type("spc\r\n0200\r\n1"), "swiss-qr-bill");
+        assert_eq!(qr_type("SPCA"), "text");
+    }
+
+    #[test]
+    fn test_classify_merchant_payments() {
+        assert_eq!(qr_type("upi://pay?pa=shop@ybl&pn=Shop"), "upi");
+        assert_eq!(qr_type("UPI://PAY?pa=shop@ybl&pn=Shop"), "upi");
+        assert_eq!(
+            qr_type("00020126580014br.gov.bcb.pix0136123e4567-e12b-12d1-a456-4266554400005204000053039865802BR5913Fulano de Tal6008BRASILIA62070503***63041D3D"),
+            "pix"
+        );
+        assert_eq!(
+            qr_type("00020101021126280009sg.paynow01011020912345678952040000530370254041.005802SG5904Shop6009Singapore6304ABCD"),
+            "emvco"
+        );
+        assert_eq!(qr_type("000201"), "text");
+    }
+
+    #[test]
+    fn test_classify_gs1() {
+        assert_eq!(qr_type("https://id.gs1.org/01/09506000134352/10/ABC123?17=261231"), "gs1");
+        assert_eq!(sub_type("https://brand.example/01/09506000134352"), Some("digital-link".to_string()));
+        assert_eq!(sub_type("0109506000134352\u{1d}10ABC123"), Some("element-string".to_string()));
+        assert_eq!(qr_type("(01)09506000134352(17)261231"), "gs1");
+        // A wrong check digit or a path that only looks similar is left alone
+        assert_eq!(qr_type("https://id.gs1.org/01/09506000134353"), "url");
+        assert_eq!(qr_type("https://example.com/01/2024/report"), "url");
+        assert_eq!(qr_type("4006381333931"), "text");
+    }
+
+    #[test]
+    fn test_classify_app_links() {
+        assert_eq!(qr_type("market://details?id=com.example.app"), "app-link");
+        assert_eq!(qr_type("itms-apps://itunes.apple.com/app/id310633997"), "app-link");
+        assert_eq!(qr_type("https://apps.apple.com/us/app/x/id310633997"), "app-link");
+        assert_eq!(qr_type("https://play.google.com/store/apps/details?id=com.example"), "app-link");
+        assert_eq!(sub_type("market://details?id=com.example.app"), Some("google-play".to_string()));
+        assert_eq!(sub_type("https://apps.apple.com/us/app/x/id310633997"), Some("app-store".to_string()));
+    }
+
+    #[test]
+    fn test_classify_google_review() {
+        assert_eq!(
+            qr_type("https://search.google.com/local/writereview?placeid=ChIJN1t_tDeuEmsRUsoyG83frY4"),
+            "google-review"
+        );
+        // case-insensitive prefix match
+        assert_eq!(qr_type("HTTPS://SEARCH.GOOGLE.COM/local/writereview?placeid=abc"), "google-review");
+        assert_eq!(qr_type("https://g.page/r/CabcDEF123/review"), "google-review");
+    }
+
+    #[test]
+    fn test_classify_url() {
+        assert_eq!(qr_type("https://example.com"), "url");
+        assert_eq!(qr_type("http://example.com/path"), "url");
+        assert_eq!(qr_type("HTTP://EXAMPLE.COM"), "url");
+        assert_eq!(sub_type("https://example.com"), None);
+    }
+
+    #[test]
+    fn test_classify_url_sub_types() {
+        assert_eq!(sub_type("https://www.youtube.com/watch?v=dQw4w9WgXcQ"), Some("youtube".to_string()));
+        assert_eq!(sub_type("https://youtu.be/dQw4w9WgXcQ"), Some("youtube".to_string()));
+        assert_eq!(sub_type("https://wa.me/15551234567?text=Hi"), Some("whatsapp".to_string()));
+        assert_eq!(sub_type("https://chat.whatsapp.com/AbCdEf"), Some("whatsapp".to_string()));
+        assert_eq!(sub_type("https://www.google.com/maps/place/Eiffel+Tower"), Some("google-maps".to_string()));
+        assert_eq!(sub_type("https://maps.app.goo.gl/abc"), Some("google-maps".to_string()));
+        assert_eq!(sub_type("https://m.facebook.com/acme"), Some("facebook".to_string()));
+        assert_eq!(sub_type("https://t.me/acme"), Some("telegram".to_string()));
+        // Lookalike domains don't count
+        assert_eq!(sub_type("https://notyoutube.com/watch"), None);
+        assert_eq!(sub_type("https://youtube.com.evil.example/"), None);
+    }
+
+    #[test]
+    fn test_classify_text() {
+        assert_eq!(qr_type("Hello, World!"), "text");
+        assert_eq!(qr_type("Just some text"), "text");
+        assert_eq!(qr_type("12345"), "text");
+    }
+
+    #[test]
+    fn test_classify_case_insensitive() {
+        assert_eq!(qr_type("WIFI:T:WPA;;"), "wifi");
+        assert_eq!(qr_type("wifi:t:wpa;;"), "wifi");
+        assert_eq!(qr_type("MAILTO:test@example.com"), "email");
+        assert_eq!(qr_type("TEL:+15551234567"), "phone");
+        assert_eq!(qr_type("GEO:0,0"), "geo");
+        assert_eq!(qr_type("HTTPS://EXAMPLE.COM"), "url");
+    }
+
+    #[test]
+    fn test_classify_confidence() {
+        // Parsed and validated
+        assert_eq!(classify("WIFI:S:Net;T:WPA;P:pw;;", &[]).confidence, CONFIDENCE_VALIDATED);
+        // Right prefix, broken body
+        assert_eq!(classify("BCD\n009\n1\nSCT", &[]).confidence, CONFIDENCE_MALFORMED);
+        assert_eq!(classify("WIFI:T:WPA;;", &[]).confidence, CONFIDENCE_MALFORMED);
+        // No parser to check against
+        assert_eq!(classify("tel:+15551234567", &[]).confidence, CONFIDENCE_PREFIX);
+        assert_eq!(classify("https://example.com", &[]).confidence, CONFIDENCE_PREFIX);
+        assert_eq!(classify("https://", &[]).confidence, CONFIDENCE_MALFORMED);
+        assert_eq!(classify("hello", &[]).confidence, CONFIDENCE_FALLBACK);
+    }
+
+    #[test]
+    fn test_classify_custom_rules_take_precedence() {
+        let rules = vec![
+            ClassifierRule {
+                name: "ACME app".to_string(),
+                prefix: "myapp://".to_string(),
+                qr_type: "app-link".to_string(),
+                sub_type: Some("myapp".to_string()),
+                confidence: 0.8,
+            },
+            ClassifierRule {
+                name: "Intranet".to_string(),
+                prefix: "https://intranet.acme.com/".to_string(),
+                qr_type: "url".to_string(),
+                sub_type: Some("intranet".to_string()),
+                confidence: 1.0,
+            },
+        ];
+
+        let result = classify("MYAPP://open/42", &rules);
+        assert_eq!(result.qr_type, "app-link");
+        assert_eq!(result.sub_type, Some("myapp".to_string()));
+        assert_eq!(result.confidence, 0.8);
+        assert_eq!(result.rule, Some("ACME app".to_string()));
+
+        assert_eq!(classify("https://intranet.acme.com/wiki", &rules).sub_type, Some("intranet".to_string()));
+        // Built-in classification still applies when no rule matches
+        let result = classify("https://example.com", &rules);
+        assert_eq!(result.qr_type, "url");
+        assert_eq!(result.rule, None);
+    }
+}
@@ -0,0 +1,173 @@
+use super::Classification;
+use crate::db;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// Settings key holding the user's custom rules as JSON
+pub const RULES_SETTING_KEY: &str = "classifier_rules";
+
+/// A user-defined rule mapping a content prefix to a type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassifierRule {
+    #[serde(default)]
+    pub name: String,
+    pub prefix: String, // matched case-insensitively, e.g. "myapp://"
+    pub qr_type: String,
+    #[serde(default)]
+    pub sub_type: Option<String>,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+fn default_confidence() -> f32 {
+    0.9
+}
+
+impl ClassifierRule {
+    pub fn matches(&self, content: &str) -> bool {
+        content
+            .get(..self.prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(&self.prefix))
+    }
+
+    pub fn classification(&self) -> Classification {
+        Classification {
+            qr_type: self.qr_type.clone(),
+            sub_type: self.sub_type.clone(),
+            confidence: self.confidence,
+            rule: Some(self.name.clone()).filter(|n| !n.is_empty()),
+        }
+    }
+}
+
+/// Check rules before they are saved
+pub fn validate_rules(rules: &[ClassifierRule]) -> Result<(), String> {
+    for (i, rule) in rules.iter().enumerate() {
+        let label = if rule.name.is_empty() {
+            format!("Rule {}", i + 1)
+        } else {
+            format!("Rule '{}'", rule.name)
+        };
+
+        if rule.prefix.trim().is_empty() {
+            return Err(format!("{} has no prefix", label));
+        }
+        if rule.qr_type.is_empty()
+            || !rule
+                .qr_type
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        {
+            return Err(format!(
+                "{} has an invalid type '{}' (use lowercase letters, digits and '-')",
+                label, rule.qr_type
+            ));
+        }
+        if !(0.0..=1.0).contains(&rule.confidence) {
+            return Err(format!("{} confidence must be between 0 and 1", label));
+        }
+    }
+    Ok(())
+}
+
+/// Load custom rules from settings. Stored rules that can't be read or fail
+/// validation are an error, so they aren't dropped without the user knowing.
+pub fn load_rules(conn: &Connection) -> Result<Vec<ClassifierRule>, String> {
+    let Some(json) = db::get_setting(conn, RULES_SETTING_KEY)
+        .map_err(|e| format!("Failed to load classifier rules: {}", e))?
+    else {
+        return Ok(Vec::new());
+    };
+    let rules: Vec<ClassifierRule> = serde_json::from_str(&json)
+        .map_err(|e| format!("Saved classifier rules are not readable: {}", e))?;
+    validate_rules(&rules).map_err(|e| format!("Saved classifier rules are invalid: {}", e))?;
+    Ok(rules)
+}
+
+/// Validate and store custom rules in settings
+pub fn save_rules(conn: &Connection, rules: &[ClassifierRule]) -> Result<(), String> {
+    validate_rules(rules)?;
+    let json = serde_json::to_string(rules).map_err(|e| e.to_string())?;
+    db::set_setting(conn, RULES_SETTING_KEY, &json)
+        .map_err(|e| format!("Failed to save classifier rules: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);")
+            .unwrap();
+        conn
+    }
+
+    fn rule(prefix: &str, qr_type: &str) -> ClassifierRule {
+        ClassifierRule {
+            name: String::new(),
+            prefix: prefix.to_string(),
+            qr_type: qr_type.to_string(),
+            sub_type: None,
+            confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_rule_matches_prefix_case_insensitively() {
+        let r = rule("myapp://", "app-link");
+        assert!(r.matches("MyApp://open"));
+        assert!(!r.matches("myapp:/"));
+        assert!(!r.matches("xmyapp://"));
+        // Multi-byte content shorter than or straddling the prefix length
+        assert!(!r.matches("caf\u{e9}\u{e9}\u{e9}"));
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        assert!(validate_rules(&[rule("myapp://", "app-link")]).is_ok());
+        assert!(validate_rules(&[rule("  ", "app-link")]).is_err());
+        assert!(validate_rules(&[rule("x", "App Link")]).is_err());
+        let mut r = rule("x", "custom");
+        r.confidence = 1.5;
+        assert!(validate_rules(&[r]).is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_rules() {
+        let conn = setup_test_db();
+        assert!(load_rules(&conn).unwrap().is_empty());
+
+        let rules = vec![rule("myapp://", "app-link")];
+        save_rules(&conn, &rules).unwrap();
+        assert_eq!(load_rules(&conn).unwrap(), rules);
+
+        assert!(save_rules(&conn, &[rule("", "x")]).is_err());
+        assert_eq!(load_rules(&conn).unwrap(), rules);
+    }
+
+    #[test]
+    fn test_load_rules_reports_bad_rules() {
+        let conn = setup_test_db();
+        db::set_setting(&conn, RULES_SETTING_KEY, "not json").unwrap();
+        let err = load_rules(&conn).unwrap_err();
+        assert!(err.starts_with("Saved classifier rules are not readable: "), "{}", err);
+
+        // One invalid rule is reported, not silently dropped with the rest
+        db::set_setting(
+            &conn,
+            RULES_SETTING_KEY,
+            r#"[{"prefix":"acme:","qrType":"acme"},{"name":"Broken","prefix":"x:","qrType":"Not Valid"}]"#,
+        )
+        .unwrap();
+        let err = load_rules(&conn).unwrap_err();
+        assert!(err.starts_with("Saved classifier rules are invalid: Rule 'Broken'"), "{}", err);
+
+        // Hand-edited rules may omit the optional fields
+        db::set_setting(&conn, RULES_SETTING_KEY, r#"[{"prefix":"acme:","qrType":"acme"}]"#).unwrap();
+        let rules = load_rules(&conn).unwrap();
+        assert_eq!(rules[0].confidence, 0.9);
+        assert_eq!(rules[0].sub_type, None);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::barcode::{self, Barcode, BarcodeType};
+use crate::commands::ExportResult;
+use crate::render::{self, BarLayout};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri_plugin_dialog::DialogExt;
+
+/// Default print resolution for barcode PNGs
+const DEFAULT_DPI: u32 = 300;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BarcodeOptions {
+    pub bar_width: Option<f64>,    // narrowest bar in mm, default 0.33
+    pub height: Option<f64>,       // bar height in mm, default 22.85
+    pub show_text: Option<bool>,   // human-readable line under the bars, default true
+    pub check_digit: Option<bool>, // Code 39 only: append the optional mod 43 check character
+    pub dpi: Option<u32>,          // PNG only, default 300
+}
+
+impl BarcodeOptions {
+    fn layout(&self) -> Result<BarLayout, String> {
+        let layout = BarLayout {
+            bar_width_mm: self.bar_width.unwrap_or(render::DEFAULT_BAR_WIDTH_MM),
+            height_mm: self.height.unwrap_or(render::DEFAULT_BAR_HEIGHT_MM),
+            show_text: self.show_text.unwrap_or(true),
+        };
+        layout.validate()?;
+        Ok(layout)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedBarcode {
+    pub image: String, // PNG, SVG or PDF data URL
+    pub data: String,  // encoded data, check digit included
+    pub text: String,  // human-readable line
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BarcodeCheck {
+    pub valid: bool,
+    pub data: Option<String>, // content with its check digit, when valid
+    pub error: Option<String>,
+}
+
+/// Encode content and render it as "png", "svg" or "pdf", returning the
+/// barcode, the file bytes and the file extension
+pub(crate) fn barcode_file(
+    content: &str,
+    barcode_type: &str,
+    format: &str,
+    options: &BarcodeOptions,
+) -> Result<(Barcode, Vec<u8>, &'static str), String> {
+    let kind = BarcodeType::parse(barcode_type)?;
+    let layout = options.layout()?;
+    let barcode = barcode::encode(kind, content, options.check_digit.unwrap_or(false))?;
+
+    let (bytes, extension) = match format {
+        "svg" => (render::render_barcode_svg(&barcode, &layout).into_bytes(), "svg"),
+        "pdf" => (render::render_barcode_pdf(&barcode, &layout), "pdf"),
+        "png" => (
+            render::render_barcode_png(&barcode, &layout, options.dpi.unwrap_or(DEFAULT_DPI))?,
+            "png",
+        ),
+        other => return Err(format!("Unknown barcode file format '{}', expected png, svg or pdf", other)),
+    };
+    Ok((barcode, bytes, extension))
+}
+
+/// Render a linear barcode (EAN-13, UPC-A, Code 128, Code 39 or ITF-14) as a
+/// PNG, SVG or PDF data URL. GS1 numbers may leave out the check digit.
+#[tauri::command]
+pub async fn render_barcode(
+    content: String,
+    barcode_type: String,
+    format: String,
+    options: Option<BarcodeOptions>,
+) -> Result<RenderedBarcode, String> {
+    let (barcode, bytes, extension) =
+        barcode_file(&content, &barcode_type, &format, &options.unwrap_or_default())?;
+    let mime = match extension {
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ => "image/png",
+    };
+
+    Ok(RenderedBarcode {
+        image: format!("data:{};base64,{}", mime, STANDARD.encode(bytes)),
+        data: barcode.data,
+        text: barcode.text,
+    })
+}
+
+/// Save a linear barcode as PNG, SVG or PDF using native file dialog
+#[tauri::command]
+pub async fn export_barcode(
+    app: tauri::AppHandle,
+    content: String,
+    barcode_type: String,
+    format: String,
+    options: Option<BarcodeOptions>,
+    suggested_name: Option<String>,
+) -> Result<ExportResult, String> {
+    // Render before showing the dialog so invalid content fails fast
+    let (_, file_bytes, extension) =
+        barcode_file(&content, &barcode_type, &format, &options.unwrap_or_default())?;
+    let filter_name = match extension {
+        "svg" => "SVG Image",
+        "pdf" => "PDF Document",
+        _ => "PNG Image",
+    };
+
+    let file_name = suggested_name.unwrap_or_else(|| format!("barcode.{}", extension));
+
+    // Show save dialog
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(&file_name)
+        .add_filter(filter_name, &[extension])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_buf = path.as_path().unwrap().to_path_buf();
+            fs::write(&path_buf, &file_bytes)
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+
+            Ok(ExportResult {
+                success: true,
+                path: Some(path_buf.to_string_lossy().to_string()),
+                error: None,
+            })
+        }
+        None => Ok(ExportResult {
+            success: false,
+            path: None,
+            error: Some("Save cancelled by user".to_string()),
+        }),
+    }
+}
+
+/// Check that content can be encoded as a barcode type, completing or
+/// verifying GS1 check digits
+#[tauri::command]
+pub async fn check_barcode(
+    content: String,
+    barcode_type: String,
+    check_digit: Option<bool>,
+) -> Result<BarcodeCheck, String> {
+    let kind = BarcodeType::parse(&barcode_type)?;
+    Ok(match barcode::encode(kind, &content, check_digit.unwrap_or(false)) {
+        Ok(barcode) => BarcodeCheck {
+            valid: true,
+            data: Some(barcode.data),
+            error: None,
+        },
+        Err(e) => BarcodeCheck {
+            valid: false,
+            data: None,
+            error: Some(e),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_barcode_file_formats() {
+        let options = BarcodeOptions::default();
+        for format in ["png", "svg", "pdf"] {
+            let (_, _, extension) = barcode_file("400638133393", "ean-13", format, &options).unwrap();
+            assert_eq!(extension, format);
+        }
+        let err = barcode_file("400638133393", "ean-13", "jpg", &options).unwrap_err();
+        assert_eq!(err, "Unknown barcode file format 'jpg', expected png, svg or pdf");
+    }
+}
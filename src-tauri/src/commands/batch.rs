@@ -1,9 +1,19 @@
+use crate::barcode::{self, BarcodeType};
+use crate::classify::{self, ClassifierRule};
+use crate::commands::barcode::{barcode_file, BarcodeOptions};
+use crate::commands::symbology::{matrix_file, png_module_size};
+use crate::db::DbState;
+use crate::payload;
+use crate::qr::QrOptions;
+use crate::render::{self, RenderTarget};
+use crate::symbology::{self, EncodeOptions, Symbology};
+use crate::urls::{self, UrlOptions};
 use base64::{engine::general_purpose::STANDARD, Engine};
-use image::ImageReader;
-use rqrr::PreparedImage;
+use image::{DynamicImage, ImageReader};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::{Cursor, Write};
+use tauri::State;
 use tauri_plugin_dialog::DialogExt;
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
@@ -15,6 +25,9 @@ pub struct BatchItem {
     pub content: String,
     pub qr_type: String,
     pub label: Option<String>,
+    pub original_content: Option<String>, // set when URL options rewrote the content
+    pub barcode_type: Option<String>,     // barcode::TYPES, from the "barcode" column
+    pub barcode_data: Option<String>,     // "barcode_data" column or the content, check digit included
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +46,25 @@ pub struct BatchGenerateItem {
     pub content: String,
     pub label: Option<String>,
     pub image_data: String, // base64 PNG
+    #[serde(default)]
+    pub symbology: Option<String>, // what image_data was rendered as; "qr" when absent
+    #[serde(default)]
+    pub barcode_type: Option<String>, // linear barcode rendered alongside, see barcode::TYPES
+    #[serde(default)]
+    pub barcode_data: Option<String>,
+    #[serde(default)]
+    pub style_json: Option<String>, // style the row was designed with, part of its render fingerprint
+    // Encoder options for the ZIP, which renders every code itself; see render_symbol
+    #[serde(default)]
+    pub ec_level: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub gs1: Option<bool>,
+    #[serde(default)]
+    pub qr_options: Option<QrOptions>,
+    #[serde(default)]
+    pub module_size: Option<u32>, // PNG pixels per module
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,20 +97,40 @@ pub struct BatchSaveFilesResult {
 
 /// Parse a CSV file and return batch items
 #[tauri::command]
-pub async fn batch_parse_csv(file_path: String) -> Result<BatchParseResult, String> {
+pub async fn batch_parse_csv(
+    state: State<'_, DbState>,
+    file_path: String,
+    url_options: Option<UrlOptions>,
+) -> Result<BatchParseResult, String> {
     let content =
         fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
 
-    parse_csv_content(&content)
+    parse_csv_content(&content, &load_classifier_rules(&state)?, url_options.as_ref())
 }
 
 /// Parse CSV content from string
 #[tauri::command]
-pub async fn batch_parse_csv_content(content: String) -> Result<BatchParseResult, String> {
-    parse_csv_content(&content)
+pub async fn batch_parse_csv_content(
+    state: State<'_, DbState>,
+    content: String,
+    url_options: Option<UrlOptions>,
+) -> Result<BatchParseResult, String> {
+    parse_csv_content(&content, &load_classifier_rules(&state)?, url_options.as_ref())
 }
 
-fn parse_csv_content(content: &str) -> Result<BatchParseResult, String> {
+/// Custom classifier rules from settings, used when the CSV has no type column
+fn load_classifier_rules(state: &State<'_, DbState>) -> Result<Vec<ClassifierRule>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    classify::load_rules(&conn)
+}
+
+fn parse_csv_content(
+    content: &str,
+    rules: &[ClassifierRule],
+    url_options: Option<&UrlOptions>,
+) -> Result<BatchParseResult, String> {
+    let url_options = url_options.filter(|o| o.is_active());
+
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(true)
         .flexible(true)
@@ -97,6 +149,8 @@ fn parse_csv_content(content: &str) -> Result<BatchParseResult, String> {
 
     let type_idx = headers.iter().position(|h| h.to_lowercase() == "type");
     let label_idx = headers.iter().position(|h| h.to_lowercase() == "label");
+    let barcode_idx = headers.iter().position(|h| h.to_lowercase() == "barcode");
+    let barcode_data_idx = headers.iter().position(|h| h.to_lowercase() == "barcode_data");
 
     let mut items = Vec::new();
 
@@ -112,18 +166,78 @@ fn parse_csv_content(content: &str) -> Result<BatchParseResult, String> {
                 let qr_type = type_idx
                     .and_then(|i| record.get(i))
                     .map(|s| s.trim().to_lowercase())
-                    .unwrap_or_else(|| detect_qr_type(&content));
+                    .unwrap_or_else(|| classify::detect_qr_type(&content, rules));
 
                 let label = label_idx
                     .and_then(|i| record.get(i))
                     .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty());
 
+                let barcode_type = barcode_idx
+                    .and_then(|i| record.get(i))
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty());
+                let (barcode_type, barcode_data) = match barcode_type {
+                    Some(name) => {
+                        let data = barcode_data_idx
+                            .and_then(|i| record.get(i))
+                            .map(|s| s.trim())
+                            .filter(|s| !s.is_empty())
+                            .unwrap_or(&content);
+                        let encoded = BarcodeType::parse(name)
+                            .and_then(|kind| barcode::encode(kind, data, false));
+                        match encoded {
+                            Ok(barcode) => (Some(barcode.kind.name().to_string()), Some(barcode.data)),
+                            Err(e) => {
+                                return Ok(BatchParseResult {
+                                    success: false,
+                                    items: vec![],
+                                    error: Some(format!("Error at row {}: {}", row_idx + 2, e)),
+                                    total_rows: 0,
+                                });
+                            }
+                        }
+                    }
+                    None => (None, None),
+                };
+
+                let (content, original_content) = match url_options {
+                    Some(options) if urls::is_web_url(&content) => {
+                        let column = |name: &str| {
+                            headers
+                                .iter()
+                                .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+                                .and_then(|i| record.get(i))
+                                .map(|v| v.to_string())
+                        };
+                        let rewritten = match urls::process_url(&content, options, column) {
+                            Ok(rewritten) => rewritten,
+                            Err(e) => {
+                                return Ok(BatchParseResult {
+                                    success: false,
+                                    items: vec![],
+                                    error: Some(format!("Error at row {}: {}", row_idx + 2, e)),
+                                    total_rows: 0,
+                                });
+                            }
+                        };
+                        if rewritten == content {
+                            (content, None)
+                        } else {
+                            (rewritten, Some(content))
+                        }
+                    }
+                    _ => (content, None),
+                };
+
                 items.push(BatchItem {
                     row: row_idx + 1, // 1-indexed for display
                     content,
                     qr_type,
                     label,
+                    original_content,
+                    barcode_type,
+                    barcode_data,
                 });
             }
             Err(e) => {
@@ -183,7 +297,7 @@ fn validate_single_item(item: &BatchGenerateItem) -> BatchValidationResult {
     // Load image
     let img = match ImageReader::new(Cursor::new(&image_bytes))
         .with_guessed_format()
-        .and_then(|r| r.decode().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)))
+        .and_then(|r| r.decode().map_err(std::io::Error::other))
     {
         Ok(img) => img,
         Err(e) => {
@@ -197,56 +311,98 @@ fn validate_single_item(item: &BatchGenerateItem) -> BatchValidationResult {
         }
     };
 
-    // Convert to grayscale and decode QR
-    let gray_img = img.to_luma8();
-    let mut prepared = PreparedImage::prepare(gray_img);
-    let grids = prepared.detect_grids();
+    validate_item_image(item, &img)
+}
 
-    if grids.is_empty() {
-        return BatchValidationResult {
+/// Scan an image of a row's code and check it against the row
+fn validate_item_image(item: &BatchGenerateItem, img: &DynamicImage) -> BatchValidationResult {
+    let (expected, label) = match symbology::lookup(item.symbology.as_deref().unwrap_or("qr")) {
+        Ok(expected) => expected,
+        Err(e) => {
+            return BatchValidationResult {
+                row: item.row,
+                success: false,
+                decoded_content: None,
+                content_match: false,
+                error: Some(e),
+            };
+        }
+    };
+
+    // Convert to grayscale and decode
+    let gray = img.to_luma8();
+    match symbology::read_first(&gray) {
+        None => BatchValidationResult {
             row: item.row,
             success: false,
             decoded_content: None,
             content_match: false,
-            error: Some("No QR code detected".to_string()),
-        };
-    }
-
-    match grids[0].decode() {
-        Ok((_, content)) => {
-            let decoded = content.to_string();
-            let content_match = decoded.trim() == item.content.trim();
+            error: Some(format!("No {} detected", label)),
+        },
+        Some((found, Ok(decoded))) => {
+            let content_match = decoded.trim() == item.content.trim()
+                || urls::same_url(&decoded, &item.content);
+            let error = if !content_match {
+                Some("Content mismatch".to_string())
+            } else if found != expected {
+                let found = symbology::lookup(found).map_or(found, |(_, label)| label);
+                Some(format!("Expected {} but found {}", label, found))
+            } else {
+                ec_level_error(&decoded, &gray)
+            };
 
             BatchValidationResult {
                 row: item.row,
-                success: content_match,
+                success: error.is_none(),
                 decoded_content: Some(decoded),
                 content_match,
-                error: if content_match {
-                    None
-                } else {
-                    Some("Content mismatch".to_string())
-                },
+                error,
             }
         }
-        Err(e) => BatchValidationResult {
+        Some((_, Err(e))) => BatchValidationResult {
             row: item.row,
             success: false,
             decoded_content: None,
             content_match: false,
-            error: Some(format!("Decode error: {:?}", e)),
+            error: Some(format!("Decode error: {}", e)),
         },
     }
 }
 
-/// Generate a ZIP file containing all QR codes
+/// Why a QR code of a type that mandates an error correction level (EPC
+/// needs M) doesn't meet it, if it doesn't
+fn ec_level_error(content: &str, gray: &image::GrayImage) -> Option<String> {
+    let qr_type = classify::classify_builtin(content).qr_type;
+    let required = payload::required_error_correction(&qr_type)?;
+    match symbology::read_qr_ec_level(gray).map(render::ec_level_name) {
+        Some(level) if level == required => None,
+        Some(level) => Some(format!(
+            "{} codes must use error correction level {}, found {}",
+            qr_type.to_uppercase(),
+            required,
+            level
+        )),
+        None => Some(format!(
+            "Could not read the error correction level of the {} code",
+            qr_type.to_uppercase()
+        )),
+    }
+}
+
+/// Generate a ZIP file containing all codes, plus a barcode file for each
+/// row that asks for one and a `manifest.csv` listing every file with its
+/// render fingerprint. Codes are rendered here from each row's options rather
+/// than taken from `image_data`, and validation scans those renders. Entries
+/// carry a fixed timestamp, so the same items always give the same archive.
 #[tauri::command]
 pub async fn batch_generate_zip(
     app: tauri::AppHandle,
     items: Vec<BatchGenerateItem>,
     format: String,
     validate: bool,
+    barcode_options: Option<BarcodeOptions>,
 ) -> Result<BatchGenerateResult, String> {
+    let barcode_options = barcode_options.unwrap_or_default();
     // Determine file extension
     let extension = if format == "svg" { "svg" } else { "png" };
 
@@ -275,50 +431,57 @@ pub async fn batch_generate_zip(
     let mut zip = ZipWriter::new(file);
     let options = SimpleFileOptions::default()
         .compression_method(zip::CompressionMethod::Deflated)
-        .compression_level(Some(6));
+        .compression_level(Some(6))
+        .last_modified_time(zip::DateTime::default());
 
     let mut validation_results = Vec::new();
+    let mut manifest = Vec::new();
 
     for item in &items {
+        let rendered = render_item(item, extension)
+            .map_err(|e| format!("Failed to render row {}: {}", item.row, e))?;
+
         // Validate if requested
         if validate {
-            let validation = validate_single_item(item);
-            validation_results.push(validation);
+            validation_results.push(validate_item_image(item, &rendered.image));
         }
-
-        // Strip data URL prefix
-        let base64_data = if item.image_data.contains(",") {
-            item.image_data.split(",").nth(1).unwrap_or(&item.image_data)
-        } else {
-            &item.image_data
-        };
-
-        // Decode image data (for SVG, this is the base64-encoded SVG content)
-        let file_bytes = if format == "svg" {
-            // SVG data URL is base64-encoded SVG XML
-            STANDARD
-                .decode(base64_data)
-                .map_err(|e| format!("Failed to decode SVG for row {}: {}", item.row, e))?
-        } else {
-            STANDARD
-                .decode(base64_data)
-                .map_err(|e| format!("Failed to decode image for row {}: {}", item.row, e))?
-        };
+        let file_bytes = rendered.file;
 
         // Generate filename
-        let filename = if let Some(label) = &item.label {
-            format!("{:03}_{}.{}", item.row, sanitize_filename(label), extension)
-        } else {
-            format!("{:03}_qr.{}", item.row, extension)
-        };
+        let stem = item
+            .label
+            .as_deref()
+            .unwrap_or_else(|| item.symbology.as_deref().unwrap_or("qr"));
+        let filename = format!("{:03}_{}.{}", item.row, sanitize_filename(stem), extension);
 
         // Add to ZIP
         zip.start_file(&filename, options)
             .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
         zip.write_all(&file_bytes)
             .map_err(|e| format!("Failed to write to ZIP: {}", e))?;
+
+        let mut barcode_file = None;
+        if let Some((barcode_type, barcode_bytes)) = row_barcode(item, extension, &barcode_options)? {
+            let filename = format!("{:03}_{}_{}.{}", item.row, sanitize_filename(stem), barcode_type, extension);
+            zip.start_file(&filename, options)
+                .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+            zip.write_all(&barcode_bytes)
+                .map_err(|e| format!("Failed to write to ZIP: {}", e))?;
+            barcode_file = Some(filename);
+        }
+        manifest.push(ManifestEntry {
+            item,
+            file: filename,
+            barcode_file,
+            target: rendered.target,
+        });
     }
 
+    zip.start_file("manifest.csv", options)
+        .map_err(|e| format!("Failed to add file to ZIP: {}", e))?;
+    zip.write_all(&manifest_csv(&manifest)?)
+        .map_err(|e| format!("Failed to write to ZIP: {}", e))?;
+
     zip.finish()
         .map_err(|e| format!("Failed to finalize ZIP: {}", e))?;
 
@@ -330,6 +493,86 @@ pub async fn batch_generate_zip(
     })
 }
 
+/// A row's code as rendered for the ZIP
+struct RenderedItem {
+    file: Vec<u8>,
+    image: DynamicImage, // what validation scans, also for SVG files
+    target: RenderTarget,
+}
+
+/// Render a row's code from its options as a PNG or SVG file
+fn render_item(item: &BatchGenerateItem, extension: &str) -> Result<RenderedItem, String> {
+    let symbology = Symbology::parse(item.symbology.as_deref().unwrap_or("qr"))?;
+    let options = EncodeOptions {
+        ec_level: item.ec_level.clone(),
+        version: item.version.clone(),
+        gs1: item.gs1.unwrap_or(false),
+        qr: item.qr_options.clone().unwrap_or_default(),
+    };
+    let encoded = symbology::encode(symbology, &item.content, &options)?;
+    let module_size = png_module_size(item.module_size);
+
+    Ok(RenderedItem {
+        file: matrix_file(&encoded.matrix, symbology.quiet_zone(), extension, Some(module_size))?,
+        image: DynamicImage::ImageLuma8(render::render_matrix_image(
+            &encoded.matrix,
+            symbology.quiet_zone(),
+            module_size,
+        )),
+        target: RenderTarget {
+            symbology: symbology.name().to_string(),
+            format: extension.to_string(),
+            // SVG output has no pixel size
+            module_size: (extension == "png").then_some(module_size),
+            ec_level: options.ec_level,
+            version: options.version,
+            mask: options.qr.mask,
+            gs1: options.gs1,
+            qr: Some(options.qr),
+        },
+    })
+}
+
+/// A manifest row: the item, the files written for it and what its code was
+/// rendered as
+struct ManifestEntry<'a> {
+    item: &'a BatchGenerateItem,
+    file: String,
+    barcode_file: Option<String>,
+    target: RenderTarget,
+}
+
+/// The ZIP manifest: one row per item with the files written for it and the
+/// fingerprint of its content, style and render target
+fn manifest_csv(entries: &[ManifestEntry]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["row", "file", "content", "label", "symbology", "barcode_file", "render_fingerprint"])
+        .map_err(|e| e.to_string())?;
+
+    for ManifestEntry {
+        item,
+        file,
+        barcode_file,
+        target,
+    } in entries
+    {
+        let style = item.style_json.as_deref().unwrap_or("{}");
+        writer
+            .write_record([
+                item.row.to_string(),
+                file.clone(),
+                item.content.clone(),
+                item.label.clone().unwrap_or_default(),
+                target.symbology.clone(),
+                barcode_file.clone().unwrap_or_default(),
+                render::render_fingerprint(&item.content, style, target),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| format!("Failed to write manifest: {}", e))
+}
+
 /// Open file picker for CSV
 #[tauri::command]
 pub async fn pick_csv_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
@@ -352,7 +595,9 @@ pub async fn batch_save_files(
     items: Vec<BatchGenerateItem>,
     format: String, // "png" or "svg"
     base_name: String,
+    barcode_options: Option<BarcodeOptions>,
 ) -> Result<BatchSaveFilesResult, String> {
+    let barcode_options = barcode_options.unwrap_or_default();
     // Show directory picker
     let dir_path = app.dialog().file().blocking_pick_folder();
 
@@ -422,6 +667,13 @@ pub async fn batch_save_files(
         }
 
         files_saved += 1;
+
+        if let Some((barcode_type, barcode_bytes)) = row_barcode(item, extension, &barcode_options)? {
+            let filename = format!("{}-{}-{}.{}", sanitize_filename(&base_name), index + 1, barcode_type, extension);
+            fs::write(directory.join(&filename), barcode_bytes)
+                .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+            files_saved += 1;
+        }
     }
 
     Ok(BatchSaveFilesResult {
@@ -432,32 +684,19 @@ pub async fn batch_save_files(
     })
 }
 
-fn detect_qr_type(content: &str) -> String {
-    let lower = content.to_lowercase();
-
-    if lower.starts_with("wifi:") {
-        "wifi".to_string()
-    } else if lower.starts_with("begin:vcard") {
-        "vcard".to_string()
-    } else if lower.starts_with("mailto:") {
-        "email".to_string()
-    } else if lower.starts_with("sms:") || lower.starts_with("smsto:") {
-        "sms".to_string()
-    } else if lower.starts_with("tel:") {
-        "phone".to_string()
-    } else if lower.starts_with("geo:") {
-        "geo".to_string()
-    } else if lower.starts_with("begin:vcalendar") || lower.starts_with("begin:vevent") {
-        "calendar".to_string()
-    } else if lower.starts_with("bitcoin:") {
-        "bitcoin".to_string()
-    } else if lower.starts_with("https://search.google.com/local/writereview") {
-        "google-review".to_string()
-    } else if lower.starts_with("http://") || lower.starts_with("https://") {
-        "url".to_string()
-    } else {
-        "text".to_string()
-    }
+/// The row's barcode rendered as `format`, with its type name, if it asked for one
+fn row_barcode(
+    item: &BatchGenerateItem,
+    format: &str,
+    options: &BarcodeOptions,
+) -> Result<Option<(&'static str, Vec<u8>)>, String> {
+    let Some(barcode_type) = &item.barcode_type else {
+        return Ok(None);
+    };
+    let data = item.barcode_data.as_deref().unwrap_or(&item.content);
+    let (barcode, bytes, _) = barcode_file(data, barcode_type, format, options)
+        .map_err(|e| format!("Failed to render barcode for row {}: {}", item.row, e))?;
+    Ok(Some((barcode.kind.name(), bytes)))
 }
 
 fn sanitize_filename(s: &str) -> String {
@@ -474,82 +713,7 @@ fn sanitize_filename(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_detect_qr_type_wifi() {
-        assert_eq!(detect_qr_type("WIFI:T:WPA;S:Network;;"), "wifi");
-        assert_eq!(detect_qr_type("wifi:T:WEP;S:test;;"), "wifi");
-    }
-
-    #[test]
-    fn test_detect_qr_type_vcard() {
-        assert_eq!(detect_qr_type("BEGIN:VCARD\nVERSION:3.0"), "vcard");
-        assert_eq!(detect_qr_type("begin:vcard"), "vcard");
-    }
-
-    #[test]
-    fn test_detect_qr_type_email() {
-        assert_eq!(detect_qr_type("mailto:test@example.com"), "email");
-        assert_eq!(detect_qr_type("MAILTO:test@example.com?subject=Hi"), "email");
-    }
-
-    #[test]
-    fn test_detect_qr_type_sms() {
-        assert_eq!(detect_qr_type("sms:+15551234567"), "sms");
-        assert_eq!(detect_qr_type("smsto:+15551234567"), "sms");
-        assert_eq!(detect_qr_type("SMS:+15551234567?body=Hello"), "sms");
-    }
-
-    #[test]
-    fn test_detect_qr_type_phone() {
-        assert_eq!(detect_qr_type("tel:+15551234567"), "phone");
-        assert_eq!(detect_qr_type("TEL:5551234567"), "phone");
-    }
-
-    #[test]
-    fn test_detect_qr_type_geo() {
-        assert_eq!(detect_qr_type("geo:37.7749,-122.4194"), "geo");
-        assert_eq!(detect_qr_type("GEO:0,0"), "geo");
-    }
-
-    #[test]
-    fn test_detect_qr_type_calendar() {
-        assert_eq!(detect_qr_type("BEGIN:VCALENDAR\r\nVERSION:2.0"), "calendar");
-        assert_eq!(detect_qr_type("BEGIN:VEVENT\nSUMMARY:Meeting"), "calendar");
-    }
-
-    #[test]
-    fn test_detect_qr_type_bitcoin() {
-        assert_eq!(detect_qr_type("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"), "bitcoin");
-        assert_eq!(detect_qr_type("BITCOIN:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=0.5"), "bitcoin");
-    }
-
-    #[test]
-    fn test_detect_qr_type_google_review() {
-        assert_eq!(
-            detect_qr_type("https://search.google.com/local/writereview?placeid=ChIJN1t_tDeuEmsRUsoyG83frY4"),
-            "google-review"
-        );
-        // case-insensitive prefix match
-        assert_eq!(
-            detect_qr_type("HTTPS://SEARCH.GOOGLE.COM/local/writereview?placeid=abc"),
-            "google-review"
-        );
-    }
-
-    #[test]
-    fn test_detect_qr_type_url() {
-        assert_eq!(detect_qr_type("https://example.com"), "url");
-        assert_eq!(detect_qr_type("http://example.com/path"), "url");
-        assert_eq!(detect_qr_type("HTTP://EXAMPLE.COM"), "url");
-    }
-
-    #[test]
-    fn test_detect_qr_type_text() {
-        assert_eq!(detect_qr_type("Hello, World!"), "text");
-        assert_eq!(detect_qr_type("Just some text"), "text");
-        assert_eq!(detect_qr_type("12345"), "text");
-    }
+    use crate::symbology::Symbology;
 
     #[test]
     fn test_sanitize_filename_basic() {
@@ -586,7 +750,7 @@ mod tests {
     #[test]
     fn test_parse_csv_content_basic() {
         let csv = "content,type,label\nhttps://example.com,url,Example\nhello world,text,Greeting";
-        let result = parse_csv_content(csv).unwrap();
+        let result = parse_csv_content(csv, &[], None).unwrap();
 
         assert!(result.success);
         assert_eq!(result.total_rows, 2);
@@ -604,7 +768,7 @@ mod tests {
     #[test]
     fn test_parse_csv_content_auto_detect_type() {
         let csv = "content\nhttps://example.com\ntel:+15551234567\nWIFI:T:WPA;S:Test;;";
-        let result = parse_csv_content(csv).unwrap();
+        let result = parse_csv_content(csv, &[], None).unwrap();
 
         assert!(result.success);
         assert_eq!(result.items.len(), 3);
@@ -613,10 +777,49 @@ mod tests {
         assert_eq!(result.items[2].qr_type, "wifi");
     }
 
+    #[test]
+    fn test_parse_csv_content_url_options() {
+        let options: UrlOptions = serde_json::from_str(
+            r#"{
+                "normalize": true,
+                "utm": {
+                    "source": { "value": "flyer" },
+                    "campaign": { "column": "campaign", "value": "default" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let csv = "content,campaign\nHTTPS://Example.com/a,spring\nhttps://example.com/b,\nHello,spring";
+        let result = parse_csv_content(csv, &[], Some(&options)).unwrap();
+
+        assert_eq!(result.items[0].content, "https://example.com/a?utm_source=flyer&utm_campaign=spring");
+        assert_eq!(result.items[0].original_content, Some("HTTPS://Example.com/a".to_string()));
+        assert_eq!(result.items[1].content, "https://example.com/b?utm_source=flyer&utm_campaign=default");
+        // Non-links are left alone
+        assert_eq!(result.items[2].content, "Hello");
+        assert_eq!(result.items[2].original_content, None);
+    }
+
+    #[test]
+    fn test_parse_csv_content_custom_rules() {
+        let rules = vec![ClassifierRule {
+            name: "ACME".to_string(),
+            prefix: "acme:".to_string(),
+            qr_type: "acme".to_string(),
+            sub_type: None,
+            confidence: 0.9,
+        }];
+        let csv = "content\nACME:widget/1\nhttps://example.com";
+        let result = parse_csv_content(csv, &rules, None).unwrap();
+
+        assert_eq!(result.items[0].qr_type, "acme");
+        assert_eq!(result.items[1].qr_type, "url");
+    }
+
     #[test]
     fn test_parse_csv_content_skips_empty_rows() {
         let csv = "content\nhttps://example.com\n\nhello\n   \nworld";
-        let result = parse_csv_content(csv).unwrap();
+        let result = parse_csv_content(csv, &[], None).unwrap();
 
         assert!(result.success);
         assert_eq!(result.items.len(), 3);
@@ -625,7 +828,7 @@ mod tests {
     #[test]
     fn test_parse_csv_content_missing_content_column() {
         let csv = "type,label\nurl,Example";
-        let result = parse_csv_content(csv);
+        let result = parse_csv_content(csv, &[], None);
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("content"));
@@ -634,21 +837,170 @@ mod tests {
     #[test]
     fn test_parse_csv_content_optional_columns() {
         let csv = "content\nhttps://example.com";
-        let result = parse_csv_content(csv).unwrap();
+        let result = parse_csv_content(csv, &[], None).unwrap();
 
         assert!(result.success);
         assert_eq!(result.items.len(), 1);
         assert_eq!(result.items[0].qr_type, "url"); // auto-detected
         assert_eq!(result.items[0].label, None);
+        assert_eq!(result.items[0].barcode_type, None);
+    }
+
+    #[test]
+    fn test_parse_csv_content_barcode_columns() {
+        let csv = "content,barcode,barcode_data\nhttps://example.com/p/1,EAN13,400638133393\nSKU-42,code128,\nhttps://example.com/p/3,,";
+        let result = parse_csv_content(csv, &[], None).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.items[0].barcode_type.as_deref(), Some("ean-13"));
+        assert_eq!(result.items[0].barcode_data.as_deref(), Some("4006381333931"));
+        // Without barcode_data the row's content is used
+        assert_eq!(result.items[1].barcode_data.as_deref(), Some("SKU-42"));
+        assert_eq!(result.items[2].barcode_type, None);
+
+        let csv = "content,barcode,barcode_data\nhttps://example.com,ean-13,4006381333932";
+        let result = parse_csv_content(csv, &[], None).unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Error at row 2: Invalid EAN-13 check digit: expected 1, got 2")
+        );
     }
 
     #[test]
     fn test_parse_csv_content_row_numbers() {
         let csv = "content\nfirst\nsecond\nthird";
-        let result = parse_csv_content(csv).unwrap();
+        let result = parse_csv_content(csv, &[], None).unwrap();
 
         assert_eq!(result.items[0].row, 1);
         assert_eq!(result.items[1].row, 2);
         assert_eq!(result.items[2].row, 3);
     }
+
+    fn batch_item(symbology: Symbology, content: &str, expected: Option<&str>) -> BatchGenerateItem {
+        let encoded = symbology::encode(symbology, content, &Default::default()).unwrap();
+        let img = crate::render::render_matrix_image(&encoded.matrix, symbology.quiet_zone(), 6);
+        BatchGenerateItem {
+            row: 1,
+            content: content.to_string(),
+            label: None,
+            image_data: STANDARD.encode(crate::render::encode_png(&img).unwrap()),
+            symbology: expected.map(|s| s.to_string()),
+            barcode_type: None,
+            barcode_data: None,
+            style_json: None,
+            ec_level: None,
+            version: None,
+            gs1: None,
+            qr_options: None,
+            module_size: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_single_item_symbologies() {
+        let result = validate_single_item(&batch_item(Symbology::DataMatrix, "LOT-7", Some("data-matrix")));
+        assert!(result.success, "{:?}", result.error);
+
+        let result = validate_single_item(&batch_item(Symbology::Qr, "LOT-7", None));
+        assert!(result.success, "{:?}", result.error);
+
+        // Readable, but not what the row asked for
+        let result = validate_single_item(&batch_item(Symbology::Aztec, "LOT-7", Some("pdf417")));
+        assert!(!result.success);
+        assert!(result.content_match);
+        assert_eq!(result.error, Some("Expected PDF417 but found Aztec".to_string()));
+    }
+
+    #[test]
+    fn test_validate_single_item_epc_error_correction() {
+        let content = "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000";
+        let item = |level: &str| {
+            let options = symbology::EncodeOptions {
+                ec_level: Some(level.to_string()),
+                ..Default::default()
+            };
+            let encoded = symbology::encode(Symbology::Qr, content, &options).unwrap();
+            let img = crate::render::render_matrix_image(&encoded.matrix, Symbology::Qr.quiet_zone(), 6);
+            BatchGenerateItem {
+                image_data: STANDARD.encode(crate::render::encode_png(&img).unwrap()),
+                ..batch_item(Symbology::Qr, content, None)
+            }
+        };
+
+        let result = validate_single_item(&item("M"));
+        assert!(result.success, "{:?}", result.error);
+
+        for level in ["L", "Q", "H"] {
+            let result = validate_single_item(&item(level));
+            assert!(!result.success);
+            assert!(result.content_match);
+            assert_eq!(
+                result.error,
+                Some(format!("EPC codes must use error correction level M, found {}", level))
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_item_is_reproducible() {
+        let mut item = batch_item(Symbology::Qr, "LOT-7", None);
+        item.ec_level = Some("H".to_string());
+        item.module_size = Some(4);
+
+        let png = render_item(&item, "png").unwrap();
+        assert_eq!(png.file, render_item(&item, "png").unwrap().file);
+        assert!(png.file.starts_with(b"\x89PNG"));
+        assert_eq!(png.target.ec_level.as_deref(), Some("H"));
+        assert_eq!(png.target.module_size, Some(4));
+        assert!(validate_item_image(&item, &png.image).success);
+
+        let svg = render_item(&item, "svg").unwrap();
+        assert!(String::from_utf8(svg.file).unwrap().contains("<svg"));
+        assert_eq!(svg.target.module_size, None);
+        // SVG files are validated through a raster of the same matrix
+        assert!(validate_item_image(&item, &svg.image).success);
+
+        // Options that change the file change the fingerprint
+        let mut low = item.clone();
+        low.ec_level = Some("L".to_string());
+        let fingerprint = |target: &RenderTarget| render::render_fingerprint("LOT-7", "{}", target);
+        assert_ne!(fingerprint(&render_item(&low, "png").unwrap().target), fingerprint(&png.target));
+        assert_ne!(fingerprint(&svg.target), fingerprint(&png.target));
+    }
+
+    #[test]
+    fn test_manifest_csv_fingerprints() {
+        let mut styled = batch_item(Symbology::Qr, "LOT-7", None);
+        styled.style_json = Some(r#"{ "size": 512 }"#.to_string());
+        let plain = batch_item(Symbology::DataMatrix, "LOT-8", Some("data-matrix"));
+        let entry = |item, file: &str, barcode_file: Option<&str>| ManifestEntry {
+            item,
+            file: file.to_string(),
+            barcode_file: barcode_file.map(str::to_string),
+            target: render_item(item, "png").unwrap().target,
+        };
+        let entries = [
+            entry(&styled, "001_qr.png", None),
+            entry(&plain, "001_data-matrix.png", Some("001_data-matrix_ean-13.png")),
+        ];
+
+        let csv = String::from_utf8(manifest_csv(&entries).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "row,file,content,label,symbology,barcode_file,render_fingerprint");
+        assert_eq!(
+            lines[1],
+            format!(
+                "1,001_qr.png,LOT-7,,qr,,{}",
+                render::render_fingerprint("LOT-7", r#"{"size":512}"#, &entries[0].target)
+            )
+        );
+        assert!(lines[2].ends_with(&format!(
+            "data-matrix,001_data-matrix_ean-13.png,{}",
+            render::render_fingerprint("LOT-8", "{}", &entries[1].target)
+        )));
+
+        // The same items always give the same manifest
+        assert_eq!(manifest_csv(&entries).unwrap(), csv.into_bytes());
+    }
 }
@@ -0,0 +1,36 @@
+use crate::classify::{self, Classification, ClassifierRule};
+use crate::db::DbState;
+use tauri::State;
+
+/// Classify content using the built-in types and any custom rules
+#[tauri::command]
+pub async fn classify_content(
+    state: State<'_, DbState>,
+    content: String,
+) -> Result<Classification, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let rules = classify::load_rules(&conn)?;
+
+    Ok(classify::classify(&content, &rules))
+}
+
+/// Get the custom classifier rules, or why the saved ones can't be used
+#[tauri::command]
+pub async fn classifier_rules_get(
+    state: State<'_, DbState>,
+) -> Result<Vec<ClassifierRule>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    classify::load_rules(&conn)
+}
+
+/// Replace the custom classifier rules. Rules are tried in order, before the built-in types.
+#[tauri::command]
+pub async fn classifier_rules_set(
+    state: State<'_, DbState>,
+    rules: Vec<ClassifierRule>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    classify::save_rules(&conn, &rules)
+}
@@ -1,3 +1,4 @@
+use crate::render;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -92,6 +93,74 @@ pub async fn export_svg(
     }
 }
 
+/// Default print resolution for Swiss QR-bill PNG exports
+const SWISS_DEFAULT_DPI: u32 = 300;
+
+/// Render a Swiss QR-bill code as "png" or "svg", returning the file bytes
+/// and extension
+fn swiss_file(content: &str, format: &str, dpi: Option<u32>) -> Result<(Vec<u8>, &'static str), String> {
+    match format {
+        "svg" => Ok((render::render_swiss_svg(content)?.into_bytes(), "svg")),
+        "png" => Ok((render::render_swiss_png(content, dpi.unwrap_or(SWISS_DEFAULT_DPI))?, "png")),
+        other => Err(format!("Unknown QR-bill file format '{}', expected png or svg", other)),
+    }
+}
+
+/// Render a Swiss QR-bill code at its mandated size and return it as a data URL
+#[tauri::command]
+pub async fn render_swiss_qr_bill(
+    content: String,
+    format: String,
+    dpi: Option<u32>,
+) -> Result<String, String> {
+    let (bytes, extension) = swiss_file(&content, &format, dpi)?;
+    let mime = if extension == "svg" { "image/svg+xml" } else { "image/png" };
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
+
+/// Save a Swiss QR-bill code at its mandated 46x46 mm size using native file dialog
+#[tauri::command]
+pub async fn export_swiss_qr_bill(
+    app: tauri::AppHandle,
+    content: String,
+    format: String,
+    dpi: Option<u32>,
+    suggested_name: Option<String>,
+) -> Result<ExportResult, String> {
+    // Render before showing the dialog so invalid payloads fail fast
+    let (file_bytes, extension) = swiss_file(&content, &format, dpi)?;
+    let filter_name = if extension == "svg" { "SVG Image" } else { "PNG Image" };
+
+    let file_name = suggested_name.unwrap_or_else(|| format!("qr-bill.{}", extension));
+
+    // Show save dialog
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(&file_name)
+        .add_filter(filter_name, &[extension])
+        .blocking_save_file();
+
+    match file_path {
+        Some(path) => {
+            let path_buf: PathBuf = path.as_path().unwrap().to_path_buf();
+            fs::write(&path_buf, &file_bytes)
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+
+            Ok(ExportResult {
+                success: true,
+                path: Some(path_buf.to_string_lossy().to_string()),
+                error: None,
+            })
+        }
+        None => Ok(ExportResult {
+            success: false,
+            path: None,
+            error: Some("Save cancelled by user".to_string()),
+        }),
+    }
+}
+
 /// Copy image data to clipboard
 #[tauri::command]
 pub async fn copy_image_to_clipboard(
@@ -132,6 +201,7 @@ pub async fn pick_image_file(app: tauri::AppHandle) -> Result<Option<String>, St
         .dialog()
         .file()
         .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp", "bmp"])
+        .add_filter("PDF documents", &["pdf"])
         .blocking_pick_file();
 
     match file_path {
@@ -248,4 +318,12 @@ mod tests {
         let decoded = STANDARD.decode(base64_only).unwrap();
         assert_eq!(decoded, b"Hello, World!");
     }
+
+    #[test]
+    fn test_swiss_file_rejects_unknown_format() {
+        let err = swiss_file("SPC", "pdf", None).unwrap_err();
+        assert_eq!(err, "Unknown QR-bill file format 'pdf', expected png or svg");
+        // Known formats get as far as checking the payload
+        assert_ne!(swiss_file("SPC", "svg", None).unwrap_err(), err);
+    }
 }
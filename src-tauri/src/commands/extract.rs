@@ -0,0 +1,173 @@
+use super::export::strip_data_url_prefix;
+use super::validate::{
+    detect_codes, image_from_bytes, load_classifier_rules, scan_result_from_content, CodeGrid,
+    DetectedCode,
+};
+use crate::db::DbState;
+use crate::{preprocess, rectify, render};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// Pixels per module in extracted images when the caller doesn't ask
+const DEFAULT_MODULE_SIZE: u32 = 10;
+
+/// Quiet zone added around extracted codes, in modules
+const QUIET_ZONE: usize = 4;
+
+/// A code cut out of a photo and straightened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedCode {
+    pub content: String, // secrets masked unless revealed
+    pub qr_type: String,
+    pub version: u32,
+    pub strategy: String,
+    pub image: String, // PNG data URL: perspective-corrected, cropped and binarised
+    pub svg: String,   // clean vector re-render of the modules read from the photo
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractResult {
+    pub success: bool,
+    pub codes: Vec<ExtractedCode>,
+    pub unreadable: usize, // codes that were located but couldn't be decoded
+    pub error: Option<String>,
+}
+
+/// Straighten and crop every readable QR code in a photo, given as a file
+/// path or base64 image data. Each code comes back as a black and white PNG
+/// and as an SVG suitable for printing.
+#[tauri::command]
+pub async fn extract_qr_codes(
+    state: State<'_, DbState>,
+    file_path: Option<String>,
+    image_data: Option<String>,
+    module_size: Option<u32>,
+    reveal_secrets: Option<bool>,
+) -> Result<ExtractResult, String> {
+    let img = match (file_path, image_data) {
+        (Some(path), _) => image::open(&path).map_err(|e| format!("Failed to open image: {}", e))?,
+        (None, Some(data)) => {
+            let bytes = STANDARD
+                .decode(strip_data_url_prefix(&data))
+                .map_err(|e| format!("Failed to decode base64: {}", e))?;
+            image_from_bytes(&bytes)?
+        }
+        (None, None) => return Err("Either a file path or image data is required".to_string()),
+    };
+
+    let rules = load_classifier_rules(&state)?;
+    let module_size = module_size.unwrap_or(DEFAULT_MODULE_SIZE).clamp(2, 40);
+    let reveal_secrets = reveal_secrets.unwrap_or(false);
+
+    let detected = detect_codes(&img);
+    let unreadable = detected.iter().filter(|c| c.content.is_err()).count();
+
+    let mut codes = Vec::new();
+    for code in detected {
+        // Only full-size QR codes come back with a grid to straighten
+        let (Ok(content), Some(grid)) = (code.content.clone(), &code.grid) else {
+            continue;
+        };
+        let Some(image) = rectified_png(&img, &code, grid, module_size)? else {
+            continue;
+        };
+
+        let scan = scan_result_from_content(content, &rules, reveal_secrets);
+        codes.push(ExtractedCode {
+            content: scan.content.unwrap_or_default(),
+            qr_type: scan.qr_type.unwrap_or_default(),
+            version: (grid.modules.width() as u32).saturating_sub(17) / 4,
+            strategy: code.strategy.to_string(),
+            image,
+            svg: render::render_matrix_svg(&grid.modules, QUIET_ZONE),
+        });
+    }
+
+    Ok(ExtractResult {
+        success: !codes.is_empty(),
+        error: codes.is_empty().then(|| "No readable QR code found in image".to_string()),
+        codes,
+        unreadable,
+    })
+}
+
+/// The code straightened into an upright square with a quiet zone, as a PNG
+/// data URL. `None` if its corners don't form a usable quadrilateral.
+fn rectified_png(
+    img: &DynamicImage,
+    code: &DetectedCode,
+    grid: &CodeGrid,
+    module_size: u32,
+) -> Result<Option<String>, String> {
+    // Find the image the code was located in; corners are in its coordinates
+    let Some(source) = preprocess::apply(code.strategy, img).into_iter().nth(code.image_index) else {
+        return Ok(None);
+    };
+
+    let size = grid.modules.width();
+    // rqrr's bounds sit one module past the far edge of the grid
+    let Some(perspective) = rectify::Perspective::from_square(size as f64 + 1.0, grid.bounds) else {
+        return Ok(None);
+    };
+
+    let quiet = QUIET_ZONE as f64;
+    let straightened = rectify::warp(
+        &source,
+        &perspective,
+        -quiet,
+        size as f64 + 2.0 * quiet,
+        module_size,
+    );
+    let png = render::encode_png(&rectify::binarise(&straightened))?;
+    Ok(Some(format!("data:image/png;base64,{}", STANDARD.encode(png))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{encode_qr, ModuleMatrix};
+    use image::{GrayImage, Luma};
+
+    /// A code drawn as a trapezoid, as if photographed at an angle
+    fn tilted_photo(matrix: &ModuleMatrix) -> DynamicImage {
+        let size = matrix.width() as f64;
+        let square = [(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)];
+        let corners = [(120.0, 60.0), (420.0, 90.0), (440.0, 400.0), (90.0, 430.0)];
+        let to_code = rectify::Perspective::between(corners, square).unwrap();
+
+        DynamicImage::ImageLuma8(GrayImage::from_fn(520, 500, |x, y| {
+            let (u, v) = to_code.map(x as f64 + 0.5, y as f64 + 0.5);
+            let inside = u >= 0.0 && v >= 0.0 && u < size && v < size;
+            let dark = inside && matrix.is_dark(u as usize, v as usize);
+            Luma([if dark { 40 } else { 210 }])
+        }))
+    }
+
+    #[test]
+    fn test_extract_straightens_tilted_code() {
+        let code = encode_qr("https://example.com/menu", qrcode::EcLevel::M).unwrap();
+        let matrix = ModuleMatrix::from_qr(&code);
+        let photo = tilted_photo(&matrix);
+
+        let detected = detect_codes(&photo);
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].content.as_deref(), Ok("https://example.com/menu"));
+        let grid = detected[0].grid.as_ref().unwrap();
+        assert_eq!(grid.modules, matrix);
+
+        let png = rectified_png(&photo, &detected[0], grid, 4).unwrap().unwrap();
+        let bytes = STANDARD.decode(strip_data_url_prefix(&png)).unwrap();
+        let straightened = image::load_from_memory(&bytes).unwrap();
+        let side = (matrix.width() as u32 + 8) * 4;
+        assert_eq!((straightened.width(), straightened.height()), (side, side));
+
+        // The straightened image reads back as the same code
+        let rescanned = detect_codes(&straightened);
+        assert_eq!(rescanned[0].content.as_deref(), Ok("https://example.com/menu"));
+        assert_eq!(rescanned[0].strategy, "original");
+    }
+}
@@ -0,0 +1,438 @@
+use super::validate::{scan_path, ScanResult};
+use crate::classify::{self, ClassifierRule};
+use crate::db::DbState;
+use crate::pdf;
+use glob::{MatchOptions, Pattern};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::{Emitter, State};
+use tauri_plugin_dialog::DialogExt;
+use walkdir::WalkDir;
+
+/// Event emitted after each file is scanned
+pub const SCAN_FOLDER_PROGRESS_EVENT: &str = "scan-folder-progress";
+
+/// File types scanned when no include patterns are given
+const SCANNABLE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff", "pdf",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanOptions {
+    pub directory: String,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub include: Vec<String>, // globs, e.g. "*.png" or "proofs/**/*.pdf"
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub dpi: Option<f32>, // for PDFs
+    pub reveal_secrets: Option<bool>,
+    pub report_path: Option<String>, // ".json" writes JSON, anything else CSV
+}
+
+/// One decoded code, or one file that produced no codes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanEntry {
+    pub file: String, // relative to the scanned folder, '/' separated
+    pub page: Option<u32>,
+    pub success: bool,
+    pub content: Option<String>,
+    pub qr_type: Option<String>,
+    pub sub_type: Option<String>,
+    pub confidence: Option<f32>,
+    pub safety_level: Option<String>,
+    pub width: Option<u32>, // image size in pixels; not set for PDFs
+    pub height: Option<u32>,
+    pub file_size: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanReport {
+    pub directory: String,
+    pub files_scanned: usize,
+    pub codes_found: usize,
+    pub failures: usize,
+    pub entries: Vec<FolderScanEntry>,
+    pub report_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanProgress {
+    pub done: usize,
+    pub total: usize,
+    pub file: String,
+}
+
+/// Scan every image and PDF in a folder in parallel, emitting
+/// `scan-folder-progress` events, and optionally write a report
+#[tauri::command]
+pub async fn scan_folder(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    options: FolderScanOptions,
+) -> Result<FolderScanReport, String> {
+    let rules = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        classify::load_rules(&conn)?
+    };
+
+    // Walking the folder and decoding block; keep them off the async runtime
+    let scan_options = options.clone();
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        let root = PathBuf::from(&scan_options.directory);
+        let files = collect_files(&root, &scan_options)?;
+        Ok::<_, String>(scan_files(&root, &files, &scan_options, &rules, |progress| {
+            let _ = app.emit(SCAN_FOLDER_PROGRESS_EVENT, progress);
+        }))
+    })
+    .await
+    .map_err(|e| format!("Folder scan failed: {}", e))??;
+
+    if let Some(path) = &options.report_path {
+        write_report(&report, Path::new(path))?;
+        report.report_path = Some(path.clone());
+    }
+
+    Ok(report)
+}
+
+/// Open a folder picker dialog
+#[tauri::command]
+pub async fn pick_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let folder = app.dialog().file().blocking_pick_folder();
+
+    match folder {
+        Some(path) => Ok(Some(path.as_path().unwrap().to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Files under `root` matching the options, sorted by path
+pub(crate) fn collect_files(root: &Path, options: &FolderScanOptions) -> Result<Vec<PathBuf>, String> {
+    if !root.is_dir() {
+        return Err(format!("'{}' is not a folder", root.display()));
+    }
+
+    let compile = |patterns: &[String]| {
+        patterns
+            .iter()
+            .map(|p| Pattern::new(p).map_err(|e| format!("Invalid pattern '{}': {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let include = compile(&options.include)?;
+    let exclude = compile(&options.exclude)?;
+
+    let mut walker = WalkDir::new(root).follow_links(false);
+    if !options.recursive {
+        walker = walker.max_depth(1);
+    }
+
+    let mut files: Vec<PathBuf> = walker
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            let relative = relative_path(root, path);
+            let included = if include.is_empty() {
+                has_scannable_extension(path)
+            } else {
+                include.iter().any(|p| matches_pattern(p, &relative))
+            };
+            included && !exclude.iter().any(|p| matches_pattern(p, &relative))
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
+
+/// Patterns with a '/' match the relative path; others match the file name
+fn matches_pattern(pattern: &Pattern, relative: &str) -> bool {
+    let options = MatchOptions {
+        case_sensitive: false,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let target = if pattern.as_str().contains('/') {
+        relative
+    } else {
+        relative.rsplit('/').next().unwrap_or(relative)
+    };
+    pattern.matches_with(target, options)
+}
+
+fn has_scannable_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SCANNABLE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+pub(crate) fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Decode all files in parallel. Entries come back in file order so reports diff cleanly.
+fn scan_files(
+    root: &Path,
+    files: &[PathBuf],
+    options: &FolderScanOptions,
+    rules: &[ClassifierRule],
+    on_progress: impl Fn(FolderScanProgress) + Sync,
+) -> FolderScanReport {
+    let dpi = pdf::clamp_dpi(options.dpi);
+    let reveal_secrets = options.reveal_secrets.unwrap_or(false);
+    let done = AtomicUsize::new(0);
+
+    let per_file: Vec<Vec<FolderScanEntry>> = files
+        .par_iter()
+        .map(|path| {
+            let entries = scan_one(root, path, dpi, rules, reveal_secrets);
+            on_progress(FolderScanProgress {
+                done: done.fetch_add(1, Ordering::SeqCst) + 1,
+                total: files.len(),
+                file: relative_path(root, path),
+            });
+            entries
+        })
+        .collect();
+
+    let entries: Vec<FolderScanEntry> = per_file.into_iter().flatten().collect();
+    FolderScanReport {
+        directory: root.to_string_lossy().to_string(),
+        files_scanned: files.len(),
+        codes_found: entries.iter().filter(|e| e.success).count(),
+        failures: entries.iter().filter(|e| !e.success).count(),
+        entries,
+        report_path: None,
+    }
+}
+
+fn scan_one(
+    root: &Path,
+    path: &Path,
+    dpi: f32,
+    rules: &[ClassifierRule],
+    reveal_secrets: bool,
+) -> Vec<FolderScanEntry> {
+    let file = relative_path(root, path);
+    let file_size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let (width, height) = match image::image_dimensions(path) {
+        Ok((w, h)) => (Some(w), Some(h)),
+        Err(_) => (None, None),
+    };
+
+    let entry = |result: ScanResult| FolderScanEntry {
+        file: file.clone(),
+        page: result.page,
+        success: result.success,
+        content: result.content,
+        qr_type: result.qr_type,
+        sub_type: result.sub_type,
+        confidence: result.confidence,
+        safety_level: result.safety.map(|s| s.level),
+        width,
+        height,
+        file_size,
+        error: result.error,
+    };
+
+    match scan_path(path, dpi, rules, reveal_secrets) {
+        Ok(results) => results.into_iter().map(entry).collect(),
+        Err(error) => vec![FolderScanEntry {
+            file: file.clone(),
+            page: None,
+            success: false,
+            content: None,
+            qr_type: None,
+            sub_type: None,
+            confidence: None,
+            safety_level: None,
+            width,
+            height,
+            file_size,
+            error: Some(error),
+        }],
+    }
+}
+
+/// Write the report as JSON or CSV depending on the file extension. The CSV
+/// uses the same `content` and `type` columns as a batch input CSV, so it can
+/// be loaded for batch generation.
+fn write_report(report: &FolderScanReport, path: &Path) -> Result<(), String> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+        return fs::write(path, json).map_err(|e| format!("Failed to write report: {}", e));
+    }
+
+    let mut writer =
+        csv::Writer::from_path(path).map_err(|e| format!("Failed to write report: {}", e))?;
+    writer
+        .write_record([
+            "file", "page", "success", "content", "type", "sub_type", "confidence", "safety",
+            "width", "height", "file_size", "error",
+        ])
+        .map_err(|e| e.to_string())?;
+
+    let opt = |value: Option<String>| value.unwrap_or_default();
+    for entry in &report.entries {
+        writer
+            .write_record([
+                entry.file.clone(),
+                opt(entry.page.map(|p| p.to_string())),
+                entry.success.to_string(),
+                opt(entry.content.clone()),
+                opt(entry.qr_type.clone()),
+                opt(entry.sub_type.clone()),
+                opt(entry.confidence.map(|c| c.to_string())),
+                opt(entry.safety_level.clone()),
+                opt(entry.width.map(|w| w.to_string())),
+                opt(entry.height.map(|h| h.to_string())),
+                entry.file_size.to_string(),
+                opt(entry.error.clone()),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+
+    writer.flush().map_err(|e| format!("Failed to write report: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn options(directory: &Path) -> FolderScanOptions {
+        FolderScanOptions {
+            directory: directory.to_string_lossy().to_string(),
+            recursive: false,
+            include: vec![],
+            exclude: vec![],
+            dpi: None,
+            reveal_secrets: None,
+            report_path: None,
+        }
+    }
+
+    fn write_qr_png(path: &Path, content: &str) {
+        let code = crate::render::encode_qr(content, qrcode::EcLevel::M).unwrap();
+        let matrix = crate::render::ModuleMatrix::from_qr(&code);
+        let size = (matrix.width() as u32 + 8) * 6;
+        image::GrayImage::from_fn(size, size, |x, y| {
+            let (mx, my) = ((x / 6) as usize, (y / 6) as usize);
+            let dark = mx >= 4 && my >= 4 && matrix.is_dark(mx - 4, my - 4);
+            image::Luma([if dark { 0 } else { 255 }])
+        })
+        .save(path)
+        .unwrap();
+    }
+
+    fn sample_folder() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("proofs")).unwrap();
+        write_qr_png(&dir.path().join("a.png"), "https://example.com/a");
+        write_qr_png(&dir.path().join("proofs/b.png"), "https://example.com/b");
+        image::GrayImage::from_pixel(40, 40, image::Luma([255]))
+            .save(dir.path().join("blank.png"))
+            .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_collect_files_filters() {
+        let dir = sample_folder();
+        let root = dir.path();
+        let names = |opts: &FolderScanOptions| -> Vec<String> {
+            collect_files(root, opts)
+                .unwrap()
+                .iter()
+                .map(|p| relative_path(root, p))
+                .collect()
+        };
+
+        let mut opts = options(root);
+        assert_eq!(names(&opts), vec!["a.png", "blank.png"]);
+
+        opts.recursive = true;
+        assert_eq!(names(&opts), vec!["a.png", "blank.png", "proofs/b.png"]);
+
+        opts.include = vec!["proofs/*.png".to_string()];
+        assert_eq!(names(&opts), vec!["proofs/b.png"]);
+
+        opts.include = vec!["*.PNG".to_string()];
+        opts.exclude = vec!["blank*".to_string()];
+        assert_eq!(names(&opts), vec!["a.png", "proofs/b.png"]);
+
+        opts.include = vec!["[".to_string()];
+        assert!(collect_files(root, &opts).is_err());
+        assert!(collect_files(&root.join("missing"), &options(root)).is_err());
+    }
+
+    #[test]
+    fn test_scan_files_reports_codes_and_failures() {
+        let dir = sample_folder();
+        let root = dir.path();
+        let mut opts = options(root);
+        opts.recursive = true;
+
+        let files = collect_files(root, &opts).unwrap();
+        let progress = Mutex::new(Vec::new());
+        let report = scan_files(root, &files, &opts, &[], |p| progress.lock().unwrap().push(p.done));
+
+        assert_eq!(report.files_scanned, 3);
+        assert_eq!(report.codes_found, 2);
+        assert_eq!(report.failures, 1);
+
+        let files: Vec<&str> = report.entries.iter().map(|e| e.file.as_str()).collect();
+        assert_eq!(files, vec!["a.png", "blank.png", "proofs/b.png"]);
+        assert_eq!(report.entries[0].content, Some("https://example.com/a".to_string()));
+        assert_eq!(report.entries[0].qr_type, Some("url".to_string()));
+        assert_eq!(report.entries[0].width, Some(report.entries[0].height.unwrap()));
+        assert!(report.entries[1].error.is_some());
+
+        let mut done = progress.into_inner().unwrap();
+        done.sort();
+        assert_eq!(done, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_write_report_csv_and_json() {
+        let dir = sample_folder();
+        let root = dir.path();
+        let opts = options(root);
+        let files = collect_files(root, &opts).unwrap();
+        let report = scan_files(root, &files, &opts, &[], |_| {});
+
+        let csv_path = root.join("report.csv");
+        write_report(&report, &csv_path).unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("file,page,success,content,type,"));
+        assert!(lines.next().unwrap().starts_with("a.png,,true,https://example.com/a,url,"));
+
+        let json_path = root.join("report.json");
+        write_report(&report, &json_path).unwrap();
+        let parsed: FolderScanReport =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(parsed.codes_found, 1);
+    }
+}
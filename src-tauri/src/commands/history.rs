@@ -1,4 +1,6 @@
+use crate::classify;
 use crate::db::{self, DbState, HistoryItem, NewHistoryItem};
+use crate::payload;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -10,21 +12,27 @@ pub struct HistoryListResult {
     pub has_more: bool,
 }
 
-/// List history items with pagination
+/// List history items with pagination.
+/// Secrets such as OTP keys are masked unless `reveal_secrets` is set.
 #[tauri::command]
 pub async fn history_list(
     state: State<'_, DbState>,
     limit: Option<i64>,
     offset: Option<i64>,
     search: Option<String>,
+    reveal_secrets: Option<bool>,
 ) -> Result<HistoryListResult, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
     let limit = limit.unwrap_or(50);
     let offset = offset.unwrap_or(0);
 
-    let items = db::list_history(&conn, limit, offset, search.as_deref())
+    let mut items = db::list_history(&conn, limit, offset, search.as_deref())
         .map_err(|e| format!("Failed to list history: {}", e))?;
 
+    if !reveal_secrets.unwrap_or(false) {
+        items.iter_mut().for_each(mask_secrets);
+    }
+
     let total = db::count_history(&conn).map_err(|e| format!("Failed to count history: {}", e))?;
 
     let has_more = (offset + items.len() as i64) < total;
@@ -36,6 +44,18 @@ pub async fn history_list(
     })
 }
 
+/// Mask the secrets of a history item. The type comes from the content, not
+/// the stored type, which the frontend may have saved as "text" or "url".
+/// The thumbnail of an item with secrets is dropped, since it is a code of
+/// the unmasked content.
+fn mask_secrets(item: &mut HistoryItem) {
+    let qr_type = classify::classify_builtin(&item.content).qr_type;
+    if qr_type == "otp" {
+        item.content = payload::mask_content(&qr_type, &item.content);
+        item.thumbnail = None;
+    }
+}
+
 /// Save a history item
 #[tauri::command]
 pub async fn history_save(
@@ -62,3 +82,41 @@ pub async fn history_clear(state: State<'_, DbState>) -> Result<i64, String> {
 
     db::clear_history(&conn).map_err(|e| format!("Failed to clear history: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_item(content: &str, qr_type: &str) -> HistoryItem {
+        HistoryItem {
+            id: 1,
+            content: content.to_string(),
+            qr_type: qr_type.to_string(),
+            label: None,
+            style_json: "{}".to_string(),
+            thumbnail: Some("data:image/png;base64,abc".to_string()),
+            render_fingerprint: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_mask_secrets_drops_thumbnail() {
+        let mut otp = history_item("otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example", "otp");
+        mask_secrets(&mut otp);
+        assert!(!otp.content.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(otp.thumbnail, None);
+
+        // Saved under another type, the content still decides
+        let mut text = history_item("otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP", "text");
+        mask_secrets(&mut text);
+        assert!(!text.content.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(text.thumbnail, None);
+
+        let mut url = history_item("https://example.com", "url");
+        mask_secrets(&mut url);
+        assert_eq!(url.content, "https://example.com");
+        assert!(url.thumbnail.is_some());
+    }
+}
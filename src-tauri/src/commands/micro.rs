@@ -0,0 +1,71 @@
+use crate::commands::symbology::matrix_data_url;
+use crate::micro::{self, Fit, Kind};
+use crate::render;
+use serde::{Deserialize, Serialize};
+
+/// How content fits one Micro QR or rMQR version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolFit {
+    pub version: String, // "1".."40", "M1".."M4" or "R7x43".."R17x139"
+    pub width: usize,    // in modules
+    pub height: usize,
+    pub capacity_bits: usize,
+    pub used_bits: Option<usize>, // None when the content can't be encoded in this version
+    pub fits: bool,
+}
+
+impl From<&Fit> for SymbolFit {
+    fn from(fit: &Fit) -> Self {
+        Self {
+            version: fit.version.clone(),
+            width: fit.width,
+            height: fit.height,
+            capacity_bits: fit.capacity_bits,
+            used_bits: fit.used_bits,
+            fits: fit.fits(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedSymbol {
+    pub image: String, // PNG or SVG data URL
+    pub fit: SymbolFit,
+}
+
+/// Render content as a Micro QR ("micro") or rectangular Micro QR ("rmqr")
+/// symbol. The smallest version that holds the content is used unless
+/// `version` names one, e.g. "M3" or "R11x43".
+#[tauri::command]
+pub async fn render_micro_qr(
+    content: String,
+    kind: String,
+    version: Option<String>,
+    ec_level: Option<String>,
+    format: String,
+    module_size: Option<u32>,
+) -> Result<RenderedSymbol, String> {
+    let kind = Kind::parse(&kind)?;
+    let ec_level = render::parse_ec_level(ec_level.as_deref().unwrap_or("M"))?;
+    let symbol = micro::encode(kind, &content, ec_level, version.as_deref())?;
+
+    Ok(RenderedSymbol {
+        image: matrix_data_url(&symbol.matrix, micro::QUIET_ZONE, &format, module_size)?,
+        fit: SymbolFit::from(&symbol.fit),
+    })
+}
+
+/// How content fits each Micro QR or rMQR version at an error correction
+/// level, smallest symbol first
+#[tauri::command]
+pub async fn micro_qr_capacity(
+    content: String,
+    kind: String,
+    ec_level: Option<String>,
+) -> Result<Vec<SymbolFit>, String> {
+    let kind = Kind::parse(&kind)?;
+    let ec_level = render::parse_ec_level(ec_level.as_deref().unwrap_or("M"))?;
+    Ok(micro::fits(kind, &content, ec_level).iter().map(SymbolFit::from).collect())
+}
@@ -1,11 +1,29 @@
+pub mod barcode;
 pub mod batch;
+pub mod classify;
 pub mod export;
+pub mod extract;
+pub mod folder;
 pub mod history;
+pub mod micro;
+pub mod payload;
+pub mod reconcile;
+pub mod structured;
+pub mod symbology;
 pub mod templates;
 pub mod validate;
 
+pub use barcode::*;
 pub use batch::*;
+pub use classify::*;
 pub use export::*;
+pub use extract::*;
+pub use folder::*;
 pub use history::*;
+pub use micro::*;
+pub use payload::*;
+pub use reconcile::*;
+pub use structured::*;
+pub use symbology::*;
 pub use templates::*;
 pub use validate::*;
@@ -0,0 +1,20 @@
+use crate::payload::{self, BuiltPayload, ParsedPayload};
+
+/// Build QR content for a structured type (e.g. "epc") from its form fields
+#[tauri::command]
+pub async fn payload_build(
+    qr_type: String,
+    fields: serde_json::Value,
+    error_correction: Option<String>,
+) -> Result<BuiltPayload, String> {
+    payload::build_payload(&qr_type, fields, error_correction.as_deref())
+}
+
+/// Parse content of a structured type back into its fields
+#[tauri::command]
+pub async fn payload_parse(
+    qr_type: String,
+    content: String,
+) -> Result<Option<ParsedPayload>, String> {
+    Ok(payload::parse_payload(&qr_type, &content))
+}
@@ -0,0 +1,317 @@
+use super::batch::BatchItem;
+use super::folder::{collect_files, FolderScanOptions};
+use super::validate::{scan_path, ScanResult};
+use crate::classify::{self, ClassifierRule};
+use crate::db::DbState;
+use crate::{payload, pdf, urls};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Where a code was seen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanLocation {
+    pub file: String,
+    pub page: Option<u32>,
+}
+
+/// Outcome for one row of the batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileRow {
+    pub row: usize,
+    pub content: String,
+    pub label: Option<String>,
+    pub status: String, // "found" | "missing" | "duplicate"
+    pub locations: Vec<ScanLocation>,
+}
+
+/// A scanned code that matches no row, or one that couldn't be read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileCode {
+    pub location: ScanLocation,
+    pub content: Option<String>, // secrets masked
+    pub qr_type: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReport {
+    pub success: bool, // every row found exactly once and nothing unexpected
+    pub total_rows: usize,
+    pub found: usize,
+    pub missing: usize,
+    pub duplicates: usize,
+    pub unexpected: usize,
+    pub rows: Vec<ReconcileRow>,
+    pub unexpected_codes: Vec<ReconcileCode>,
+    pub unreadable: Vec<ReconcileCode>, // files or codes that failed to decode
+}
+
+/// Check that every batch row appears in the scanned images or PDFs. Folders
+/// in `paths` are expanded to the images and PDFs directly inside them.
+#[tauri::command]
+pub async fn batch_reconcile(
+    state: State<'_, DbState>,
+    items: Vec<BatchItem>,
+    paths: Vec<String>,
+    dpi: Option<f32>,
+) -> Result<ReconcileReport, String> {
+    let rules = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        classify::load_rules(&conn)?
+    };
+
+    let files = expand_paths(&paths)?;
+    let scanned = scan_all(&files, pdf::clamp_dpi(dpi), &rules);
+
+    Ok(reconcile(&items, scanned))
+}
+
+fn expand_paths(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if path.is_dir() {
+            let options = FolderScanOptions {
+                directory: path.to_string_lossy().to_string(),
+                recursive: false,
+                include: vec![],
+                exclude: vec![],
+                dpi: None,
+                reveal_secrets: None,
+                report_path: None,
+            };
+            files.extend(collect_files(&path, &options)?);
+        } else if path.is_file() {
+            files.push(path);
+        } else {
+            return Err(format!("'{}' does not exist", path.display()));
+        }
+    }
+    Ok(files)
+}
+
+/// Decode all files in parallel, unmasked so OTP rows can be compared
+fn scan_all(
+    files: &[PathBuf],
+    dpi: f32,
+    rules: &[ClassifierRule],
+) -> Vec<(String, Result<Vec<ScanResult>, String>)> {
+    files
+        .par_iter()
+        .map(|path| (display_name(path), scan_path(path, dpi, rules, true)))
+        .collect()
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Key used to match decoded content to rows
+fn match_key(content: &str) -> String {
+    urls::normalize_url(content).unwrap_or_else(|_| content.trim().to_string())
+}
+
+fn reconcile(
+    items: &[BatchItem],
+    scanned: Vec<(String, Result<Vec<ScanResult>, String>)>,
+) -> ReconcileReport {
+    // Rows sharing content are expected that many times
+    let mut rows_by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, item) in items.iter().enumerate() {
+        rows_by_key.entry(match_key(&item.content)).or_default().push(i);
+    }
+
+    let mut seen: HashMap<String, Vec<ScanLocation>> = HashMap::new();
+    let mut unexpected_codes = Vec::new();
+    let mut unreadable = Vec::new();
+
+    for (file, results) in scanned {
+        let results = match results {
+            Ok(results) => results,
+            Err(error) => {
+                unreadable.push(ReconcileCode {
+                    location: ScanLocation { file, page: None },
+                    content: None,
+                    qr_type: None,
+                    error: Some(error),
+                });
+                continue;
+            }
+        };
+
+        for result in results {
+            let location = ScanLocation {
+                file: file.clone(),
+                page: result.page,
+            };
+            let content = match (result.success, result.content) {
+                (true, Some(content)) => content,
+                (_, _) => {
+                    unreadable.push(ReconcileCode {
+                        location,
+                        content: None,
+                        qr_type: None,
+                        error: result.error,
+                    });
+                    continue;
+                }
+            };
+
+            let key = match_key(&content);
+            if rows_by_key.contains_key(&key) {
+                seen.entry(key).or_default().push(location);
+            } else {
+                let qr_type = result.qr_type.unwrap_or_default();
+                unexpected_codes.push(ReconcileCode {
+                    location,
+                    content: Some(payload::mask_content(&qr_type, &content)),
+                    qr_type: Some(qr_type),
+                    error: None,
+                });
+            }
+        }
+    }
+
+    let mut rows: Vec<ReconcileRow> = items
+        .iter()
+        .map(|item| ReconcileRow {
+            row: item.row,
+            content: item.content.clone(),
+            label: item.label.clone(),
+            status: "missing".to_string(),
+            locations: vec![],
+        })
+        .collect();
+
+    // Hand out sightings to rows in order; extras make the last row a duplicate
+    for (key, indices) in &rows_by_key {
+        let mut locations = seen.remove(key).unwrap_or_default().into_iter();
+        for (n, &i) in indices.iter().enumerate() {
+            let is_last = n + 1 == indices.len();
+            rows[i].locations = if is_last {
+                locations.by_ref().collect()
+            } else {
+                locations.next().into_iter().collect()
+            };
+            rows[i].status = match rows[i].locations.len() {
+                0 => "missing",
+                1 => "found",
+                _ => "duplicate",
+            }
+            .to_string();
+        }
+    }
+
+    let count = |status: &str| rows.iter().filter(|r| r.status == status).count();
+    let (found, missing, duplicates) = (count("found"), count("missing"), count("duplicate"));
+
+    ReconcileReport {
+        success: missing == 0 && duplicates == 0 && unexpected_codes.is_empty(),
+        total_rows: rows.len(),
+        found,
+        missing,
+        duplicates,
+        unexpected: unexpected_codes.len(),
+        rows,
+        unexpected_codes,
+        unreadable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(row: usize, content: &str) -> BatchItem {
+        BatchItem {
+            row,
+            content: content.to_string(),
+            qr_type: "url".to_string(),
+            label: None,
+            original_content: None,
+            barcode_type: None,
+            barcode_data: None,
+        }
+    }
+
+    fn code(content: &str, page: Option<u32>) -> ScanResult {
+        ScanResult {
+            success: true,
+            content: Some(content.to_string()),
+            qr_type: Some(classify::detect_qr_type(content, &[])),
+            sub_type: None,
+            confidence: None,
+            parsed: None,
+            secrets_masked: false,
+            safety: None,
+            page,
+            strategy: None,
+            symbology: None,
+            eci: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_statuses() {
+        let items = vec![
+            item(1, "https://example.com/1"),
+            item(2, "https://example.com/2"),
+            item(3, "https://example.com/3"),
+        ];
+        let scanned = vec![
+            ("sheet1.png".to_string(), Ok(vec![code("https://example.com/1", None), code("HTTPS://EXAMPLE.COM/2", None)])),
+            ("sheet2.pdf".to_string(), Ok(vec![code("https://example.com/2", Some(1)), code("https://example.com/99", Some(2))])),
+            ("blurry.jpg".to_string(), Err("No QR code found in image".to_string())),
+        ];
+
+        let report = reconcile(&items, scanned);
+        assert!(!report.success);
+        assert_eq!((report.found, report.missing, report.duplicates, report.unexpected), (1, 1, 1, 1));
+
+        assert_eq!(report.rows[0].status, "found");
+        assert_eq!(report.rows[1].status, "duplicate");
+        assert_eq!(
+            report.rows[1].locations,
+            vec![
+                ScanLocation { file: "sheet1.png".to_string(), page: None },
+                ScanLocation { file: "sheet2.pdf".to_string(), page: Some(1) },
+            ]
+        );
+        assert_eq!(report.rows[2].status, "missing");
+
+        assert_eq!(report.unexpected_codes[0].content, Some("https://example.com/99".to_string()));
+        assert_eq!(report.unexpected_codes[0].location.page, Some(2));
+        assert_eq!(report.unreadable[0].location.file, "blurry.jpg");
+    }
+
+    #[test]
+    fn test_reconcile_rows_with_identical_content() {
+        let items = vec![item(1, "SAME"), item(2, "SAME")];
+
+        let report = reconcile(&items, vec![("a.png".to_string(), Ok(vec![code("SAME", None)]))]);
+        assert_eq!(report.rows[0].status, "found");
+        assert_eq!(report.rows[1].status, "missing");
+
+        let twice = vec![code("SAME", None), code("SAME", None)];
+        let report = reconcile(&items, vec![("a.png".to_string(), Ok(twice))]);
+        assert!(report.success);
+        assert_eq!(report.found, 2);
+    }
+
+    #[test]
+    fn test_reconcile_masks_unexpected_secrets() {
+        let otp = "otpauth://totp/ACME:bob?secret=JBSWY3DPEHPK3PXP";
+        let report = reconcile(&[item(1, "https://example.com")], vec![("a.png".to_string(), Ok(vec![code(otp, None)]))]);
+        assert_eq!(report.missing, 1);
+        assert_eq!(report.unexpected_codes[0].content, Some("otpauth://totp/ACME:bob?secret=****3PXP".to_string()));
+    }
+}
@@ -0,0 +1,153 @@
+use super::validate::{load_classifier_rules, scan_result_from_content, ScanResult};
+use crate::db::DbState;
+use crate::{pdf, render, structured};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::State;
+
+/// Quiet zone around rendered symbols, in modules
+const QUIET_ZONE: usize = 4;
+
+/// Pixels per module in PNG output when the caller doesn't ask
+const DEFAULT_MODULE_SIZE: u32 = 10;
+
+/// Where one part of a set was seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredAppendPart {
+    pub part: u8, // 1-based
+    pub file: String,
+    pub page: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredAppendSet {
+    pub total: u8,
+    pub parity: u8,
+    pub parts: Vec<StructuredAppendPart>,
+    pub missing: Vec<u8>,        // 1-based part numbers not found in any file
+    pub parity_ok: Option<bool>, // None until every part is present
+    pub result: Option<ScanResult>, // the reassembled content
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredAppendScanResult {
+    pub success: bool, // at least one set was reassembled
+    pub sets: Vec<StructuredAppendSet>,
+    pub unreadable: Vec<String>, // files that couldn't be opened
+    pub error: Option<String>,
+}
+
+/// Split content across up to 16 Structured Append symbols and return each
+/// one as a data URL, in sequence order
+#[tauri::command]
+pub async fn render_structured_append(
+    content: String,
+    ec_level: Option<String>,
+    max_version: Option<i16>,
+    format: String,
+    module_size: Option<u32>,
+) -> Result<Vec<String>, String> {
+    let ec_level = render::parse_ec_level(ec_level.as_deref().unwrap_or("M"))?;
+    let symbols = structured::encode(&content, ec_level, max_version.unwrap_or(40))?;
+    let module_size = module_size.unwrap_or(DEFAULT_MODULE_SIZE).clamp(1, 40);
+
+    symbols
+        .iter()
+        .map(|matrix| {
+            if format == "svg" {
+                let svg = render::render_matrix_svg(matrix, QUIET_ZONE);
+                Ok(format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg)))
+            } else {
+                let img = render::render_matrix_image(matrix, QUIET_ZONE, module_size);
+                let png = render::encode_png(&img)?;
+                Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+            }
+        })
+        .collect()
+}
+
+/// Find Structured Append symbols in images and PDFs, group them into sets,
+/// check parity and join every complete set back into the original content
+#[tauri::command]
+pub async fn scan_structured_append(
+    state: State<'_, DbState>,
+    file_paths: Vec<String>,
+    dpi: Option<f32>,
+    reveal_secrets: Option<bool>,
+) -> Result<StructuredAppendScanResult, String> {
+    let rules = load_classifier_rules(&state)?;
+    let dpi = pdf::clamp_dpi(dpi);
+
+    let mut parts = Vec::new();
+    let mut locations = Vec::new();
+    let mut unreadable = Vec::new();
+
+    for file_path in &file_paths {
+        let file = Path::new(file_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| file_path.clone());
+
+        let mut visit = |page: Option<u32>, img: image::DynamicImage| {
+            for part in structured::detect_parts(&img) {
+                locations.push((file.clone(), page));
+                parts.push(part);
+            }
+        };
+
+        let read = if pdf::is_pdf_path(file_path) {
+            fs::read(file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|bytes| pdf::for_each_page(&bytes, dpi, |page, img| visit(Some(page), img)))
+                .map(|_| ())
+        } else {
+            image::open(file_path)
+                .map_err(|e| format!("Failed to open image: {}", e))
+                .map(|img| visit(None, img))
+        };
+        if let Err(e) = read {
+            unreadable.push(format!("{}: {}", file, e));
+        }
+    }
+
+    let sets: Vec<StructuredAppendSet> = structured::reassemble(&parts)
+        .into_iter()
+        .map(|set| StructuredAppendSet {
+            total: set.total,
+            parity: set.parity,
+            parts: set
+                .parts
+                .iter()
+                .map(|(&index, &position)| StructuredAppendPart {
+                    part: index + 1,
+                    file: locations[position].0.clone(),
+                    page: locations[position].1,
+                })
+                .collect(),
+            missing: set.missing.iter().map(|i| i + 1).collect(),
+            parity_ok: set.parity_ok,
+            result: set
+                .content
+                .map(|content| scan_result_from_content(content, &rules, reveal_secrets.unwrap_or(false))),
+        })
+        .collect();
+
+    let success = sets.iter().any(|s| s.result.is_some());
+    let error = match (sets.is_empty(), success) {
+        (true, _) => Some("No Structured Append symbols found".to_string()),
+        (false, false) => Some("No set could be completed".to_string()),
+        _ => None,
+    };
+
+    Ok(StructuredAppendScanResult {
+        success,
+        sets,
+        unreadable,
+        error,
+    })
+}
@@ -0,0 +1,108 @@
+use crate::commands::SymbolFit;
+use crate::qr::{self, QrOptions, SegmentReport};
+use crate::render::{self, ModuleMatrix};
+use crate::symbology::{self, EncodeOptions, Symbology};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+/// Pixels per module in PNG output when the caller doesn't ask
+const DEFAULT_MODULE_SIZE: u32 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedCode {
+    pub image: String,     // PNG or SVG data URL
+    pub symbology: String, // "qr" | "micro-qr" | "rmqr" | "data-matrix" | "aztec" | "pdf417"
+    pub width: usize,      // in modules, without the quiet zone
+    pub height: usize,
+    pub fit: Option<SymbolFit>, // QR, Micro QR and rMQR only
+    pub ec_level: Option<String>, // QR only: the level used, which `boost_ec` may raise
+    pub mask: Option<u8>,         // QR only: mask pattern 0..=7
+}
+
+/// Render content in any supported symbology. `version` picks a QR, Micro QR
+/// or rMQR version; `gs1` encodes GS1 element strings in Data Matrix, with
+/// fields separated by GS (0x1D); `qr_options` picks Kanji mode, a character
+/// set and ECI header, a fixed mask or an error correction boost for QR codes.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn render_symbol(
+    content: String,
+    symbology: String,
+    ec_level: Option<String>,
+    version: Option<String>,
+    gs1: Option<bool>,
+    qr_options: Option<QrOptions>,
+    format: String,
+    module_size: Option<u32>,
+) -> Result<RenderedCode, String> {
+    let symbology = Symbology::parse(&symbology)?;
+    let options = EncodeOptions {
+        ec_level,
+        version,
+        gs1: gs1.unwrap_or(false),
+        qr: qr_options.unwrap_or_default(),
+    };
+    let encoded = symbology::encode(symbology, &content, &options)?;
+
+    Ok(RenderedCode {
+        image: matrix_data_url(&encoded.matrix, symbology.quiet_zone(), &format, module_size)?,
+        symbology: symbology.name().to_string(),
+        width: encoded.matrix.width(),
+        height: encoded.matrix.height(),
+        fit: encoded.fit.as_ref().map(SymbolFit::from),
+        ec_level: encoded.ec_level.map(|level| render::ec_level_name(level).to_string()),
+        mask: encoded.mask,
+    })
+}
+
+/// Names of every symbology `render_symbol` and the scanners understand
+#[tauri::command]
+pub async fn list_symbologies() -> Result<Vec<String>, String> {
+    Ok(symbology::SYMBOLOGIES.iter().map(|s| s.to_string()).collect())
+}
+
+/// How the QR encoder splits content into numeric, alphanumeric, byte and
+/// Kanji segments, and the bits that saves over a single byte segment
+#[tauri::command]
+pub async fn analyze_qr_segments(
+    content: String,
+    ec_level: Option<String>,
+    qr_options: Option<QrOptions>,
+) -> Result<SegmentReport, String> {
+    let ec_level = render::parse_ec_level(ec_level.as_deref().unwrap_or("M"))?;
+    qr::segment_report(&content, ec_level, &qr_options.unwrap_or_default())
+}
+
+/// A module matrix as an SVG or PNG data URL
+pub(crate) fn matrix_data_url(
+    matrix: &ModuleMatrix,
+    quiet_zone: usize,
+    format: &str,
+    module_size: Option<u32>,
+) -> Result<String, String> {
+    let mime = if format == "svg" { "image/svg+xml" } else { "image/png" };
+    let bytes = matrix_file(matrix, quiet_zone, format, module_size)?;
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes)))
+}
+
+/// A module matrix as SVG or PNG file bytes
+pub(crate) fn matrix_file(
+    matrix: &ModuleMatrix,
+    quiet_zone: usize,
+    format: &str,
+    module_size: Option<u32>,
+) -> Result<Vec<u8>, String> {
+    if format == "svg" {
+        Ok(render::render_matrix_svg(matrix, quiet_zone).into_bytes())
+    } else {
+        let img = render::render_matrix_image(matrix, quiet_zone, png_module_size(module_size));
+        render::encode_png(&img)
+    }
+}
+
+/// Pixels per module in PNG output, within the supported range
+pub(crate) fn png_module_size(module_size: Option<u32>) -> u32 {
+    module_size.unwrap_or(DEFAULT_MODULE_SIZE).clamp(1, 40)
+}
+
@@ -1,8 +1,22 @@
+use crate::classify::{self, ClassifierRule};
+use crate::db::DbState;
+use crate::media;
+use crate::payload::{self, ParsedPayload};
+use crate::pdf;
+use crate::preprocess;
+use crate::qr;
+use crate::render::ModuleMatrix;
+use crate::safety::{self, UrlSafetyReport};
+use crate::symbology::{self, Symbology};
+use crate::urls;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use image::{DynamicImage, ImageReader};
 use rqrr::PreparedImage;
 use serde::{Deserialize, Serialize};
+use std::fs;
 use std::io::Cursor;
+use std::path::Path;
+use tauri::State;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,15 +26,22 @@ pub struct ValidationResult {
     pub content_match: bool,
     pub message: String,
     pub suggestions: Vec<String>,
+    pub safety: Option<UrlSafetyReport>, // checks on the expected content, if it is a link
 }
 
 /// Validate a QR code by decoding it and comparing with expected content
-/// Takes a base64-encoded PNG image and the expected content string
+/// Takes a base64-encoded PNG image and the expected content string. When
+/// `symbology` is given (a 2D symbology or linear barcode type), a code of
+/// another kind only passes with a warning.
 #[tauri::command]
 pub async fn validate_qr(
     image_data: String,
     expected_content: String,
+    symbology: Option<String>,
 ) -> Result<ValidationResult, String> {
+    let expected_symbology = symbology.as_deref().map(symbology::lookup).transpose()?;
+    let safety = safety::analyze_url(&expected_content);
+
     // Strip data URL prefix if present
     let base64_data = if image_data.contains(",") {
         image_data.split(",").nth(1).unwrap_or(&image_data)
@@ -40,85 +61,130 @@ pub async fn validate_qr(
         .decode()
         .map_err(|e| format!("Failed to decode image: {}", e))?;
 
-    // Convert to grayscale for QR detection
-    let gray_img = img.to_luma8();
-
-    // Prepare image for QR detection
-    let mut prepared = PreparedImage::prepare(gray_img);
-
-    // Try to find and decode QR codes
-    let grids = prepared.detect_grids();
+    Ok(validate_image(&img.to_luma8(), &expected_content, expected_symbology, safety))
+}
 
-    if grids.is_empty() {
-        return Ok(ValidationResult {
+/// Read the first code in an image and compare it with the expected content
+/// and, when given, the expected (name, label) of its symbology
+fn validate_image(
+    gray_img: &image::GrayImage,
+    expected_content: &str,
+    expected_symbology: Option<(&'static str, &'static str)>,
+    safety: Option<UrlSafetyReport>,
+) -> ValidationResult {
+    let found_label = |found: &'static str| symbology::lookup(found).map_or(found, |(_, label)| label);
+
+    match symbology::read_first(gray_img) {
+        None => ValidationResult {
             state: "fail".to_string(),
             decoded_content: None,
             content_match: false,
-            message: "No QR code detected in image".to_string(),
+            message: format!(
+                "No {} detected in image",
+                expected_symbology.map_or(Symbology::Qr.label(), |(_, label)| label)
+            ),
             suggestions: vec![
                 "Increase error correction level to H".to_string(),
                 "Reduce logo size if using one".to_string(),
                 "Ensure sufficient contrast between colors".to_string(),
             ],
-        });
+            safety,
+        },
+        Some((found, Ok(content))) => {
+            let mut result = compare_decoded(content, expected_content, found_label(found), safety);
+            if let Some((_, label)) = expected_symbology.filter(|(name, _)| *name != found) {
+                let mismatch = format!("Expected {} but found {}", label, found_label(found));
+                result.state = "warn".to_string();
+                result.message = if result.content_match {
+                    mismatch
+                } else {
+                    format!("{}. {}", result.message, mismatch)
+                };
+                result.suggestions.push(format!("Render the code as {}", label));
+            }
+            result
+        }
+        // Grid detected but couldn't decode - marginal case
+        Some((found, Err(_))) => ValidationResult {
+            state: "warn".to_string(),
+            decoded_content: None,
+            content_match: false,
+            message: format!("{} detected but decode was unreliable", found_label(found)),
+            suggestions: vec![
+                "Increase error correction level".to_string(),
+                "Reduce customization complexity".to_string(),
+                "Ensure logo doesn't cover critical areas".to_string(),
+            ],
+            safety,
+        },
     }
+}
 
-    // Try to decode the first grid found
-    match grids[0].decode() {
-        Ok((_, content)) => {
-            let decoded_str = content.to_string();
-            let content_match = decoded_str.trim() == expected_content.trim();
-
-            if content_match {
-                Ok(ValidationResult {
-                    state: "pass".to_string(),
-                    decoded_content: Some(decoded_str),
-                    content_match: true,
-                    message: "QR code scans correctly".to_string(),
-                    suggestions: vec![],
-                })
-            } else {
-                // Content decoded but doesn't match - this is unusual
-                Ok(ValidationResult {
-                    state: "warn".to_string(),
-                    decoded_content: Some(decoded_str.clone()),
-                    content_match: false,
-                    message: format!("Decoded content differs from expected"),
-                    suggestions: vec![
-                        "Verify the QR content is correct".to_string(),
-                    ],
-                })
-            }
+/// Pass when the decoded content matches what was expected, warn otherwise
+fn compare_decoded(
+    decoded_str: String,
+    expected_content: &str,
+    label: &str,
+    safety: Option<UrlSafetyReport>,
+) -> ValidationResult {
+    let content_match =
+        decoded_str.trim() == expected_content.trim() || urls::same_url(&decoded_str, expected_content);
+
+    if content_match {
+        ValidationResult {
+            state: "pass".to_string(),
+            decoded_content: Some(decoded_str),
+            content_match: true,
+            message: format!("{} scans correctly", label),
+            suggestions: vec![],
+            safety,
         }
-        Err(_) => {
-            // Grid detected but couldn't decode - marginal case
-            Ok(ValidationResult {
-                state: "warn".to_string(),
-                decoded_content: None,
-                content_match: false,
-                message: "QR code detected but decode was unreliable".to_string(),
-                suggestions: vec![
-                    "Increase error correction level".to_string(),
-                    "Reduce customization complexity".to_string(),
-                    "Ensure logo doesn't cover critical areas".to_string(),
-                ],
-            })
+    } else {
+        // Content decoded but doesn't match - this is unusual
+        ValidationResult {
+            state: "warn".to_string(),
+            decoded_content: Some(decoded_str),
+            content_match: false,
+            message: "Decoded content differs from expected".to_string(),
+            suggestions: vec![
+                format!("Verify the {} content is correct", label),
+            ],
+            safety,
         }
     }
 }
 
-/// Decode a QR code from an image file path
+/// Decode a QR code from an image or PDF file path. For a PDF, the first code
+/// found at the default DPI is returned; use `scan_qr_from_pdf` for all of them.
+/// Secrets such as OTP keys are masked unless `reveal_secrets` is set.
 #[tauri::command]
-pub async fn scan_qr_from_file(file_path: String) -> Result<ScanResult, String> {
+pub async fn scan_qr_from_file(
+    state: State<'_, DbState>,
+    file_path: String,
+    reveal_secrets: Option<bool>,
+) -> Result<ScanResult, String> {
+    let rules = load_classifier_rules(&state)?;
+    let reveal_secrets = reveal_secrets.unwrap_or(false);
+
+    if pdf::is_pdf_path(&file_path) {
+        let bytes = fs::read(&file_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+        return first_pdf_result(&bytes, &rules, reveal_secrets);
+    }
+
     let img = image::open(&file_path)
         .map_err(|e| format!("Failed to open image: {}", e))?;
 
-    decode_qr_from_image(img)
+    decode_qr_from_image(img, &rules, reveal_secrets)
 }
 
-/// Decode a QR code from base64 image data
+/// Decode a QR code from base64 image or PDF data.
+/// Secrets such as OTP keys are masked unless `reveal_secrets` is set.
 #[tauri::command]
-pub async fn scan_qr_from_data(image_data: String) -> Result<ScanResult, String> {
+pub async fn scan_qr_from_data(
+    state: State<'_, DbState>,
+    image_data: String,
+    reveal_secrets: Option<bool>,
+) -> Result<ScanResult, String> {
     // Strip data URL prefix if present
     let base64_data = if image_data.contains(",") {
         image_data.split(",").nth(1).unwrap_or(&image_data)
@@ -130,13 +196,76 @@ pub async fn scan_qr_from_data(image_data: String) -> Result<ScanResult, String>
         .decode(base64_data)
         .map_err(|e| format!("Failed to decode base64: {}", e))?;
 
-    let img = ImageReader::new(Cursor::new(&image_bytes))
-        .with_guessed_format()
-        .map_err(|e| format!("Failed to read image format: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let rules = load_classifier_rules(&state)?;
+    let reveal_secrets = reveal_secrets.unwrap_or(false);
 
-    decode_qr_from_image(img)
+    if pdf::is_pdf_data(&image_bytes) {
+        return first_pdf_result(&image_bytes, &rules, reveal_secrets);
+    }
+
+    let img = image_from_bytes(&image_bytes)?;
+
+    decode_qr_from_image(img, &rules, reveal_secrets)
+}
+
+/// Decode a QR code from the image on the clipboard, such as a pasted screenshot.
+/// Secrets such as OTP keys are masked unless `reveal_secrets` is set.
+#[tauri::command]
+pub async fn scan_qr_from_clipboard(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    reveal_secrets: Option<bool>,
+) -> Result<ScanResult, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let clipboard_image = app
+        .clipboard()
+        .read_image()
+        .map_err(|_| "The clipboard does not contain an image".to_string())?;
+
+    let img = clipboard_to_image(
+        clipboard_image.rgba(),
+        clipboard_image.width(),
+        clipboard_image.height(),
+    )?;
+
+    let rules = load_classifier_rules(&state)?;
+    decode_qr_from_image(img, &rules, reveal_secrets.unwrap_or(false))
+}
+
+/// Rasterise every page of a PDF and decode all QR codes on it.
+/// `dpi` defaults to 150 and is clamped to 36-600.
+#[tauri::command]
+pub async fn scan_qr_from_pdf(
+    state: State<'_, DbState>,
+    file_path: String,
+    dpi: Option<f32>,
+    reveal_secrets: Option<bool>,
+) -> Result<PdfScanResult, String> {
+    let bytes = fs::read(&file_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+    if !pdf::is_pdf_data(&bytes) {
+        return Err("File is not a PDF".to_string());
+    }
+
+    let rules = load_classifier_rules(&state)?;
+    Ok(scan_pdf(&bytes, pdf::clamp_dpi(dpi), &rules, reveal_secrets.unwrap_or(false)))
+}
+
+/// Decode every frame of an animated GIF/APNG/WebP and return each distinct
+/// code with the time ranges it is visible in.
+#[tauri::command]
+pub async fn scan_qr_from_media(
+    state: State<'_, DbState>,
+    file_path: String,
+    reveal_secrets: Option<bool>,
+) -> Result<MediaScanResult, String> {
+    let rules = load_classifier_rules(&state)?;
+    let path = Path::new(&file_path);
+
+    if !media::is_animation_path(path) {
+        return Err("Unsupported file type; expected a GIF, APNG or WebP animation".to_string());
+    }
+    Ok(scan_media(path, &rules, reveal_secrets.unwrap_or(false)))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,126 +274,364 @@ pub struct ScanResult {
     pub success: bool,
     pub content: Option<String>,
     pub qr_type: Option<String>,
+    pub sub_type: Option<String>,
+    pub confidence: Option<f32>,
+    pub parsed: Option<ParsedPayload>,
+    pub secrets_masked: bool,
+    pub safety: Option<UrlSafetyReport>,
+    pub page: Option<u32>, // 1-based page number when scanning a PDF
+    pub strategy: Option<String>, // preprocessing that made the image readable, e.g. "inverted"
+    pub symbology: Option<String>, // symbology::SYMBOLOGIES or barcode::TYPES, for codes read from images
+    pub eci: Option<String>, // character set declared by a QR code's ECI header, e.g. "utf-8"
     pub error: Option<String>,
 }
 
-fn decode_qr_from_image(img: DynamicImage) -> Result<ScanResult, String> {
-    let gray_img = img.to_luma8();
-    let mut prepared = PreparedImage::prepare(gray_img);
-    let grids = prepared.detect_grids();
-
-    if grids.is_empty() {
-        return Ok(ScanResult {
+impl ScanResult {
+    fn failure(error: String) -> Self {
+        Self {
             success: false,
             content: None,
             qr_type: None,
-            error: Some("No QR code found in image".to_string()),
-        });
+            sub_type: None,
+            confidence: None,
+            parsed: None,
+            secrets_masked: false,
+            safety: None,
+            page: None,
+            strategy: None,
+            symbology: None,
+            eci: None,
+            error: Some(error),
+        }
     }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfScanResult {
+    pub success: bool, // at least one code decoded
+    pub page_count: u32,
+    pub dpi: f32,
+    pub results: Vec<ScanResult>, // one per code found, including ones that failed to decode
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaScanResult {
+    pub success: bool, // at least one code decoded
+    pub frame_count: u32,
+    pub codes: Vec<MediaCode>, // in order of first appearance
+    pub error: Option<String>,
+}
+
+/// A distinct code and when it is on screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaCode {
+    pub result: ScanResult, // from the first frame it appears in
+    pub appearances: Vec<TimeRange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Custom classifier rules from settings
+pub(crate) fn load_classifier_rules(state: &State<'_, DbState>) -> Result<Vec<ClassifierRule>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    classify::load_rules(&conn)
+}
 
-    match grids[0].decode() {
-        Ok((_, content)) => {
-            let content_str = content.to_string();
-            let qr_type = detect_qr_type(&content_str);
-
-            Ok(ScanResult {
-                success: true,
-                content: Some(content_str),
-                qr_type: Some(qr_type),
-                error: None,
-            })
+fn scan_pdf(bytes: &[u8], dpi: f32, rules: &[ClassifierRule], reveal_secrets: bool) -> PdfScanResult {
+    let mut results = Vec::new();
+    let pages = pdf::for_each_page(bytes, dpi, |page, img| {
+        for mut result in decode_all_qr_from_image(img, rules, reveal_secrets) {
+            result.page = Some(page);
+            results.push(result);
         }
-        Err(e) => Ok(ScanResult {
+    });
+
+    match pages {
+        Ok(page_count) => PdfScanResult {
+            success: results.iter().any(|r| r.success),
+            page_count,
+            dpi,
+            error: results
+                .is_empty()
+                .then(|| "No QR code found in PDF".to_string()),
+            results,
+        },
+        Err(e) => PdfScanResult {
             success: false,
-            content: None,
-            qr_type: None,
-            error: Some(format!("Failed to decode QR: {:?}", e)),
-        }),
+            page_count: 0,
+            dpi,
+            results: vec![],
+            error: Some(e),
+        },
     }
 }
 
-fn detect_qr_type(content: &str) -> String {
-    let lower = content.to_lowercase();
-
-    if lower.starts_with("wifi:") {
-        "wifi".to_string()
-    } else if lower.starts_with("begin:vcard") {
-        "vcard".to_string()
-    } else if lower.starts_with("mailto:") {
-        "email".to_string()
-    } else if lower.starts_with("sms:") || lower.starts_with("smsto:") {
-        "sms".to_string()
-    } else if lower.starts_with("tel:") {
-        "phone".to_string()
-    } else if lower.starts_with("geo:") {
-        "geo".to_string()
-    } else if lower.starts_with("begin:vcalendar") || lower.starts_with("begin:vevent") {
-        "calendar".to_string()
-    } else if lower.starts_with("bitcoin:") {
-        "bitcoin".to_string()
-    } else if lower.starts_with("https://search.google.com/local/writereview") {
-        "google-review".to_string()
-    } else if lower.starts_with("http://") || lower.starts_with("https://") {
-        "url".to_string()
+/// Decode every code in an image or PDF file. Errors when nothing is found.
+pub(crate) fn scan_path(
+    path: &Path,
+    dpi: f32,
+    rules: &[ClassifierRule],
+    reveal_secrets: bool,
+) -> Result<Vec<ScanResult>, String> {
+    let results = if pdf::is_pdf_path(&path.to_string_lossy()) {
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+        let scan = scan_pdf(&bytes, dpi, rules, reveal_secrets);
+        if let (true, Some(error)) = (scan.results.is_empty(), scan.error) {
+            return Err(error);
+        }
+        scan.results
     } else {
-        "text".to_string()
+        let img = image::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+        decode_all_qr_from_image(img, rules, reveal_secrets)
+    };
+
+    if results.is_empty() {
+        return Err("No QR code found in image".to_string());
     }
+    Ok(results)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_detect_qr_type_all_types() {
-        // WiFi
-        assert_eq!(detect_qr_type("WIFI:T:WPA;S:Network;;"), "wifi");
+fn scan_media(path: &Path, rules: &[ClassifierRule], reveal_secrets: bool) -> MediaScanResult {
+    let mut codes: Vec<(String, MediaCode)> = Vec::new();
+
+    let mut visit = |frame: media::Frame| {
+        // Decode unmasked so distinct secrets aren't merged, then mask once per code
+        for result in decode_all_qr_from_image(frame.image, rules, true) {
+            let Some(content) = result.content.clone().filter(|_| result.success) else {
+                continue;
+            };
+            let span = TimeRange {
+                start_ms: frame.start_ms,
+                end_ms: frame.end_ms,
+            };
+
+            match codes.iter_mut().find(|(key, _)| *key == content) {
+                Some((_, code)) => match code.appearances.last_mut() {
+                    Some(last) if last.end_ms >= span.start_ms => last.end_ms = span.end_ms,
+                    _ => code.appearances.push(span),
+                },
+                None => {
+                    let mut result = result;
+                    if !reveal_secrets {
+                        let qr_type = result.qr_type.clone().unwrap_or_default();
+                        result.content = Some(payload::mask_content(&qr_type, &content));
+                        result.parsed = result.parsed.map(|p| p.masked());
+                        result.secrets_masked = qr_type == "otp";
+                    }
+                    codes.push((
+                        content,
+                        MediaCode {
+                            result,
+                            appearances: vec![span],
+                        },
+                    ));
+                }
+            }
+        }
+    };
 
-        // vCard
-        assert_eq!(detect_qr_type("BEGIN:VCARD\nVERSION:3.0"), "vcard");
+    match media::for_each_animation_frame(path, &mut visit) {
+        Ok(frame_count) => MediaScanResult {
+            success: !codes.is_empty(),
+            frame_count,
+            error: codes
+                .is_empty()
+                .then(|| "No QR code found in any frame".to_string()),
+            codes: codes.into_iter().map(|(_, code)| code).collect(),
+        },
+        Err(e) => MediaScanResult {
+            success: false,
+            frame_count: 0,
+            codes: vec![],
+            error: Some(e),
+        },
+    }
+}
 
-        // Email
-        assert_eq!(detect_qr_type("mailto:test@example.com"), "email");
+fn first_pdf_result(
+    bytes: &[u8],
+    rules: &[ClassifierRule],
+    reveal_secrets: bool,
+) -> Result<ScanResult, String> {
+    let scan = scan_pdf(bytes, pdf::DEFAULT_DPI, rules, reveal_secrets);
+    let first = scan
+        .results
+        .iter()
+        .find(|r| r.success)
+        .or(scan.results.first())
+        .cloned();
+
+    Ok(first.unwrap_or_else(|| {
+        ScanResult::failure(scan.error.unwrap_or_else(|| "No QR code found in PDF".to_string()))
+    }))
+}
 
-        // SMS
-        assert_eq!(detect_qr_type("sms:+15551234567"), "sms");
-        assert_eq!(detect_qr_type("smsto:+15551234567"), "sms");
+/// Decode image bytes in any supported format
+pub(crate) fn image_from_bytes(bytes: &[u8]) -> Result<DynamicImage, String> {
+    ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image format: {}", e))?
+        .decode()
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
 
-        // Phone
-        assert_eq!(detect_qr_type("tel:+15551234567"), "phone");
+/// Wrap raw RGBA pixels from the clipboard in an image
+fn clipboard_to_image(rgba: &[u8], width: u32, height: u32) -> Result<DynamicImage, String> {
+    if width == 0 || height == 0 {
+        return Err("The clipboard does not contain an image".to_string());
+    }
+    image::RgbaImage::from_raw(width, height, rgba.to_vec())
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| "Clipboard image data is incomplete".to_string())
+}
 
-        // Geo
-        assert_eq!(detect_qr_type("geo:37.7749,-122.4194"), "geo");
+fn decode_qr_from_image(
+    img: DynamicImage,
+    rules: &[ClassifierRule],
+    reveal_secrets: bool,
+) -> Result<ScanResult, String> {
+    Ok(decode_all_qr_from_image(img, rules, reveal_secrets)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| ScanResult::failure("No QR code found in image".to_string())))
+}
 
-        // Calendar
-        assert_eq!(detect_qr_type("BEGIN:VCALENDAR\r\nVERSION:2.0"), "calendar");
-        assert_eq!(detect_qr_type("BEGIN:VEVENT"), "calendar");
+/// Decode every QR code found in an image
+fn decode_all_qr_from_image(
+    img: DynamicImage,
+    rules: &[ClassifierRule],
+    reveal_secrets: bool,
+) -> Vec<ScanResult> {
+    detect_codes(&img)
+        .into_iter()
+        .map(|code| match code.content {
+            Ok(content) => ScanResult {
+                strategy: Some(code.strategy.to_string()),
+                symbology: Some(code.symbology.to_string()),
+                eci: code.eci.map(str::to_string),
+                ..scan_result_from_content(content, rules, reveal_secrets)
+            },
+            Err(e) => ScanResult::failure(e),
+        })
+        .collect()
+}
 
-        // Bitcoin
-        assert_eq!(detect_qr_type("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"), "bitcoin");
-        assert_eq!(detect_qr_type("BITCOIN:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=0.5"), "bitcoin");
+/// A code located in one of the preprocessed versions of an image
+pub(crate) struct DetectedCode {
+    pub strategy: &'static str,
+    pub image_index: usize, // which of the strategy's images it was found in
+    pub symbology: &'static str, // symbology::SYMBOLOGIES or barcode::TYPES
+    pub grid: Option<CodeGrid>, // QR codes only
+    pub content: Result<String, String>,
+    pub eci: Option<&'static str>, // QR codes only, see qr::CHARSETS
+}
 
-        // Google Review
-        assert_eq!(detect_qr_type("https://search.google.com/local/writereview?placeid=ChIJN1t_tDeuEmsRUsoyG83frY4"), "google-review");
+/// Where a QR code sits in its image and the modules read from it
+pub(crate) struct CodeGrid {
+    pub bounds: [(f64, f64); 4], // top-left, top-right, bottom-right, bottom-left
+    pub modules: ModuleMatrix,
+}
 
-        // URL
-        assert_eq!(detect_qr_type("https://example.com"), "url");
-        assert_eq!(detect_qr_type("http://example.com"), "url");
+/// Locate QR codes, trying each preprocessing strategy in turn until one
+/// yields a readable code. Other symbologies are looked for when no QR code
+/// can be read. Codes that were found but couldn't be read are
+/// returned if nothing works.
+pub(crate) fn detect_codes(img: &DynamicImage) -> Vec<DetectedCode> {
+    let mut unreadable = Vec::new();
+
+    for &strategy in preprocess::STRATEGIES {
+        for (image_index, gray_img) in preprocess::apply(strategy, img).into_iter().enumerate() {
+            let mut prepared = PreparedImage::prepare(gray_img.clone());
+            let mut codes: Vec<DetectedCode> = prepared
+                .detect_grids()
+                .iter()
+                .map(|grid| {
+                    let text = qr::decode_grid(grid);
+                    DetectedCode {
+                        strategy,
+                        image_index,
+                        symbology: "qr",
+                        grid: Some(CodeGrid {
+                            bounds: grid.bounds.map(|p| (p.x as f64, p.y as f64)),
+                            modules: qr::grid_modules(&grid.grid),
+                        }),
+                        eci: text.as_ref().ok().and_then(|text| text.eci),
+                        content: text.map(|text| text.content),
+                    }
+                })
+                .collect();
+
+            if !codes.iter().any(|c| c.content.is_ok()) {
+                codes.extend(symbology::read(&gray_img).into_iter().map(|(symbology, content)| DetectedCode {
+                    strategy,
+                    image_index,
+                    symbology,
+                    grid: None,
+                    content: Ok(content),
+                    eci: None,
+                }));
+            }
 
-        // Text (default)
-        assert_eq!(detect_qr_type("Hello World"), "text");
-        assert_eq!(detect_qr_type("12345"), "text");
+            if codes.iter().any(|c| c.content.is_ok()) {
+                return codes;
+            }
+            if unreadable.is_empty() {
+                unreadable = codes;
+            }
+        }
     }
+    unreadable
+}
 
-    #[test]
-    fn test_detect_qr_type_case_insensitive() {
-        assert_eq!(detect_qr_type("WIFI:T:WPA;;"), "wifi");
-        assert_eq!(detect_qr_type("wifi:t:wpa;;"), "wifi");
-        assert_eq!(detect_qr_type("MAILTO:test@example.com"), "email");
-        assert_eq!(detect_qr_type("TEL:+15551234567"), "phone");
-        assert_eq!(detect_qr_type("GEO:0,0"), "geo");
-        assert_eq!(detect_qr_type("HTTPS://EXAMPLE.COM"), "url");
+pub(crate) fn scan_result_from_content(
+    content_str: String,
+    rules: &[ClassifierRule],
+    reveal_secrets: bool,
+) -> ScanResult {
+    let classification = classify::classify(&content_str, rules);
+    let qr_type = classification.qr_type;
+    let parsed = payload::parse_payload(&qr_type, &content_str);
+    let safety = safety::analyze_url(&content_str);
+
+    let (content_str, parsed) = if reveal_secrets {
+        (content_str, parsed)
+    } else {
+        (
+            payload::mask_content(&qr_type, &content_str),
+            parsed.map(|p| p.masked()),
+        )
+    };
+    let secrets_masked = !reveal_secrets && qr_type == "otp";
+
+    ScanResult {
+        success: true,
+        content: Some(content_str),
+        qr_type: Some(qr_type),
+        sub_type: classification.sub_type,
+        confidence: Some(classification.confidence),
+        parsed,
+        secrets_masked,
+        safety,
+        page: None,
+        strategy: None,
+        symbology: None,
+        eci: None,
+        error: None,
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_validation_result_structure() {
@@ -274,6 +641,7 @@ mod tests {
             content_match: true,
             message: "Success".to_string(),
             suggestions: vec![],
+            safety: None,
         };
 
         assert_eq!(result.state, "pass");
@@ -288,18 +656,37 @@ mod tests {
             success: true,
             content: Some("https://example.com".to_string()),
             qr_type: Some("url".to_string()),
+            sub_type: None,
+            confidence: Some(0.9),
+            parsed: None,
+            secrets_masked: false,
+            safety: None,
+            page: None,
+            strategy: None,
+            symbology: None,
+            eci: None,
             error: None,
         };
 
         assert!(success_result.success);
         assert_eq!(success_result.content, Some("https://example.com".to_string()));
         assert_eq!(success_result.qr_type, Some("url".to_string()));
+        assert_eq!(success_result.confidence, Some(0.9));
         assert!(success_result.error.is_none());
 
         let failure_result = ScanResult {
             success: false,
             content: None,
             qr_type: None,
+            sub_type: None,
+            confidence: None,
+            parsed: None,
+            secrets_masked: false,
+            safety: None,
+            page: None,
+            strategy: None,
+            symbology: None,
+            eci: None,
             error: Some("No QR code found".to_string()),
         };
 
@@ -307,4 +694,245 @@ mod tests {
         assert!(failure_result.content.is_none());
         assert!(failure_result.error.is_some());
     }
+
+    fn qr_image(content: &str) -> DynamicImage {
+        let code = crate::render::encode_qr(content, qrcode::EcLevel::M).unwrap();
+        let matrix = crate::render::ModuleMatrix::from_qr(&code);
+        let size = (matrix.width() as u32 + 8) * 8;
+
+        DynamicImage::ImageLuma8(image::GrayImage::from_fn(size, size, |x, y| {
+            let (mx, my) = ((x / 8) as usize, (y / 8) as usize);
+            let dark = mx >= 4 && my >= 4 && matrix.is_dark(mx - 4, my - 4);
+            image::Luma([if dark { 0 } else { 255 }])
+        }))
+    }
+
+    #[test]
+    fn test_decode_masks_otp_secret() {
+        let uri = "otpauth://totp/ACME:alice?secret=JBSWY3DPEHPK3PXP&issuer=ACME";
+
+        let masked = decode_qr_from_image(qr_image(uri), &[], false).unwrap();
+        assert_eq!(masked.qr_type, Some("otp".to_string()));
+        assert_eq!(masked.sub_type, Some("totp".to_string()));
+        assert_eq!(
+            masked.content,
+            Some("otpauth://totp/ACME:alice?secret=****3PXP&issuer=ACME".to_string())
+        );
+        assert!(masked.secrets_masked);
+        match masked.parsed {
+            Some(ParsedPayload::Otp(otp)) => assert_eq!(otp.secret, "****3PXP"),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+
+        let revealed = decode_qr_from_image(qr_image(uri), &[], true).unwrap();
+        assert_eq!(revealed.content, Some(uri.to_string()));
+        assert!(!revealed.secrets_masked);
+    }
+
+    #[test]
+    fn test_decode_reports_link_safety() {
+        let result = decode_qr_from_image(qr_image("http://bit.ly/3abcDEF"), &[], false).unwrap();
+        let safety = result.safety.unwrap();
+        assert_eq!(safety.level, "caution");
+        let codes: Vec<&str> = safety.findings.iter().map(|f| f.code.as_str()).collect();
+        assert_eq!(codes, vec!["insecure-http", "url-shortener"]);
+
+        let result = decode_qr_from_image(qr_image("Hello World"), &[], false).unwrap();
+        assert!(result.safety.is_none());
+    }
+
+    #[test]
+    fn test_decode_all_finds_every_code() {
+        let left = qr_image("https://example.com/a").to_luma8();
+        let right = qr_image("https://example.com/b").to_luma8();
+        let mut sheet = image::GrayImage::from_pixel(left.width() + right.width(), left.height(), image::Luma([255]));
+        image::imageops::replace(&mut sheet, &left, 0, 0);
+        image::imageops::replace(&mut sheet, &right, left.width() as i64, 0);
+
+        let mut contents: Vec<String> = decode_all_qr_from_image(DynamicImage::ImageLuma8(sheet), &[], false)
+            .into_iter()
+            .filter_map(|r| r.content)
+            .collect();
+        contents.sort();
+        assert_eq!(contents, vec!["https://example.com/a", "https://example.com/b"]);
+
+        let blank = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(64, 64, image::Luma([255])));
+        let result = decode_qr_from_image(blank, &[], false).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.page, None);
+    }
+
+    /// A one-page PDF showing `img` as an uncompressed grayscale image
+    fn pdf_with_image(img: &image::GrayImage) -> Vec<u8> {
+        let (width, height) = img.dimensions();
+        let contents = b"q 200 0 0 200 50 50 cm /Im0 Do Q";
+        let objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 300 300] /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>".to_vec(),
+            crate::render::pdf::stream(
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8",
+                    width, height
+                ),
+                img.as_raw(),
+            ),
+            crate::render::pdf::stream("", contents),
+        ];
+        crate::render::pdf::write_pdf(&objects)
+    }
+
+    #[test]
+    #[ignore = "needs PDFium: run `npm run fetch:pdfium` and put src-tauri/pdfium on the library path"]
+    fn test_scan_pdf_finds_code_on_page() {
+        let bytes = pdf_with_image(&qr_image("https://example.com/flyer").to_luma8());
+        assert!(pdf::is_pdf_data(&bytes));
+
+        let result = scan_pdf(&bytes, pdf::DEFAULT_DPI, &[], false);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.page_count, 1);
+        assert_eq!(result.results.len(), 1);
+        assert_eq!(result.results[0].content, Some("https://example.com/flyer".to_string()));
+        assert_eq!(result.results[0].page, Some(1));
+
+        let broken = scan_pdf(b"%PDF-1.4\nnot really", pdf::DEFAULT_DPI, &[], false);
+        assert!(!broken.success);
+        assert!(broken.error.is_some());
+    }
+
+    #[test]
+    fn test_scan_media_reports_time_ranges() {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame};
+
+        let dir = tempfile::tempdir().unwrap();
+        let gif = |name: &str, content: &str| {
+            let code = qr_image(content).to_rgba8();
+            let blank = image::RgbaImage::from_pixel(code.width(), code.height(), image::Rgba([255, 255, 255, 255]));
+            let path = dir.path().join(name);
+            let mut encoder = GifEncoder::new(fs::File::create(&path).unwrap());
+            for buffer in [&code, &code, &blank, &code] {
+                let frame = Frame::from_parts(buffer.clone(), 0, 0, Delay::from_numer_denom_ms(100, 1));
+                encoder.encode_frame(frame).unwrap();
+            }
+            path
+        };
+
+        let result = scan_media(&gif("ad.gif", "https://example.com/ad"), &[], false);
+        assert!(result.success);
+        assert_eq!(result.frame_count, 4);
+        assert_eq!(result.codes.len(), 1);
+        assert_eq!(result.codes[0].result.content, Some("https://example.com/ad".to_string()));
+        assert_eq!(result.codes[0].result.symbology, Some("qr".to_string()));
+        assert_eq!(result.codes[0].result.strategy, Some("original".to_string()));
+        assert_eq!(
+            result.codes[0].appearances,
+            vec![
+                TimeRange { start_ms: 0, end_ms: 200 },
+                TimeRange { start_ms: 300, end_ms: 400 },
+            ]
+        );
+
+        // Secrets are masked without losing what the frame decode found
+        let otp = "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&issuer=Example";
+        let result = scan_media(&gif("otp.gif", otp), &[], false);
+        let code = &result.codes[0].result;
+        assert!(code.secrets_masked);
+        assert!(!code.content.as_ref().unwrap().contains("JBSWY3DPEHPK3PXP"));
+        assert!(code.parsed.is_some());
+        assert_eq!(code.symbology, Some("qr".to_string()));
+        assert_eq!(code.strategy, Some("original".to_string()));
+    }
+
+    #[test]
+    fn test_clipboard_to_image() {
+        let rgba = qr_image("https://example.com/pasted").to_rgba8();
+        let (width, height) = rgba.dimensions();
+
+        let img = clipboard_to_image(rgba.as_raw(), width, height).unwrap();
+        let result = decode_qr_from_image(img, &[], false).unwrap();
+        assert_eq!(result.content, Some("https://example.com/pasted".to_string()));
+
+        assert!(clipboard_to_image(&[], 0, 0).is_err());
+        assert!(clipboard_to_image(&rgba.as_raw()[..16], width, height).is_err());
+    }
+
+    #[test]
+    fn test_preprocessing_strategy_reported() {
+        let result = decode_qr_from_image(qr_image("PLAIN"), &[], false).unwrap();
+        assert_eq!(result.strategy, Some("original".to_string()));
+
+        let mut inverted = qr_image("INVERTED");
+        inverted.invert();
+        let result = decode_qr_from_image(inverted, &[], false).unwrap();
+        assert_eq!(result.content, Some("INVERTED".to_string()));
+        assert_eq!(result.strategy, Some("inverted".to_string()));
+
+        // Blue on dark red: both have the same brightness, only the red channel differs
+        let luma = qr_image("COLORED").to_luma8();
+        let colored = image::RgbImage::from_fn(luma.width(), luma.height(), |x, y| {
+            if luma.get_pixel(x, y)[0] == 0 {
+                image::Rgb([0, 0, 255])
+            } else {
+                image::Rgb([85, 0, 0])
+            }
+        });
+        let result = decode_qr_from_image(DynamicImage::ImageRgb8(colored), &[], false).unwrap();
+        assert_eq!(result.content, Some("COLORED".to_string()));
+        assert_eq!(result.strategy, Some("red-channel".to_string()));
+    }
+
+    #[test]
+    fn test_other_symbologies_scanned() {
+        let result = decode_qr_from_image(qr_image("https://example.com"), &[], false).unwrap();
+        assert_eq!(result.symbology, Some("qr".to_string()));
+
+        for name in ["micro-qr", "rmqr", "data-matrix", "aztec", "pdf417"] {
+            let symbology = Symbology::parse(name).unwrap();
+            let encoded = symbology::encode(symbology, "SKU-0042", &Default::default()).unwrap();
+            let img = crate::render::render_matrix_image(&encoded.matrix, symbology.quiet_zone(), 8);
+            let result = decode_qr_from_image(DynamicImage::ImageLuma8(img), &[], false).unwrap();
+            assert_eq!(result.content, Some("SKU-0042".to_string()), "{}", name);
+            assert_eq!(result.symbology, Some(name.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_validate_image_names_found_symbology() {
+        let aztec = |content: &str| {
+            let encoded = symbology::encode(Symbology::Aztec, content, &Default::default()).unwrap();
+            crate::render::render_matrix_image(&encoded.matrix, Symbology::Aztec.quiet_zone(), 8)
+        };
+        let pdf417 = symbology::lookup("pdf417").ok();
+
+        let result = validate_image(&aztec("SKU-0042"), "SKU-0042", None, None);
+        assert_eq!(result.state, "pass");
+        assert_eq!(result.message, "Aztec scans correctly");
+
+        let result = validate_image(&aztec("SKU-0042"), "SKU-0042", pdf417, None);
+        assert_eq!(result.state, "warn");
+        assert_eq!(result.message, "Expected PDF417 but found Aztec");
+
+        // A wrong symbology doesn't hide that the content differs too
+        let result = validate_image(&aztec("SKU-0043"), "SKU-0042", pdf417, None);
+        assert!(!result.content_match);
+        assert_eq!(result.message, "Decoded content differs from expected. Expected PDF417 but found Aztec");
+    }
+
+    #[test]
+    fn test_decode_reports_eci() {
+        let options = qr::QrOptions {
+            charset: Some("shift-jis".to_string()),
+            ..Default::default()
+        };
+        let symbol = qr::encode("ﾃｽﾄ", qrcode::EcLevel::M, None, &options).unwrap();
+        let img = crate::render::render_matrix_image(&symbol.matrix, 4, 8);
+        let result = decode_qr_from_image(DynamicImage::ImageLuma8(img), &[], false).unwrap();
+        assert_eq!(result.content, Some("ﾃｽﾄ".to_string()));
+        assert_eq!(result.eci, Some("shift-jis".to_string()));
+
+        let result = decode_qr_from_image(qr_image("日本語"), &[], false).unwrap();
+        assert_eq!(result.content, Some("日本語".to_string()));
+        assert_eq!(result.eci, None);
+    }
 }
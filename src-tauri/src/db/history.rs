@@ -1,3 +1,4 @@
+use crate::render;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +11,7 @@ pub struct HistoryItem {
     pub label: Option<String>,
     pub style_json: String,
     pub thumbnail: Option<String>,
+    pub render_fingerprint: Option<String>, // render::render_fingerprint; None for items saved before it existed
     pub created_at: String,
     pub updated_at: String,
 }
@@ -24,7 +26,9 @@ pub struct NewHistoryItem {
     pub thumbnail: Option<String>,
 }
 
-/// List history items with pagination
+/// List history items with pagination. A search matches content and label,
+/// but only the label of otpauth URIs, whatever type they were saved as, so
+/// their secrets can't be probed.
 pub fn list_history(
     conn: &Connection,
     limit: i64,
@@ -33,9 +37,9 @@ pub fn list_history(
 ) -> Result<Vec<HistoryItem>, rusqlite::Error> {
     let mut stmt = if let Some(search_term) = search {
         let query = r#"
-            SELECT id, content, qr_type, label, style_json, thumbnail, created_at, updated_at
+            SELECT id, content, qr_type, label, style_json, thumbnail, render_fingerprint, created_at, updated_at
             FROM history
-            WHERE content LIKE ?1 OR label LIKE ?1
+            WHERE (content NOT LIKE 'otpauth:%' AND content LIKE ?1) OR label LIKE ?1
             ORDER BY created_at DESC
             LIMIT ?2 OFFSET ?3
         "#;
@@ -50,8 +54,9 @@ pub fn list_history(
                     label: row.get(3)?,
                     style_json: row.get(4)?,
                     thumbnail: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
+                    render_fingerprint: row.get(6)?,
+                    created_at: row.get(7)?,
+                    updated_at: row.get(8)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -59,7 +64,7 @@ pub fn list_history(
     } else {
         conn.prepare(
             r#"
-            SELECT id, content, qr_type, label, style_json, thumbnail, created_at, updated_at
+            SELECT id, content, qr_type, label, style_json, thumbnail, render_fingerprint, created_at, updated_at
             FROM history
             ORDER BY created_at DESC
             LIMIT ?1 OFFSET ?2
@@ -76,8 +81,9 @@ pub fn list_history(
                 label: row.get(3)?,
                 style_json: row.get(4)?,
                 thumbnail: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
+                render_fingerprint: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -85,25 +91,36 @@ pub fn list_history(
     Ok(items)
 }
 
-/// Save a new history item
+/// Save a new history item, with the fingerprint of its content and style
 pub fn save_history(conn: &Connection, item: &NewHistoryItem) -> Result<i64, rusqlite::Error> {
     conn.execute(
         r#"
-        INSERT INTO history (content, qr_type, label, style_json, thumbnail)
-        VALUES (?1, ?2, ?3, ?4, ?5)
+        INSERT INTO history (content, qr_type, label, style_json, thumbnail, render_fingerprint)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
         "#,
         params![
             item.content,
             item.qr_type,
             item.label,
             item.style_json,
-            item.thumbnail
+            item.thumbnail,
+            render::render_fingerprint(&item.content, &item.style_json, &render_target())
         ],
     )?;
 
     Ok(conn.last_insert_rowid())
 }
 
+/// History items are QR codes saved with a PNG thumbnail; anything else
+/// that shapes the render lives in their style
+fn render_target() -> render::RenderTarget {
+    render::RenderTarget {
+        symbology: "qr".to_string(),
+        format: "png".to_string(),
+        ..Default::default()
+    }
+}
+
 /// Delete a history item
 pub fn delete_history(conn: &Connection, id: i64) -> Result<bool, rusqlite::Error> {
     let affected = conn.execute("DELETE FROM history WHERE id = ?1", params![id])?;
@@ -137,6 +154,7 @@ mod tests {
                 label TEXT,
                 style_json TEXT NOT NULL,
                 thumbnail TEXT,
+                render_fingerprint TEXT,
                 created_at TEXT DEFAULT (datetime('now')),
                 updated_at TEXT DEFAULT (datetime('now'))
             );
@@ -235,6 +253,23 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_list_history_search_skips_otp_content() {
+        let conn = setup_test_db();
+
+        let mut otp = create_test_item("otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP", "otp");
+        otp.label = Some("Example".to_string());
+        save_history(&conn, &otp).unwrap();
+
+        assert!(list_history(&conn, 50, 0, Some("JBSWY3")).unwrap().is_empty());
+        assert!(list_history(&conn, 50, 0, Some("alice")).unwrap().is_empty());
+        assert_eq!(list_history(&conn, 50, 0, Some("Example")).unwrap().len(), 1);
+
+        // The type column doesn't decide it
+        save_history(&conn, &create_test_item("OTPAUTH://totp/Other:bob?secret=KRSXG5CTMVRXEZLU", "text")).unwrap();
+        assert!(list_history(&conn, 50, 0, Some("KRSXG5")).unwrap().is_empty());
+    }
+
     #[test]
     fn test_list_history_search_by_label() {
         let conn = setup_test_db();
@@ -331,6 +366,10 @@ mod tests {
         assert_eq!(retrieved.label, Some("My Link".to_string()));
         assert_eq!(retrieved.style_json, style);
         assert_eq!(retrieved.thumbnail, Some("data:image/png;base64,thumb".to_string()));
+        assert_eq!(
+            retrieved.render_fingerprint,
+            Some(render::render_fingerprint("https://example.com", style, &render_target()))
+        );
         assert!(!retrieved.created_at.is_empty());
         assert!(!retrieved.updated_at.is_empty());
     }
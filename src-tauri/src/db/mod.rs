@@ -3,9 +3,11 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 
 pub mod history;
+pub mod settings;
 pub mod templates;
 
 pub use history::*;
+pub use settings::*;
 pub use templates::*;
 
 /// Get the database path in the app data directory
@@ -51,6 +53,7 @@ fn init_schema(conn: &Connection) -> Result<()> {
             label TEXT,
             style_json TEXT NOT NULL,
             thumbnail TEXT,
+            render_fingerprint TEXT,
             created_at TEXT DEFAULT (datetime('now')),
             updated_at TEXT DEFAULT (datetime('now'))
         );
@@ -76,5 +79,53 @@ fn init_schema(conn: &Connection) -> Result<()> {
         "#,
     )?;
 
+    // Columns added after the tables were first created
+    add_column(conn, "history", "render_fingerprint", "TEXT")?;
+
+    Ok(())
+}
+
+/// Add a column to a table created by an older version of the app
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists([column])?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_schema_adds_missing_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                content TEXT NOT NULL,
+                qr_type TEXT NOT NULL,
+                label TEXT,
+                style_json TEXT NOT NULL,
+                thumbnail TEXT,
+                created_at TEXT DEFAULT (datetime('now')),
+                updated_at TEXT DEFAULT (datetime('now'))
+            );
+            INSERT INTO history (content, qr_type, style_json) VALUES ('old', 'text', '{}');
+            "#,
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+        // Running it again must not try to add the column twice
+        init_schema(&conn).unwrap();
+
+        let items = list_history(&conn, 10, 0, None).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].render_fingerprint, None);
+    }
+}
@@ -0,0 +1,59 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Get a setting value by key
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Insert or replace a setting value
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?1, ?2)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value
+        "#,
+        params![key, value],
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_get_setting_missing() {
+        let conn = setup_test_db();
+        assert_eq!(get_setting(&conn, "nope").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_setting_upserts() {
+        let conn = setup_test_db();
+        set_setting(&conn, "theme", "dark").unwrap();
+        assert_eq!(get_setting(&conn, "theme").unwrap(), Some("dark".to_string()));
+
+        set_setting(&conn, "theme", "light").unwrap();
+        assert_eq!(get_setting(&conn, "theme").unwrap(), Some("light".to_string()));
+    }
+}
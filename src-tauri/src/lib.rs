@@ -1,19 +1,40 @@
+mod barcode;
+mod classify;
 mod commands;
 mod db;
+mod media;
+mod micro;
+mod payload;
+mod pdf;
+mod preprocess;
+mod qr;
+mod rectify;
+mod render;
+mod safety;
+mod structured;
+mod symbology;
+mod urls;
 
 use commands::{
     // Export commands
-    copy_image_to_clipboard, export_png, export_svg, pick_image_file,
+    analyze_qr_segments, check_barcode, copy_image_to_clipboard, export_barcode, export_png,
+    export_svg, export_swiss_qr_bill, list_symbologies, micro_qr_capacity, pick_image_file, render_barcode,
+    render_micro_qr, render_structured_append, render_swiss_qr_bill, render_symbol,
     // Validation commands
-    scan_qr_from_data, scan_qr_from_file, validate_qr,
+    extract_qr_codes, pick_folder, scan_folder, scan_qr_from_clipboard, scan_qr_from_data,
+    scan_qr_from_file, scan_qr_from_media, scan_qr_from_pdf, scan_structured_append, validate_qr,
+    // Payload commands
+    payload_build, payload_parse,
+    // Classifier commands
+    classifier_rules_get, classifier_rules_set, classify_content,
     // History commands
     history_clear, history_delete, history_list, history_save,
     // Template commands
     template_delete, template_get, template_list, template_save, template_set_default,
     template_update,
     // Batch commands
-    batch_generate_zip, batch_parse_csv, batch_parse_csv_content, batch_save_files, batch_validate,
-    pick_csv_file,
+    batch_generate_zip, batch_parse_csv, batch_parse_csv_content, batch_reconcile, batch_save_files,
+    batch_validate, pick_csv_file,
 };
 use db::DbState;
 
@@ -36,11 +57,36 @@ pub fn run() {
             validate_qr,
             scan_qr_from_file,
             scan_qr_from_data,
+            scan_qr_from_clipboard,
+            scan_qr_from_pdf,
+            scan_qr_from_media,
+            scan_folder,
+            pick_folder,
+            extract_qr_codes,
+            scan_structured_append,
             // Export
             export_png,
             export_svg,
+            render_swiss_qr_bill,
+            export_swiss_qr_bill,
+            render_structured_append,
+            render_micro_qr,
+            micro_qr_capacity,
+            render_symbol,
+            list_symbologies,
+            analyze_qr_segments,
+            render_barcode,
+            export_barcode,
+            check_barcode,
             copy_image_to_clipboard,
             pick_image_file,
+            // Payload
+            payload_build,
+            payload_parse,
+            // Classifier
+            classify_content,
+            classifier_rules_get,
+            classifier_rules_set,
             // History
             history_list,
             history_save,
@@ -59,6 +105,7 @@ pub fn run() {
             batch_validate,
             batch_generate_zip,
             batch_save_files,
+            batch_reconcile,
             pick_csv_file,
         ])
         .run(tauri::generate_context!())
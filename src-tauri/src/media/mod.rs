@@ -0,0 +1,166 @@
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, Frames};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+const ANIMATION_EXTENSIONS: &[&str] = &["gif", "png", "apng", "webp"];
+
+/// A decoded frame and where it sits on the timeline
+pub struct Frame {
+    pub index: u32,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub image: DynamicImage,
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Whether a path may hold an animation (GIF, APNG or animated WebP)
+pub fn is_animation_path(path: &Path) -> bool {
+    has_extension(path, ANIMATION_EXTENSIONS)
+}
+
+/// Decode every frame of a GIF, APNG or animated WebP. Still images yield a
+/// single frame. Frames identical to the one before are skipped but still
+/// extend its time range. Returns the number of frames in the file.
+pub fn for_each_animation_frame(
+    path: &Path,
+    mut visit: impl FnMut(Frame),
+) -> Result<u32, String> {
+    let frames = open_animation(path)?;
+    let Some(frames) = frames else {
+        let image = image::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+        visit(Frame {
+            index: 0,
+            start_ms: 0,
+            end_ms: 0,
+            image,
+        });
+        return Ok(1);
+    };
+
+    let mut elapsed = 0u64;
+    let mut count = 0u32;
+    let mut pending: Option<Frame> = None;
+
+    for frame in frames {
+        let frame = frame.map_err(|e| format!("Failed to decode frame {}: {}", count + 1, e))?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let duration = numer.checked_div(denom).unwrap_or(0) as u64;
+        let buffer = frame.into_buffer();
+
+        match &mut pending {
+            Some(previous) if previous.image.as_rgba8() == Some(&buffer) => {
+                previous.end_ms = elapsed + duration;
+            }
+            _ => {
+                if let Some(previous) = pending.take() {
+                    visit(previous);
+                }
+                pending = Some(Frame {
+                    index: count,
+                    start_ms: elapsed,
+                    end_ms: elapsed + duration,
+                    image: DynamicImage::ImageRgba8(buffer),
+                });
+            }
+        }
+
+        elapsed += duration;
+        count += 1;
+    }
+
+    if let Some(previous) = pending {
+        visit(previous);
+    }
+    Ok(count)
+}
+
+/// The frame iterator for animated files, or `None` for still images
+fn open_animation(path: &Path) -> Result<Option<Frames<'static>>, String> {
+    let open = || {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| format!("Failed to open file: {}", e))
+    };
+    let decode_err = |e: image::ImageError| format!("Failed to decode animation: {}", e);
+
+    let mut magic = [0u8; 12];
+    let read = open()?.read(&mut magic).unwrap_or(0);
+    let magic = &magic[..read];
+
+    if magic.starts_with(b"GIF8") {
+        let decoder = GifDecoder::new(open()?).map_err(decode_err)?;
+        Ok(Some(decoder.into_frames()))
+    } else if magic.starts_with(b"\x89PNG") {
+        let decoder = PngDecoder::new(open()?).map_err(decode_err)?;
+        if !decoder.is_apng().map_err(decode_err)? {
+            return Ok(None);
+        }
+        Ok(Some(decoder.apng().map_err(decode_err)?.into_frames()))
+    } else if magic.starts_with(b"RIFF") && magic.get(8..12) == Some(b"WEBP".as_slice()) {
+        let decoder = WebPDecoder::new(open()?).map_err(decode_err)?;
+        if !decoder.has_animation() {
+            return Ok(None);
+        }
+        Ok(Some(decoder.into_frames()))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Rgba, RgbaImage};
+
+    fn solid(value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(8, 8, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn test_animation_frames_and_timestamps() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ad.gif");
+        {
+            let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+            for (value, ms) in [(0, 100), (0, 100), (255, 300)] {
+                let frame = image::Frame::from_parts(solid(value), 0, 0, Delay::from_numer_denom_ms(ms, 1));
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+
+        let mut frames = Vec::new();
+        let count = for_each_animation_frame(&path, |f| frames.push((f.index, f.start_ms, f.end_ms))).unwrap();
+
+        assert_eq!(count, 3);
+        // The repeated first frame is merged into one 200 ms span
+        assert_eq!(frames, vec![(0, 0, 200), (2, 200, 500)]);
+    }
+
+    #[test]
+    fn test_still_image_is_one_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("still.png");
+        solid(128).save(&path).unwrap();
+
+        let mut seen = 0;
+        assert_eq!(for_each_animation_frame(&path, |_| seen += 1).unwrap(), 1);
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn test_media_paths() {
+        assert!(is_animation_path(Path::new("ad.GIF")));
+        assert!(is_animation_path(Path::new("ad.webp")));
+        assert!(!is_animation_path(Path::new("spot.mp4")));
+    }
+}
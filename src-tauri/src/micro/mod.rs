@@ -0,0 +1,272 @@
+use crate::render::ModuleMatrix;
+use crate::structured::BitWriter;
+use qrcode::bits::Bits;
+use qrcode::canvas::Canvas;
+use qrcode::{ec, Color, EcLevel, Version};
+
+pub mod rmqr;
+
+/// Quiet zone both Micro QR and rMQR need, in modules
+pub const QUIET_ZONE: usize = 2;
+
+/// The small QR variants
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Micro, // square, M1 to M4, one finder pattern
+    Rmqr,  // rectangular, R7x43 to R17x139
+}
+
+impl Kind {
+    /// Parse "micro" or "rmqr"
+    pub fn parse(kind: &str) -> Result<Self, String> {
+        match kind.trim().to_lowercase().as_str() {
+            "micro" | "micro-qr" => Ok(Kind::Micro),
+            "rmqr" => Ok(Kind::Rmqr),
+            other => Err(format!("Unknown symbol kind '{}'", other)),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Kind::Micro => "Micro QR",
+            Kind::Rmqr => "rMQR",
+        }
+    }
+}
+
+/// How content fits one version of a symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    pub version: String, // "1".."40", "M1".."M4" or "R7x43".."R17x139"
+    pub width: usize,
+    pub height: usize,
+    pub capacity_bits: usize,
+    pub used_bits: Option<usize>, // None when the content can't be represented at all
+}
+
+impl Fit {
+    pub fn fits(&self) -> bool {
+        self.used_bits.is_some_and(|used| used <= self.capacity_bits)
+    }
+}
+
+/// An encoded symbol and the version that was chosen
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub matrix: ModuleMatrix,
+    pub fit: Fit,
+}
+
+/// How the content fits every version that supports the error correction
+/// level, smallest symbol first
+pub fn fits(kind: Kind, content: &str, ec_level: EcLevel) -> Vec<Fit> {
+    match kind {
+        Kind::Micro => (1..=4)
+            .filter_map(|number| {
+                let version = Version::Micro(number);
+                let capacity_bits = Bits::new(version).max_len(ec_level).ok()?;
+                let mut bits = Bits::new(version);
+                let used_bits = bits.push_optimal_data(content.as_bytes()).ok().map(|_| bits.len());
+                let side = version.width() as usize;
+                Some(Fit {
+                    version: format!("M{}", number),
+                    width: side,
+                    height: side,
+                    capacity_bits,
+                    used_bits,
+                })
+            })
+            .collect(),
+        Kind::Rmqr => {
+            let mut fits: Vec<Fit> = (1..=rmqr::SIZES.len() as u32)
+                .filter_map(|number| {
+                    let (width, height) = rmqr::SIZES[number as usize - 1];
+                    Some(Fit {
+                        version: rmqr::name(number),
+                        width,
+                        height,
+                        capacity_bits: rmqr::capacity_bits(number, ec_level).ok()?,
+                        used_bits: rmqr::data_bits(content, number),
+                    })
+                })
+                .collect();
+            fits.sort_by_key(|f| (f.width * f.height, f.height));
+            fits
+        }
+    }
+}
+
+/// Encode content in the smallest version that holds it, or in `version`
+/// when given (e.g. "M3" or "R11x43")
+pub fn encode(kind: Kind, content: &str, ec_level: EcLevel, version: Option<&str>) -> Result<Symbol, String> {
+    if content.is_empty() {
+        return Err("Content is empty".to_string());
+    }
+    let fits = fits(kind, content, ec_level);
+    if fits.is_empty() {
+        return Err(format!(
+            "{} does not support error correction level {:?}",
+            kind.label(),
+            ec_level
+        ));
+    }
+
+    let fit = match version {
+        Some(version) => {
+            let fit = fits
+                .iter()
+                .find(|f| f.version.eq_ignore_ascii_case(version.trim()))
+                .ok_or_else(|| format!("{} has no version '{}' at level {:?}", kind.label(), version, ec_level))?;
+            if !fit.fits() {
+                return Err(format!(
+                    "Content does not fit in {} {}: it holds {} data bits",
+                    kind.label(),
+                    fit.version,
+                    fit.capacity_bits
+                ));
+            }
+            fit
+        }
+        None => fits.iter().find(|f| f.fits()).ok_or_else(|| {
+            let largest = fits.iter().max_by_key(|f| f.capacity_bits).unwrap();
+            format!(
+                "Content is too long for {} at level {:?}: the largest symbol, {}, holds {} data bits",
+                kind.label(),
+                ec_level,
+                largest.version,
+                largest.capacity_bits
+            )
+        })?,
+    };
+
+    let matrix = match kind {
+        Kind::Micro => encode_micro(content, fit.version[1..].parse().unwrap_or(4), ec_level)?,
+        Kind::Rmqr => {
+            let number = rmqr::parse_version(&fit.version).unwrap_or(1);
+            rmqr::encode(content, ec_level, number)?
+        }
+    };
+
+    Ok(Symbol {
+        matrix,
+        fit: fit.clone(),
+    })
+}
+
+/// Encode content as a Micro QR symbol. The qrcode crate picks the mode
+/// segments; padding and module placement are done here because the crate
+/// mishandles the 4-bit last data codeword of M3-L.
+fn encode_micro(content: &str, number: i16, ec_level: EcLevel) -> Result<ModuleMatrix, String> {
+    let version = Version::Micro(number);
+    let encode_error = |e: qrcode::types::QrError| format!("Failed to encode Micro QR code: {}", e);
+    let capacity = Bits::new(version).max_len(ec_level).map_err(encode_error)?;
+
+    let mut bits = Bits::new(version);
+    bits.push_optimal_data(content.as_bytes()).map_err(encode_error)?;
+    let len = bits.len();
+    let raw = bits.into_bytes();
+    let mut data = BitWriter::default();
+    for i in 0..len {
+        data.push(1, (raw[i / 8] >> (7 - i % 8)) as u32 & 1);
+    }
+    let data = data.finish(capacity, number as usize * 2 + 1);
+    let (data, ec_codewords) = ec::construct_codewords(&data, version, ec_level).map_err(encode_error)?;
+
+    // One bit stream with the short codeword in place, drawn as whole bytes
+    let mut stream = BitWriter::default();
+    for (i, &byte) in data.iter().enumerate() {
+        let short = i + 1 == data.len() && capacity % 8 == 4;
+        if short {
+            stream.push(4, (byte >> 4) as u32);
+        } else {
+            stream.push(8, byte as u32);
+        }
+    }
+    for &byte in &ec_codewords {
+        stream.push(8, byte as u32);
+    }
+
+    let mut canvas = Canvas::new(version, ec_level);
+    canvas.draw_all_functional_patterns();
+    canvas.draw_data(&stream.into_bytes(), &[]);
+    let width = version.width() as usize;
+    let modules = canvas
+        .apply_best_mask()
+        .into_colors()
+        .into_iter()
+        .map(|c| c == Color::Dark)
+        .collect();
+    Ok(ModuleMatrix::new(width, width, modules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_matrix_image;
+    use crate::symbology::read;
+
+    fn scan(symbol: &Symbol) -> Vec<(&'static str, String)> {
+        read(&render_matrix_image(&symbol.matrix, QUIET_ZONE + 2, 6))
+    }
+
+    #[test]
+    fn test_micro_picks_smallest_version() {
+        let symbol = encode(Kind::Micro, "12345", EcLevel::L, None).unwrap();
+        assert_eq!(symbol.fit.version, "M1");
+        assert_eq!(symbol.matrix.width(), 11);
+
+        let symbol = encode(Kind::Micro, "HELLO WORLD", EcLevel::L, None).unwrap();
+        assert_eq!(symbol.fit.version, "M3");
+        assert_eq!(scan(&symbol), vec![("micro-qr", "HELLO WORLD".to_string())]);
+    }
+
+    #[test]
+    fn test_micro_m3_l_short_last_codeword() {
+        // The terminator ends inside M3-L's 4-bit final data codeword
+        let symbol = encode(Kind::Micro, "12345678901234567890", EcLevel::L, Some("M3")).unwrap();
+        assert_eq!(scan(&symbol), vec![("micro-qr", "12345678901234567890".to_string())]);
+    }
+
+    #[test]
+    fn test_micro_capacity_errors() {
+        let long = "x".repeat(40);
+        let err = encode(Kind::Micro, &long, EcLevel::L, None).unwrap_err();
+        assert!(err.contains("M4"), "{}", err);
+
+        let err = encode(Kind::Micro, "hello", EcLevel::M, Some("M1")).unwrap_err();
+        assert!(err.contains("no version 'M1'"), "{}", err);
+        assert!(encode(Kind::Micro, "hi", EcLevel::H, None).is_err());
+    }
+
+    #[test]
+    fn test_rmqr_round_trip() {
+        for (content, ec_level) in [
+            ("https://example.com/a", EcLevel::M),
+            ("0123456789", EcLevel::H),
+            ("LOT 42-7/A", EcLevel::M),
+        ] {
+            let symbol = encode(Kind::Rmqr, content, ec_level, None).unwrap();
+            assert_eq!(scan(&symbol), vec![("rmqr", content.to_string())], "{}", symbol.fit.version);
+        }
+    }
+
+    #[test]
+    fn test_rmqr_forced_versions_round_trip() {
+        // One of each height, including multi-block versions
+        for version in ["R7x139", "R9x77", "R11x43", "R13x99", "R15x59", "R17x139"] {
+            let symbol = encode(Kind::Rmqr, "rMQR 2026", EcLevel::H, Some(version)).unwrap();
+            assert_eq!(symbol.fit.version, version);
+            let (width, height) = (symbol.matrix.width(), symbol.matrix.height());
+            assert_eq!(version, format!("R{}x{}", height, width));
+            assert_eq!(scan(&symbol), vec![("rmqr", "rMQR 2026".to_string())], "{}", version);
+        }
+    }
+
+    #[test]
+    fn test_rmqr_rejects_unsupported_levels_and_long_content() {
+        assert!(encode(Kind::Rmqr, "abc", EcLevel::L, None).unwrap_err().contains("does not support"));
+        let err = encode(Kind::Rmqr, &"x".repeat(400), EcLevel::M, None).unwrap_err();
+        assert!(err.contains("R17x139"), "{}", err);
+        assert!(encode(Kind::Rmqr, &"x".repeat(20), EcLevel::M, Some("R7x43")).is_err());
+    }
+}
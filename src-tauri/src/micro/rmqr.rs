@@ -0,0 +1,323 @@
+use crate::render::ModuleMatrix;
+use crate::structured::BitWriter;
+use qrcode::{ec, EcLevel};
+use rxing::qrcode::common::{ErrorCorrectionLevel, Mode, Version, VersionRef};
+
+/// Width and height of each rMQR version, R7x43 first (ISO/IEC 23941 Table 1)
+pub const SIZES: [(usize, usize); 32] = [
+    (43, 7),
+    (59, 7),
+    (77, 7),
+    (99, 7),
+    (139, 7),
+    (43, 9),
+    (59, 9),
+    (77, 9),
+    (99, 9),
+    (139, 9),
+    (27, 11),
+    (43, 11),
+    (59, 11),
+    (77, 11),
+    (99, 11),
+    (139, 11),
+    (27, 13),
+    (43, 13),
+    (59, 13),
+    (77, 13),
+    (99, 13),
+    (139, 13),
+    (43, 15),
+    (59, 15),
+    (77, 15),
+    (99, 15),
+    (139, 15),
+    (43, 17),
+    (59, 17),
+    (77, 17),
+    (99, 17),
+    (139, 17),
+];
+
+/// Format information masks next to the finder and the finder sub-pattern
+const FORMAT_MASK: u32 = 0x1FAB2;
+const FORMAT_MASK_SUB: u32 = 0x20A7B;
+
+/// Generator polynomial of the BCH(18,6) code protecting format information
+const FORMAT_GENERATOR: u32 = 0x1F25;
+
+const ALPHANUMERIC: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Version name as printed on the symbol, e.g. "R11x43" (height first)
+pub fn name(version: u32) -> String {
+    let (width, height) = SIZES[version as usize - 1];
+    format!("R{}x{}", height, width)
+}
+
+/// Version number for a name like "R11x43"
+pub fn parse_version(name: &str) -> Option<u32> {
+    (1..=SIZES.len() as u32).find(|&v| self::name(v).eq_ignore_ascii_case(name.trim()))
+}
+
+fn ec_level_for(ec_level: EcLevel) -> Result<ErrorCorrectionLevel, String> {
+    match ec_level {
+        EcLevel::M => Ok(ErrorCorrectionLevel::M),
+        EcLevel::H => Ok(ErrorCorrectionLevel::H),
+        _ => Err("rMQR supports error correction levels M and H only".to_string()),
+    }
+}
+
+fn version_ref(version: u32) -> Result<VersionRef, String> {
+    Version::rMQR(version).map_err(|_| format!("Unknown rMQR version {}", version))
+}
+
+/// Data codeword lengths of each error correction block
+fn block_lengths(version: VersionRef, ec_level: ErrorCorrectionLevel) -> Vec<usize> {
+    version
+        .getECBlocksForLevel(ec_level)
+        .getECBlocks()
+        .iter()
+        .flat_map(|b| std::iter::repeat_n(b.getDataCodewords() as usize, b.getCount() as usize))
+        .collect()
+}
+
+/// Data bits available in a version at an error correction level
+pub fn capacity_bits(version: u32, ec_level: EcLevel) -> Result<usize, String> {
+    let blocks = block_lengths(version_ref(version)?, ec_level_for(ec_level)?);
+    Ok(blocks.iter().sum::<usize>() * 8)
+}
+
+/// The single most compact mode that can hold all of the content
+fn mode_for(content: &str) -> Mode {
+    if !content.is_empty() && content.bytes().all(|b| b.is_ascii_digit()) {
+        Mode::NUMERIC
+    } else if !content.is_empty() && content.chars().all(|c| ALPHANUMERIC.contains(c)) {
+        Mode::ALPHANUMERIC
+    } else {
+        Mode::BYTE
+    }
+}
+
+/// Bits needed to encode the content in a version, or `None` when its
+/// length overflows the version's character count field
+pub fn data_bits(content: &str, version: u32) -> Option<usize> {
+    let mode = mode_for(content);
+    let count_bits = mode.CharacterCountBits(version_ref(version).ok()?) as usize;
+    let (count, payload) = match mode {
+        Mode::NUMERIC => {
+            let n = content.len();
+            (n, n / 3 * 10 + [0, 4, 7][n % 3])
+        }
+        Mode::ALPHANUMERIC => {
+            let n = content.len();
+            (n, n / 2 * 11 + n % 2 * 6)
+        }
+        _ => (content.len(), content.len() * 8),
+    };
+    (count < 1 << count_bits).then_some(3 + count_bits + payload)
+}
+
+/// Encode content as an rMQR symbol of the given version
+pub fn encode(content: &str, ec_level: EcLevel, version: u32) -> Result<ModuleMatrix, String> {
+    let version_ref = version_ref(version)?;
+    let level = ec_level_for(ec_level)?;
+    let capacity = capacity_bits(version, ec_level)?;
+    if data_bits(content, version).is_none_or(|bits| bits > capacity) {
+        return Err(format!("Content does not fit in rMQR {}", name(version)));
+    }
+
+    let mode = mode_for(content);
+    let indicator = match mode {
+        Mode::NUMERIC => 0b001,
+        Mode::ALPHANUMERIC => 0b010,
+        _ => 0b011,
+    };
+    let mut bits = BitWriter::default();
+    bits.push(3, indicator);
+    bits.push(mode.CharacterCountBits(version_ref) as usize, content.len() as u32);
+    match mode {
+        Mode::NUMERIC => {
+            for group in content.as_bytes().chunks(3) {
+                let value = group.iter().fold(0, |acc, &d| acc * 10 + (d - b'0') as u32);
+                bits.push([0, 4, 7, 10][group.len()], value);
+            }
+        }
+        Mode::ALPHANUMERIC => {
+            let values: Vec<u32> = content.chars().map(|c| ALPHANUMERIC.find(c).unwrap() as u32).collect();
+            for pair in values.chunks(2) {
+                match pair {
+                    [a, b] => bits.push(11, a * 45 + b),
+                    [a] => bits.push(6, *a),
+                    _ => unreachable!(),
+                }
+            }
+        }
+        _ => {
+            for &byte in content.as_bytes() {
+                bits.push(8, byte as u32);
+            }
+        }
+    }
+    let data = bits.finish(capacity, 3);
+
+    // Split into blocks, add error correction, then interleave both
+    let ec_per_block = version_ref.getECBlocksForLevel(level).getECCodewordsPerBlock() as usize;
+    let mut blocks = Vec::new();
+    let mut rest = &data[..];
+    for length in block_lengths(version_ref, level) {
+        let (block, tail) = rest.split_at(length);
+        blocks.push((block, ec::create_error_correction_code(block, ec_per_block)));
+        rest = tail;
+    }
+    let longest = blocks.iter().map(|(b, _)| b.len()).max().unwrap_or(0);
+    let mut codewords = Vec::new();
+    for i in 0..longest {
+        codewords.extend(blocks.iter().filter_map(|(b, _)| b.get(i)));
+    }
+    for i in 0..ec_per_block {
+        codewords.extend(blocks.iter().map(|(_, e)| e[i]));
+    }
+
+    let (width, height) = SIZES[version as usize - 1];
+    let mut grid = Grid::new(width, height);
+    grid.draw_function_patterns(version_ref.getAlignmentPatternCenters());
+    grid.draw_format(((level == ErrorCorrectionLevel::H) as u32) << 5 | (version - 1));
+
+    let function = version_ref
+        .buildFunctionPattern()
+        .map_err(|e| format!("Failed to lay out rMQR symbol: {}", e))?;
+    grid.draw_data(&codewords, |x, y| function.get(x as u32, y as u32));
+
+    Ok(ModuleMatrix::new(width, height, grid.modules))
+}
+
+struct Grid {
+    width: usize,
+    height: usize,
+    modules: Vec<bool>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            modules: vec![false; width * height],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.width + x] = dark;
+    }
+
+    fn draw_function_patterns(&mut self, alignment_centers: &[u32]) {
+        let (w, h) = (self.width, self.height);
+
+        // Timing patterns run round all four edges
+        for x in 0..w {
+            self.set(x, 0, x % 2 == 0);
+            self.set(x, h - 1, x % 2 == 0);
+        }
+        for y in 0..h {
+            self.set(0, y, y % 2 == 0);
+            self.set(w - 1, y, y % 2 == 0);
+        }
+
+        // Vertical timing columns, each capped by an alignment pattern at both ends
+        for &cx in alignment_centers {
+            let cx = cx as usize;
+            for y in 0..h {
+                self.set(cx, y, y % 2 == 0);
+            }
+            for y in [1, 2, h - 3, h - 2] {
+                self.set(cx - 1, y, true);
+                self.set(cx + 1, y, true);
+            }
+        }
+
+        // Finder pattern, top left
+        for y in 0..7usize {
+            for x in 0..7usize {
+                let ring = x.abs_diff(3).max(y.abs_diff(3));
+                self.set(x, y, ring != 2);
+            }
+        }
+
+        // Finder sub-pattern, bottom right
+        for y in 0..5usize {
+            for x in 0..5usize {
+                let ring = x.abs_diff(2).max(y.abs_diff(2));
+                self.set(w - 5 + x, h - 5 + y, ring != 1);
+            }
+        }
+
+        // Corner finder patterns, top right and bottom left
+        self.set(w - 2, 0, true);
+        self.set(w - 2, 1, false);
+        self.set(w - 1, 1, true);
+        self.set(0, h - 2, true);
+        self.set(1, h - 2, false);
+        self.set(1, h - 1, true);
+
+        // Separator right of and below the finder
+        for y in 0..7usize {
+            self.set(7, y, false);
+        }
+        if h > 7 {
+            for x in 0..8 {
+                self.set(x, 7, false);
+            }
+        }
+    }
+
+    /// 18-bit format information, written next to both finder patterns
+    fn draw_format(&mut self, data: u32) {
+        let mut remainder = data << 12;
+        for bit in (12..18).rev() {
+            if remainder & (1 << bit) != 0 {
+                remainder ^= FORMAT_GENERATOR << (bit - 12);
+            }
+        }
+        let code = (data << 12) | remainder;
+        let (w, h) = (self.width, self.height);
+
+        // Most significant bit first, in the order a reader collects them
+        let mut finder_side = vec![(11, 3), (11, 2), (11, 1)];
+        let mut sub_side = vec![(w - 3, h - 6), (w - 4, h - 6), (w - 5, h - 6)];
+        for column in 0..3 {
+            for row in 0..5 {
+                finder_side.push((10 - column, 5 - row));
+                sub_side.push((w - 6 - column, h - 2 - row));
+            }
+        }
+
+        for (positions, mask) in [(finder_side, FORMAT_MASK), (sub_side, FORMAT_MASK_SUB)] {
+            let masked = code ^ mask;
+            for (i, (x, y)) in positions.into_iter().enumerate() {
+                self.set(x, y, (masked >> (17 - i)) & 1 == 1);
+            }
+        }
+    }
+
+    /// Place codewords in two-module columns from the right, alternating
+    /// upwards and downwards, then apply the fixed rMQR data mask
+    fn draw_data(&mut self, codewords: &[u8], is_function: impl Fn(usize, usize) -> bool) {
+        let mut bits = codewords.iter().flat_map(|&c| (0..8).rev().map(move |i| (c >> i) & 1 == 1));
+        let mut upwards = true;
+        let mut x = self.width - 2;
+
+        while x > 0 {
+            for row in 0..self.height {
+                let y = if upwards { self.height - 1 - row } else { row };
+                for xx in [x, x - 1] {
+                    if !is_function(xx, y) {
+                        let mask = ((y / 2) + (xx / 3)) % 2 == 0;
+                        self.set(xx, y, bits.next().unwrap_or(false) != mask);
+                    }
+                }
+            }
+            upwards = !upwards;
+            x = x.saturating_sub(2);
+        }
+    }
+}
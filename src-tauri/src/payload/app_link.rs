@@ -0,0 +1,157 @@
+use super::{query_pairs, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+/// A link to an app in a mobile app store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLink {
+    pub store: String, // "google-play" | "app-store"
+    pub app_id: Option<String>, // package name or numeric App Store id
+    pub search: Option<String>,
+    pub web_url: String, // https equivalent, openable on desktop
+}
+
+/// Whether content is a store link this module understands
+pub fn is_app_link(content: &str) -> bool {
+    let lower = content.trim().to_lowercase();
+    ["market://", "itms-apps://", "itms-appss://"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+        || [
+            "https://play.google.com/store/apps/",
+            "https://apps.apple.com/",
+            "https://itunes.apple.com/",
+        ]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+}
+
+/// Parse `market://`, `itms-apps://` and store web links
+pub fn parse_app_link(content: &str) -> Result<AppLink, String> {
+    let content = content.trim();
+
+    if let Some(rest) = strip_prefix_ci(content, "market://") {
+        return parse_play(rest);
+    }
+    if let Some(rest) = strip_prefix_ci(content, "https://play.google.com/store/apps/") {
+        return parse_play(rest);
+    }
+
+    let apple = strip_prefix_ci(content, "itms-apps://")
+        .or_else(|| strip_prefix_ci(content, "itms-appss://"))
+        .or_else(|| strip_prefix_ci(content, "https://"))
+        .filter(|rest| {
+            let host = rest.split('/').next().unwrap_or("").to_lowercase();
+            host == "apps.apple.com" || host == "itunes.apple.com"
+        });
+    if let Some(rest) = apple {
+        return parse_apple(rest);
+    }
+
+    Err("Not an app store link".to_string())
+}
+
+/// `details?id=com.example.app` or `search?q=term`
+fn parse_play(rest: &str) -> Result<AppLink, String> {
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let params = query_pairs(query);
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .filter(|v| !v.is_empty())
+    };
+
+    let mut link = AppLink {
+        store: "google-play".to_string(),
+        ..Default::default()
+    };
+
+    match path.trim_end_matches('/') {
+        "details" => {
+            let id = param("id").ok_or("Play Store link has no app id")?;
+            link.web_url = format!(
+                "https://play.google.com/store/apps/details?id={}",
+                urlencoding::encode(&id)
+            );
+            link.app_id = Some(id);
+        }
+        "search" => {
+            let query = param("q").ok_or("Play Store search has no query")?;
+            link.web_url = format!(
+                "https://play.google.com/store/search?q={}&c=apps",
+                urlencoding::encode(&query)
+            );
+            link.search = Some(query);
+        }
+        other => return Err(format!("Unsupported Play Store link '{}'", other)),
+    }
+
+    Ok(link)
+}
+
+/// `apps.apple.com/us/app/name/id123456789`
+fn parse_apple(rest: &str) -> Result<AppLink, String> {
+    let (path, _) = rest.split_once('?').unwrap_or((rest, ""));
+    let app_id = path
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix("id"))
+        .find(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+        .map(str::to_string);
+
+    Ok(AppLink {
+        store: "app-store".to_string(),
+        web_url: match &app_id {
+            Some(id) => format!("https://apps.apple.com/app/id{}", id),
+            None => format!("https://{}", rest),
+        },
+        app_id,
+        search: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_market_link() {
+        let link = parse_app_link("market://details?id=com.example.app&referrer=qr").unwrap();
+        assert_eq!(link.store, "google-play");
+        assert_eq!(link.app_id, Some("com.example.app".to_string()));
+        assert_eq!(link.web_url, "https://play.google.com/store/apps/details?id=com.example.app");
+
+        let search = parse_app_link("market://search?q=qr%20scanner").unwrap();
+        assert_eq!(search.search, Some("qr scanner".to_string()));
+
+        assert!(parse_app_link("market://details").is_err());
+    }
+
+    #[test]
+    fn test_parse_play_web_link() {
+        let link = parse_app_link("https://play.google.com/store/apps/details?id=com.whatsapp").unwrap();
+        assert_eq!(link.app_id, Some("com.whatsapp".to_string()));
+    }
+
+    #[test]
+    fn test_parse_apple_links() {
+        let link = parse_app_link("https://apps.apple.com/us/app/whatsapp-messenger/id310633997").unwrap();
+        assert_eq!(link.store, "app-store");
+        assert_eq!(link.app_id, Some("310633997".to_string()));
+        assert_eq!(link.web_url, "https://apps.apple.com/app/id310633997");
+
+        let link = parse_app_link("itms-apps://itunes.apple.com/app/id310633997").unwrap();
+        assert_eq!(link.app_id, Some("310633997".to_string()));
+
+        assert!(parse_app_link("https://example.com/app/id1").is_err());
+    }
+
+    #[test]
+    fn test_is_app_link() {
+        assert!(is_app_link("MARKET://details?id=x"));
+        assert!(is_app_link("https://apps.apple.com/app/id1"));
+        assert!(!is_app_link("https://play.google.com/store/movies"));
+        assert!(!is_app_link("https://example.com"));
+    }
+}
@@ -0,0 +1,174 @@
+/// Bech32 character set, indexed by 5-bit value
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+/// Checksum variant: BIP173 bech32 or BIP350 bech32m
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+/// A decoded bech32 string: lowercase human-readable part and 5-bit data words
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub hrp: String,
+    pub data: Vec<u8>,
+    pub variant: Variant,
+}
+
+/// Decode and checksum-verify a bech32/bech32m string.
+/// No length limit is applied; Lightning invoices and LNURLs routinely exceed 90 characters.
+pub fn decode(s: &str) -> Result<Decoded, String> {
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("Bech32 string mixes upper and lower case".to_string());
+    }
+
+    let s = s.to_lowercase();
+    let (hrp, data) = s.rsplit_once('1').ok_or("Bech32 string has no separator")?;
+    if hrp.is_empty() || !hrp.bytes().all(|b| (33..=126).contains(&b)) {
+        return Err("Invalid bech32 human-readable part".to_string());
+    }
+    if data.len() < 6 {
+        return Err("Bech32 data is too short".to_string());
+    }
+
+    let data = data
+        .bytes()
+        .map(|b| {
+            CHARSET
+                .iter()
+                .position(|&c| c == b)
+                .map(|p| p as u8)
+                .ok_or_else(|| format!("Invalid bech32 character '{}'", b as char))
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+
+    let variant = match polymod(&[hrp_expand(hrp), data.clone()].concat()) {
+        1 => Variant::Bech32,
+        0x2bc830a3 => Variant::Bech32m,
+        _ => return Err("Bech32 checksum mismatch".to_string()),
+    };
+
+    Ok(Decoded {
+        hrp: hrp.to_string(),
+        data: data[..data.len() - 6].to_vec(),
+        variant,
+    })
+}
+
+/// Encode 5-bit data words as a lowercase bech32/bech32m string
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let hrp = hrp.to_lowercase();
+    let values = [hrp_expand(&hrp), data.to_vec(), vec![0; 6]].concat();
+    let checksum = polymod(&values) ^ variant.constant();
+
+    let mut out = format!("{}1", hrp);
+    for value in data
+        .iter()
+        .copied()
+        .chain((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8))
+    {
+        out.push(CHARSET[value as usize] as char);
+    }
+    out
+}
+
+/// Regroup bits, e.g. 8-bit bytes into 5-bit words and back
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, String> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err("Invalid data value for bit conversion".to_string());
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err("Invalid padding in bech32 data".to_string());
+    }
+
+    Ok(out)
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    bytes
+        .iter()
+        .map(|b| b >> 5)
+        .chain(std::iter::once(0))
+        .chain(bytes.iter().map(|b| b & 31))
+        .collect()
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for &value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_bip_test_vectors() {
+        let v0 = decode("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap();
+        assert_eq!(v0.hrp, "bc");
+        assert_eq!(v0.variant, Variant::Bech32);
+        assert_eq!(v0.data[0], 0);
+
+        let v1 = decode("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0").unwrap();
+        assert_eq!(v1.variant, Variant::Bech32m);
+        assert_eq!(v1.data[0], 1);
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid() {
+        assert!(decode("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzffffff").is_err());
+        assert!(decode("bc1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").is_err());
+        assert!(decode("bc1qb").is_err());
+        assert!(decode("no-separator").is_err());
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let data = convert_bits(b"https://example.com", 8, 5, true).unwrap();
+        let encoded = encode("lnurl", &data, Variant::Bech32);
+
+        let decoded = decode(&encoded.to_uppercase()).unwrap();
+        assert_eq!(decoded.hrp, "lnurl");
+        assert_eq!(convert_bits(&decoded.data, 5, 8, false).unwrap(), b"https://example.com");
+    }
+}
@@ -0,0 +1,238 @@
+use super::bech32::{self, Variant};
+use super::lightning::parse_bolt11;
+use super::{format_units, non_empty, parse_units, query_pairs, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Satoshis per bitcoin, as a number of decimal places
+pub const BTC_DECIMALS: u32 = 8;
+
+/// A BIP21 `bitcoin:` payment URI, optionally carrying a BOLT11 fallback
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitcoinUri {
+    pub address: String,
+    pub amount: Option<String>, // BTC, e.g. "0.0025"
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub lightning: Option<String>, // BOLT11 invoice offered as an alternative
+    #[serde(default)]
+    pub network: Option<String>, // filled in when parsing
+    #[serde(default)]
+    pub amount_sats: Option<u64>, // filled in when parsing
+}
+
+/// Build a BIP21 URI, validating the address and any Lightning fallback
+pub fn build_bitcoin_uri(uri: &BitcoinUri) -> Result<String, String> {
+    let address = uri.address.trim();
+    validate_bitcoin_address(address)?;
+
+    let mut params = Vec::new();
+    if let Some(amount) = non_empty(&uri.amount) {
+        params.push(("amount", btc_amount(amount)?.0));
+    }
+    if let Some(label) = non_empty(&uri.label) {
+        params.push(("label", label.to_string()));
+    }
+    if let Some(message) = non_empty(&uri.message) {
+        params.push(("message", message.to_string()));
+    }
+    if let Some(invoice) = non_empty(&uri.lightning) {
+        parse_bolt11(invoice)?;
+        params.push(("lightning", invoice.to_string()));
+    }
+
+    let mut out = format!("bitcoin:{}", address);
+    for (i, (key, value)) in params.iter().enumerate() {
+        out.push(if i == 0 { '?' } else { '&' });
+        out.push_str(&format!("{}={}", key, urlencoding::encode(value)));
+    }
+    Ok(out)
+}
+
+/// Parse and validate a BIP21 URI. Unknown `req-` parameters are rejected as the spec requires.
+pub fn parse_bitcoin_uri(content: &str) -> Result<BitcoinUri, String> {
+    let content = content.trim();
+    let rest = strip_prefix_ci(content, "bitcoin:").ok_or("Not a bitcoin: URI")?;
+
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut uri = BitcoinUri {
+        address: address.to_string(),
+        ..Default::default()
+    };
+
+    if !address.is_empty() {
+        uri.network = Some(validate_bitcoin_address(address)?.to_string());
+    }
+
+    for (key, value) in query_pairs(query) {
+        match key.as_str() {
+            "amount" => {
+                let (amount, sats) = btc_amount(&value)?;
+                uri.amount = Some(amount);
+                uri.amount_sats = Some(sats);
+            }
+            "label" => uri.label = Some(value),
+            "message" => uri.message = Some(value),
+            "lightning" => {
+                parse_bolt11(&value)?;
+                uri.lightning = Some(value);
+            }
+            other if other.starts_with("req-") => {
+                return Err(format!("Unsupported required parameter '{}'", key));
+            }
+            _ => {}
+        }
+    }
+
+    // An empty address is only valid as a Lightning-only unified URI
+    if uri.address.is_empty() && uri.lightning.is_none() {
+        return Err("Bitcoin URI has no address".to_string());
+    }
+
+    Ok(uri)
+}
+
+/// Validate a Base58Check or segwit (bech32/bech32m) address and return its network
+pub fn validate_bitcoin_address(address: &str) -> Result<&'static str, String> {
+    let invalid = || format!("'{}' is not a valid Bitcoin address", address);
+
+    if let Some((hrp, network)) = segwit_network(&address.to_lowercase()) {
+        let decoded = bech32::decode(address).map_err(|_| invalid())?;
+        if decoded.hrp != hrp || address.len() > 90 {
+            return Err(invalid());
+        }
+
+        let (&version, program) = decoded.data.split_first().ok_or_else(invalid)?;
+        let program = bech32::convert_bits(program, 5, 8, false).map_err(|_| invalid())?;
+        let expected = if version == 0 {
+            Variant::Bech32
+        } else {
+            Variant::Bech32m
+        };
+
+        let valid = version <= 16
+            && decoded.variant == expected
+            && (2..=40).contains(&program.len())
+            && (version != 0 || program.len() == 20 || program.len() == 32);
+        return if valid { Ok(network) } else { Err(invalid()) };
+    }
+
+    let bytes = base58_decode(address).ok_or_else(invalid)?;
+    if bytes.len() != 25 {
+        return Err(invalid());
+    }
+
+    let (payload, checksum) = bytes.split_at(21);
+    if Sha256::digest(Sha256::digest(payload))[..4] != *checksum {
+        return Err(format!("'{}' has an invalid address checksum", address));
+    }
+
+    match payload[0] {
+        0x00 | 0x05 => Ok("mainnet"),
+        0x6f | 0xc4 => Ok("testnet"),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a BTC amount, returning it normalized along with its value in satoshis
+fn btc_amount(amount: &str) -> Result<(String, u64), String> {
+    let sats = parse_units(amount, BTC_DECIMALS)?;
+    if sats == 0 || sats > 21_000_000 * 100_000_000 {
+        return Err(format!("Invalid bitcoin amount '{}'", amount));
+    }
+    Ok((format_units(sats, BTC_DECIMALS), sats as u64))
+}
+
+/// Human-readable part and network of a segwit address, judged by its prefix
+fn segwit_network(lower: &str) -> Option<(&'static str, &'static str)> {
+    [("bc", "mainnet"), ("tb", "testnet"), ("bcrt", "regtest")]
+        .into_iter()
+        .find(|(hrp, _)| lower.strip_prefix(hrp).is_some_and(|rest| rest.starts_with('1')))
+}
+
+fn base58_decode(s: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    for c in s.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    let mut out = vec![0; zeros];
+    out.extend(bytes);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_bitcoin_address() {
+        assert_eq!(validate_bitcoin_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").unwrap(), "mainnet");
+        assert_eq!(validate_bitcoin_address("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy").unwrap(), "mainnet");
+        assert_eq!(
+            validate_bitcoin_address("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap(),
+            "mainnet"
+        );
+        assert_eq!(
+            validate_bitcoin_address("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0").unwrap(),
+            "mainnet"
+        );
+
+        // Bad Base58Check checksum
+        assert!(validate_bitcoin_address("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb").is_err());
+        // Taproot program with a bech32 (not bech32m) checksum
+        assert!(validate_bitcoin_address("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd").is_err());
+        assert!(validate_bitcoin_address("not-an-address").is_err());
+    }
+
+    #[test]
+    fn test_build_bitcoin_uri() {
+        let uri = BitcoinUri {
+            address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_string(),
+            amount: Some("0.00250000".to_string()),
+            label: Some("Luke Jr".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            build_bitcoin_uri(&uri).unwrap(),
+            "bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=0.0025&label=Luke%20Jr"
+        );
+    }
+
+    #[test]
+    fn test_parse_bitcoin_uri() {
+        let uri = parse_bitcoin_uri(
+            "BITCOIN:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=20.3&label=Luke-Jr&message=Donation%20for%20project",
+        )
+        .unwrap();
+
+        assert_eq!(uri.network, Some("mainnet".to_string()));
+        assert_eq!(uri.amount, Some("20.3".to_string()));
+        assert_eq!(uri.amount_sats, Some(2_030_000_000));
+        assert_eq!(uri.label, Some("Luke-Jr".to_string()));
+        assert_eq!(uri.message, Some("Donation for project".to_string()));
+    }
+
+    #[test]
+    fn test_parse_bitcoin_uri_rejects_invalid() {
+        assert!(parse_bitcoin_uri("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb").is_err());
+        assert!(parse_bitcoin_uri("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?amount=1.123456789").is_err());
+        assert!(parse_bitcoin_uri("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa?req-somethingyoudontunderstand=50").is_err());
+        assert!(parse_bitcoin_uri("bitcoin:?label=nothing").is_err());
+    }
+}
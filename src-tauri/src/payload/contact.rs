@@ -0,0 +1,195 @@
+use super::{split_fields, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+/// A contact parsed from a MECARD or vCard code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    pub format: String, // "mecard" | "vcard"
+    pub name: String,
+    pub organization: Option<String>,
+    pub title: Option<String>,
+    pub phones: Vec<String>,
+    pub emails: Vec<String>,
+    pub url: Option<String>,
+    pub address: Option<String>,
+    pub birthday: Option<String>,
+    pub note: Option<String>,
+}
+
+/// Parse either contact format
+pub fn parse_contact(content: &str) -> Result<Contact, String> {
+    let content = content.trim();
+    if strip_prefix_ci(content, "MECARD:").is_some() {
+        parse_mecard(content)
+    } else {
+        parse_vcard(content)
+    }
+}
+
+/// Parse a `MECARD:N:Doe,John;TEL:...;;` code
+pub fn parse_mecard(content: &str) -> Result<Contact, String> {
+    let body = strip_prefix_ci(content.trim(), "MECARD:").ok_or("Not a MECARD")?;
+
+    let mut contact = Contact {
+        format: "mecard".to_string(),
+        ..Default::default()
+    };
+
+    for (key, value) in split_fields(body) {
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            // Family and given name are separated by a comma
+            "N" => {
+                contact.name = match value.split_once(',') {
+                    Some((family, given)) => format!("{} {}", given.trim(), family.trim()),
+                    None => value,
+                }
+            }
+            "NICKNAME" if contact.name.is_empty() => contact.name = value,
+            "TEL" | "TEL-AV" => contact.phones.push(value),
+            "EMAIL" => contact.emails.push(value),
+            "URL" => contact.url = Some(value),
+            "ADR" => contact.address = Some(value.replace(',', ", ")),
+            "BDAY" => contact.birthday = Some(value),
+            "NOTE" | "MEMO" => contact.note = Some(value),
+            "ORG" => contact.organization = Some(value),
+            _ => {}
+        }
+    }
+
+    finish(contact)
+}
+
+/// Parse the common properties of a `BEGIN:VCARD` code (versions 2.1 to 4.0)
+pub fn parse_vcard(content: &str) -> Result<Contact, String> {
+    let content = content.trim();
+    if strip_prefix_ci(content, "BEGIN:VCARD").is_none() {
+        return Err("Not a vCard".to_string());
+    }
+
+    let mut contact = Contact {
+        format: "vcard".to_string(),
+        ..Default::default()
+    };
+    let mut structured_name = None;
+
+    // Continuation lines start with whitespace and belong to the previous line
+    let unfolded = content.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+
+    for line in unfolded.lines() {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Drop parameters (TEL;TYPE=CELL) and group prefixes (item1.EMAIL)
+        let name = property.split(';').next().unwrap_or("");
+        let name = name.rsplit('.').next().unwrap_or(name).to_uppercase();
+        let value = unescape_vcard(value.trim());
+        if value.is_empty() {
+            continue;
+        }
+
+        match name.as_str() {
+            "FN" => contact.name = value,
+            "N" => structured_name = Some(value),
+            "ORG" => contact.organization = Some(value.trim_end_matches(';').replace(';', ", ")),
+            "TITLE" => contact.title = Some(value),
+            "TEL" => contact.phones.push(strip_scheme(&value, "tel:")),
+            "EMAIL" => contact.emails.push(value),
+            "URL" => contact.url = Some(value),
+            "ADR" => {
+                let parts: Vec<&str> = value.split(';').map(str::trim).filter(|p| !p.is_empty()).collect();
+                contact.address = Some(parts.join(", "));
+            }
+            "BDAY" => contact.birthday = Some(value),
+            "NOTE" => contact.note = Some(value),
+            _ => {}
+        }
+    }
+
+    // Fall back to "Given Family" from the structured name when FN is missing
+    if contact.name.is_empty() {
+        if let Some(n) = structured_name {
+            let mut parts = n.split(';');
+            let family = parts.next().unwrap_or("").trim();
+            let given = parts.next().unwrap_or("").trim();
+            contact.name = format!("{} {}", given, family).trim().to_string();
+        }
+    }
+
+    finish(contact)
+}
+
+fn finish(contact: Contact) -> Result<Contact, String> {
+    if contact.name.is_empty() && contact.phones.is_empty() && contact.emails.is_empty() {
+        return Err("Contact has no name, phone number or e-mail".to_string());
+    }
+    Ok(contact)
+}
+
+fn unescape_vcard(value: &str) -> String {
+    value
+        .replace("\\n", "\n")
+        .replace("\\N", "\n")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+fn strip_scheme(value: &str, scheme: &str) -> String {
+    strip_prefix_ci(value, scheme).unwrap_or(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mecard() {
+        let contact = parse_mecard(
+            "MECARD:N:Doe,John;TEL:+15551234567;TEL:+15557654321;EMAIL:john@example.com;ADR:1 Main St,Springfield;NOTE:Call me\\; maybe;;",
+        )
+        .unwrap();
+
+        assert_eq!(contact.format, "mecard");
+        assert_eq!(contact.name, "John Doe");
+        assert_eq!(contact.phones, vec!["+15551234567", "+15557654321"]);
+        assert_eq!(contact.emails, vec!["john@example.com"]);
+        assert_eq!(contact.address, Some("1 Main St, Springfield".to_string()));
+        assert_eq!(contact.note, Some("Call me; maybe".to_string()));
+    }
+
+    #[test]
+    fn test_parse_mecard_rejects_empty() {
+        assert!(parse_mecard("MECARD:;;").is_err());
+        assert!(parse_mecard("BEGIN:VCARD").is_err());
+    }
+
+    #[test]
+    fn test_parse_vcard() {
+        let contact = parse_vcard(
+            "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Doe;Jane;;;\r\nORG:ACME;Sales\r\nTEL;TYPE=CELL:+15551234567\r\nitem1.EMAIL;TYPE=INTERNET:jane@example.com\r\nADR;TYPE=WORK:;;1 Main St;Springfield;;12345;USA\r\nNOTE:Line one\\nline two\r\nEND:VCARD",
+        )
+        .unwrap();
+
+        assert_eq!(contact.format, "vcard");
+        assert_eq!(contact.name, "Jane Doe");
+        assert_eq!(contact.organization, Some("ACME, Sales".to_string()));
+        assert_eq!(contact.phones, vec!["+15551234567"]);
+        assert_eq!(contact.emails, vec!["jane@example.com"]);
+        assert_eq!(contact.address, Some("1 Main St, Springfield, 12345, USA".to_string()));
+        assert_eq!(contact.note, Some("Line one\nline two".to_string()));
+    }
+
+    #[test]
+    fn test_parse_contact_dispatches_on_format() {
+        assert_eq!(parse_contact("mecard:N:Solo;;").unwrap().name, "Solo");
+        assert_eq!(
+            parse_contact("BEGIN:VCARD\nVERSION:4.0\nFN:Dr. Who\nTEL:tel:+441234\nEND:VCARD").unwrap().phones,
+            vec!["+441234"]
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use super::check_length;
+use serde::{Deserialize, Serialize};
+
+/// A primitive data object inside an EMVCo template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmvField {
+    pub id: String,
+    pub value: String,
+}
+
+/// Merchant account information template (IDs 26-51), identified by its GUI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmvMerchantAccount {
+    pub id: String,
+    pub gui: String,
+    #[serde(default)]
+    pub fields: Vec<EmvField>,
+}
+
+/// An EMVCo merchant-presented payment (EMV QRCPS)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmvPayment {
+    pub merchant_name: String,
+    pub merchant_city: String,
+    pub country_code: String,
+    pub currency: String, // ISO 4217 numeric, e.g. "986"
+    #[serde(default)]
+    pub currency_code: Option<String>, // ISO 4217 alpha, filled in when parsing
+    pub amount: Option<String>,
+    pub merchant_category_code: Option<String>,
+    pub postal_code: Option<String>,
+    pub reference_label: Option<String>,
+    #[serde(default)]
+    pub dynamic: bool, // single-use code (point of initiation "12")
+    #[serde(default)]
+    pub network_accounts: Vec<EmvField>, // primitive IDs 02-25 (card networks)
+    #[serde(default)]
+    pub merchant_accounts: Vec<EmvMerchantAccount>,
+}
+
+/// Build an EMVCo merchant-presented payload with its trailing CRC
+pub fn build_emv(payment: &EmvPayment) -> Result<String, String> {
+    let name = payment.merchant_name.trim();
+    let city = payment.merchant_city.trim();
+    if name.is_empty() || city.is_empty() {
+        return Err("Merchant name and city are required".to_string());
+    }
+    check_length("Merchant name", name, 25)?;
+    check_length("Merchant city", city, 15)?;
+
+    let country = payment.country_code.trim().to_uppercase();
+    if country.len() != 2 || !country.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err("Country code must be a two-letter ISO code".to_string());
+    }
+
+    let currency = normalize_currency(&payment.currency)?;
+
+    let mcc = payment
+        .merchant_category_code
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or("0000");
+    if mcc.len() != 4 || !mcc.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("Merchant category code must be 4 digits".to_string());
+    }
+
+    if payment.network_accounts.is_empty() && payment.merchant_accounts.is_empty() {
+        return Err("At least one merchant account is required".to_string());
+    }
+
+    let amount = payment
+        .amount
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+
+    let mut payload = tlv("00", "01")?;
+    // Point of initiation is only required for single-use codes; absent means static
+    if payment.dynamic {
+        payload.push_str(&tlv("01", "12")?);
+    }
+
+    for account in &payment.network_accounts {
+        match account.id.parse::<u8>() {
+            Ok(2..=25) => payload.push_str(&tlv(&account.id, &account.value)?),
+            _ => return Err(format!("Network account ID '{}' must be 02-25", account.id)),
+        }
+    }
+
+    for account in &payment.merchant_accounts {
+        if !matches!(account.id.parse::<u8>(), Ok(26..=51)) || account.id.len() != 2 {
+            return Err(format!("Merchant account ID '{}' must be 26-51", account.id));
+        }
+        if account.gui.trim().is_empty() {
+            return Err(format!("Merchant account {} needs a globally unique identifier", account.id));
+        }
+
+        let mut template = tlv("00", account.gui.trim())?;
+        for field in &account.fields {
+            template.push_str(&tlv(&field.id, &field.value)?);
+        }
+        payload.push_str(&tlv(&account.id, &template)?);
+    }
+
+    payload.push_str(&tlv("52", mcc)?);
+    payload.push_str(&tlv("53", &currency)?);
+
+    if let Some(amount) = amount {
+        validate_emv_amount(amount)?;
+        payload.push_str(&tlv("54", amount)?);
+    }
+
+    payload.push_str(&tlv("58", &country)?);
+    payload.push_str(&tlv("59", name)?);
+    payload.push_str(&tlv("60", city)?);
+
+    if let Some(postal) = payment.postal_code.as_deref().filter(|s| !s.trim().is_empty()) {
+        check_length("Postal code", postal.trim(), 10)?;
+        payload.push_str(&tlv("61", postal.trim())?);
+    }
+
+    if let Some(label) = payment.reference_label.as_deref().filter(|s| !s.trim().is_empty()) {
+        check_length("Reference label", label.trim(), 25)?;
+        payload.push_str(&tlv("62", &tlv("05", label.trim())?)?);
+    }
+
+    Ok(append_crc(payload))
+}
+
+/// Parse an EMVCo merchant-presented payload, verifying its CRC
+pub fn parse_emv(content: &str) -> Result<EmvPayment, String> {
+    let content = content.trim();
+    verify_crc(content)?;
+
+    let fields = parse_tlv(content)?;
+    if fields.first().map(|f| (f.id.as_str(), f.value.as_str())) != Some(("00", "01")) {
+        return Err("Missing EMV payload format indicator".to_string());
+    }
+
+    let mut payment = EmvPayment::default();
+
+    for field in fields {
+        let id: u8 = field.id.parse().map_err(|_| format!("Invalid EMV tag '{}'", field.id))?;
+        match id {
+            1 => payment.dynamic = field.value == "12",
+            2..=25 => payment.network_accounts.push(field),
+            26..=51 => {
+                let sub = parse_tlv(&field.value)?;
+                let gui = sub
+                    .iter()
+                    .find(|f| f.id == "00")
+                    .map(|f| f.value.clone())
+                    .unwrap_or_default();
+                payment.merchant_accounts.push(EmvMerchantAccount {
+                    id: field.id,
+                    gui,
+                    fields: sub.into_iter().filter(|f| f.id != "00").collect(),
+                });
+            }
+            52 => payment.merchant_category_code = Some(field.value),
+            53 => {
+                payment.currency_code = currency_alpha(&field.value).map(str::to_string);
+                payment.currency = field.value;
+            }
+            54 => payment.amount = Some(field.value),
+            58 => payment.country_code = field.value,
+            59 => payment.merchant_name = field.value,
+            60 => payment.merchant_city = field.value,
+            61 => payment.postal_code = Some(field.value),
+            62 => {
+                payment.reference_label = parse_tlv(&field.value)?
+                    .into_iter()
+                    .find(|f| f.id == "05")
+                    .map(|f| f.value);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(payment)
+}
+
+/// Split a string of ID-length-value data objects; lengths count characters
+pub fn parse_tlv(data: &str) -> Result<Vec<EmvField>, String> {
+    let chars: Vec<char> = data.chars().collect();
+    let mut fields = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        if pos + 4 > chars.len() {
+            return Err("Truncated EMV data object".to_string());
+        }
+
+        let id: String = chars[pos..pos + 2].iter().collect();
+        let len: String = chars[pos + 2..pos + 4].iter().collect();
+        if !id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Invalid EMV tag '{}'", id));
+        }
+        let len: usize = len
+            .parse()
+            .map_err(|_| format!("Invalid length for EMV tag {}", id))?;
+
+        let start = pos + 4;
+        if start + len > chars.len() {
+            return Err(format!("EMV tag {} overruns the payload", id));
+        }
+
+        fields.push(EmvField {
+            id,
+            value: chars[start..start + len].iter().collect(),
+        });
+        pos = start + len;
+    }
+
+    Ok(fields)
+}
+
+/// CRC-16/CCITT-FALSE (polynomial 0x1021, initial value 0xFFFF)
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// Whether content looks like an EMVCo merchant-presented payload
+pub fn is_emv_payload(content: &str) -> bool {
+    let content = content.trim();
+    content.starts_with("000201")
+        && content.len() >= 8
+        && content
+            .get(..content.len() - 4)
+            .is_some_and(|data| data.ends_with("6304"))
+}
+
+fn tlv(id: &str, value: &str) -> Result<String, String> {
+    let len = value.chars().count();
+    if len > 99 {
+        return Err(format!("EMV tag {} value is {} characters, maximum is 99", id, len));
+    }
+    if id.len() != 2 || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Invalid EMV tag '{}'", id));
+    }
+    Ok(format!("{}{:02}{}", id, len, value))
+}
+
+fn append_crc(mut payload: String) -> String {
+    payload.push_str("6304");
+    let crc = crc16_ccitt(payload.as_bytes());
+    payload.push_str(&format!("{:04X}", crc));
+    payload
+}
+
+fn verify_crc(content: &str) -> Result<(), String> {
+    if !is_emv_payload(content) {
+        return Err("EMV payload must end with a CRC (tag 63)".to_string());
+    }
+
+    let (data, crc) = content.split_at(content.len() - 4);
+    let expected = u16::from_str_radix(crc, 16).map_err(|_| "Invalid EMV CRC".to_string())?;
+    let actual = crc16_ccitt(data.as_bytes());
+
+    if actual != expected {
+        return Err(format!(
+            "EMV CRC mismatch: payload says {:04X}, computed {:04X}",
+            expected, actual
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_emv_amount(amount: &str) -> Result<(), String> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let valid = amount.len() <= 13
+        && !whole.is_empty()
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.len() <= 3
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+        && !(amount.contains('.') && fraction.is_empty());
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid amount '{}'", amount))
+    }
+}
+
+/// ISO 4217 numeric/alpha pairs for currencies used by EMVCo QR schemes
+const CURRENCIES: &[(&str, &str)] = &[
+    ("036", "AUD"),
+    ("050", "BDT"),
+    ("116", "KHR"),
+    ("124", "CAD"),
+    ("144", "LKR"),
+    ("156", "CNY"),
+    ("344", "HKD"),
+    ("356", "INR"),
+    ("360", "IDR"),
+    ("392", "JPY"),
+    ("410", "KRW"),
+    ("418", "LAK"),
+    ("458", "MYR"),
+    ("484", "MXN"),
+    ("524", "NPR"),
+    ("586", "PKR"),
+    ("608", "PHP"),
+    ("682", "SAR"),
+    ("702", "SGD"),
+    ("704", "VND"),
+    ("756", "CHF"),
+    ("764", "THB"),
+    ("784", "AED"),
+    ("826", "GBP"),
+    ("840", "USD"),
+    ("901", "TWD"),
+    ("978", "EUR"),
+    ("986", "BRL"),
+];
+
+/// Look up the ISO 4217 alpha code for a numeric currency code
+pub fn currency_alpha(numeric: &str) -> Option<&'static str> {
+    CURRENCIES
+        .iter()
+        .find(|(n, _)| *n == numeric)
+        .map(|(_, alpha)| *alpha)
+}
+
+/// Accept a numeric or known alpha ISO 4217 code and return the numeric form
+fn normalize_currency(currency: &str) -> Result<String, String> {
+    let currency = currency.trim().to_uppercase();
+
+    if currency.len() == 3 && currency.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(currency);
+    }
+
+    CURRENCIES
+        .iter()
+        .find(|(_, alpha)| *alpha == currency)
+        .map(|(numeric, _)| numeric.to_string())
+        .ok_or_else(|| format!("Unknown currency '{}'", currency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Static PIX example from the Banco Central do Brasil manual
+    const PIX_EXAMPLE: &str = "00020126580014br.gov.bcb.pix0136123e4567-e12b-12d1-a456-4266554400005204000053039865802BR5913Fulano de Tal6008BRASILIA62070503***63041D3D";
+
+    fn sample_payment() -> EmvPayment {
+        EmvPayment {
+            merchant_name: "Best Transport".to_string(),
+            merchant_city: "Beijing".to_string(),
+            country_code: "CN".to_string(),
+            currency: "CNY".to_string(),
+            amount: Some("23.72".to_string()),
+            merchant_category_code: Some("4111".to_string()),
+            reference_label: Some("INV-0042".to_string()),
+            dynamic: true,
+            merchant_accounts: vec![EmvMerchantAccount {
+                id: "29".to_string(),
+                gui: "com.example.pay".to_string(),
+                fields: vec![EmvField {
+                    id: "01".to_string(),
+                    value: "12345678".to_string(),
+                }],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_crc16_ccitt() {
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+        assert_eq!(crc16_ccitt(b""), 0xFFFF);
+    }
+
+    #[test]
+    fn test_parse_tlv() {
+        let fields = parse_tlv("000201010211").unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0], EmvField { id: "00".into(), value: "01".into() });
+        assert_eq!(fields[1], EmvField { id: "01".into(), value: "11".into() });
+
+        assert!(parse_tlv("0002").is_err());
+        assert!(parse_tlv("00990").is_err());
+        assert!(parse_tlv("AB0201").is_err());
+    }
+
+    #[test]
+    fn test_parse_emv_pix_example() {
+        let payment = parse_emv(PIX_EXAMPLE).unwrap();
+
+        assert_eq!(payment.merchant_name, "Fulano de Tal");
+        assert_eq!(payment.merchant_city, "BRASILIA");
+        assert_eq!(payment.country_code, "BR");
+        assert_eq!(payment.currency, "986");
+        assert_eq!(payment.currency_code, Some("BRL".to_string()));
+        assert_eq!(payment.amount, None);
+        assert!(!payment.dynamic);
+        assert_eq!(payment.reference_label, Some("***".to_string()));
+        assert_eq!(payment.merchant_accounts.len(), 1);
+        assert_eq!(payment.merchant_accounts[0].gui, "br.gov.bcb.pix");
+        assert_eq!(
+            payment.merchant_accounts[0].fields[0].value,
+            "123e4567-e12b-12d1-a456-426655440000"
+        );
+    }
+
+    #[test]
+    fn test_parse_emv_rejects_bad_crc() {
+        let tampered = PIX_EXAMPLE.replace("Fulano", "Fulana");
+        assert!(parse_emv(&tampered).unwrap_err().contains("CRC mismatch"));
+        assert!(parse_emv("000201010211").is_err());
+    }
+
+    #[test]
+    fn test_build_emv_round_trip() {
+        let payload = build_emv(&sample_payment()).unwrap();
+        assert!(payload.starts_with("000201010212"));
+        assert!(payload.contains("5303156"));
+        assert!(payload.contains("540523.72"));
+
+        let parsed = parse_emv(&payload).unwrap();
+        assert_eq!(parsed.merchant_name, "Best Transport");
+        assert_eq!(parsed.currency_code, Some("CNY".to_string()));
+        assert_eq!(parsed.amount, Some("23.72".to_string()));
+        assert_eq!(parsed.merchant_category_code, Some("4111".to_string()));
+        assert_eq!(parsed.reference_label, Some("INV-0042".to_string()));
+        assert!(parsed.dynamic);
+        assert_eq!(parsed.merchant_accounts, sample_payment().merchant_accounts);
+    }
+
+    #[test]
+    fn test_build_emv_static_omits_point_of_initiation() {
+        let mut payment = sample_payment();
+        payment.dynamic = false;
+        let payload = build_emv(&payment).unwrap();
+        assert!(payload.starts_with("00020129"));
+        assert!(!parse_emv(&payload).unwrap().dynamic);
+    }
+
+    #[test]
+    fn test_build_emv_rejects_invalid_fields() {
+        let mut payment = sample_payment();
+        payment.merchant_name = "x".repeat(26);
+        assert!(build_emv(&payment).is_err());
+
+        let mut payment = sample_payment();
+        payment.currency = "XYZ".to_string();
+        assert!(build_emv(&payment).unwrap_err().contains("Unknown currency"));
+
+        let mut payment = sample_payment();
+        payment.amount = Some("12.".to_string());
+        assert!(build_emv(&payment).is_err());
+
+        let mut payment = sample_payment();
+        payment.merchant_accounts[0].id = "99".to_string();
+        assert!(build_emv(&payment).is_err());
+
+        let mut payment = sample_payment();
+        payment.merchant_accounts.clear();
+        assert!(build_emv(&payment).unwrap_err().contains("merchant account"));
+    }
+
+    #[test]
+    fn test_is_emv_payload() {
+        assert!(is_emv_payload(PIX_EXAMPLE));
+        assert!(!is_emv_payload("000201"));
+        assert!(!is_emv_payload("https://example.com"));
+    }
+}
@@ -0,0 +1,307 @@
+use super::iban::{validate_creditor_reference, validate_iban};
+use super::{check_length, format_cents, non_empty, parse_amount};
+use serde::{Deserialize, Serialize};
+
+/// EPC069-12 mandates error correction level M for SEPA credit transfer codes
+pub const EPC_ERROR_CORRECTION: &str = "M";
+
+/// Maximum payload size allowed by EPC069-12
+const MAX_PAYLOAD_BYTES: usize = 331;
+
+/// A SEPA credit transfer as encoded in an EPC ("GiroCode") QR code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpcPayment {
+    pub bic: Option<String>,
+    pub name: String,
+    pub iban: String,
+    pub amount: Option<String>, // EUR, e.g. "12.50"
+    pub purpose: Option<String>,
+    pub reference: Option<String>,  // structured (ISO 11649) remittance
+    pub remittance: Option<String>, // unstructured remittance text
+    pub information: Option<String>,
+}
+
+/// Build an EPC069-12 payload, validating every field against the standard
+pub fn build_epc(payment: &EpcPayment) -> Result<String, String> {
+    let name = payment.name.trim();
+    if name.is_empty() {
+        return Err("Beneficiary name is required".to_string());
+    }
+    check_length("Beneficiary name", name, 70)?;
+
+    let iban = validate_iban(&payment.iban)?;
+
+    let bic = match non_empty(&payment.bic) {
+        Some(bic) => validate_bic(bic)?,
+        None => String::new(),
+    };
+
+    let amount = match non_empty(&payment.amount) {
+        Some(amount) => format!("EUR{}", format_cents(parse_amount(amount)?)),
+        None => String::new(),
+    };
+
+    let purpose = match non_empty(&payment.purpose) {
+        Some(purpose) => {
+            let purpose = purpose.to_uppercase();
+            if purpose.len() != 4 || !purpose.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                return Err("Purpose code must be 4 letters or digits".to_string());
+            }
+            purpose
+        }
+        None => String::new(),
+    };
+
+    let reference = non_empty(&payment.reference);
+    let remittance = non_empty(&payment.remittance);
+    if reference.is_some() && remittance.is_some() {
+        return Err("Use either a structured reference or remittance text, not both".to_string());
+    }
+
+    let reference = match reference {
+        Some(reference) if reference.to_uppercase().starts_with("RF") => {
+            validate_creditor_reference(reference)?
+        }
+        Some(reference) => {
+            check_length("Reference", reference, 35)?;
+            reference.to_string()
+        }
+        None => String::new(),
+    };
+
+    let remittance = match remittance {
+        Some(text) => {
+            check_length("Remittance text", text, 140)?;
+            text.to_string()
+        }
+        None => String::new(),
+    };
+
+    let information = match non_empty(&payment.information) {
+        Some(info) => {
+            check_length("Beneficiary information", info, 70)?;
+            info.to_string()
+        }
+        None => String::new(),
+    };
+
+    let mut lines = vec![
+        "BCD".to_string(),
+        "002".to_string(),
+        "1".to_string(), // UTF-8
+        "SCT".to_string(),
+        bic,
+        name.to_string(),
+        iban,
+        amount,
+        purpose,
+        reference,
+        remittance,
+        information,
+    ];
+
+    // Trailing empty elements may be omitted
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    let payload = lines.join("\n");
+    if payload.len() > MAX_PAYLOAD_BYTES {
+        return Err(format!(
+            "EPC payload is {} bytes, maximum is {}",
+            payload.len(),
+            MAX_PAYLOAD_BYTES
+        ));
+    }
+
+    Ok(payload)
+}
+
+/// Parse an EPC069-12 payload back into its fields
+pub fn parse_epc(content: &str) -> Result<EpcPayment, String> {
+    let lines: Vec<&str> = content
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r'))
+        .collect();
+
+    if lines.first().map(|l| l.trim()) != Some("BCD") {
+        return Err("Missing BCD service tag".to_string());
+    }
+
+    let field = |idx: usize| lines.get(idx).map(|s| s.trim()).unwrap_or("");
+
+    let version = field(1);
+    if version != "001" && version != "002" {
+        return Err(format!("Unsupported EPC version '{}'", version));
+    }
+
+    match field(2).parse::<u8>() {
+        Ok(1..=8) => {}
+        _ => return Err(format!("Invalid character set '{}'", field(2))),
+    }
+
+    if field(3) != "SCT" {
+        return Err(format!("Unsupported identification code '{}'", field(3)));
+    }
+
+    let optional = |idx: usize| Some(field(idx).to_string()).filter(|s| !s.is_empty());
+
+    let amount = match optional(7) {
+        Some(raw) => {
+            let value = raw.strip_prefix("EUR").ok_or("Amount must be in EUR")?;
+            Some(format_cents(parse_amount(value)?))
+        }
+        None => None,
+    };
+
+    Ok(EpcPayment {
+        bic: optional(4),
+        name: field(5).to_string(),
+        iban: field(6).to_string(),
+        amount,
+        purpose: optional(8),
+        reference: optional(9),
+        remittance: optional(10),
+        information: optional(11),
+    })
+}
+
+/// Validate a BIC (ISO 9362): 8 or 11 characters, letters for bank and country code
+fn validate_bic(bic: &str) -> Result<String, String> {
+    let bic = bic.trim().to_uppercase();
+    let bytes = bic.as_bytes();
+
+    let valid = (bic.len() == 8 || bic.len() == 11)
+        && bytes[..6].iter().all(u8::is_ascii_uppercase)
+        && bytes[6..].iter().all(u8::is_ascii_alphanumeric);
+
+    if valid {
+        Ok(bic)
+    } else {
+        Err(format!("Invalid BIC '{}'", bic))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_payment() -> EpcPayment {
+        EpcPayment {
+            bic: Some("BFSWDE33BER".to_string()),
+            name: "Wikimedia Foerdergesellschaft".to_string(),
+            iban: "DE33 1002 0500 0001 1947 00".to_string(),
+            amount: Some("123.45".to_string()),
+            remittance: Some("Spende fuer Wikipedia".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_epc_full() {
+        let payload = build_epc(&sample_payment()).unwrap();
+        assert_eq!(
+            payload,
+            "BCD\n002\n1\nSCT\nBFSWDE33BER\nWikimedia Foerdergesellschaft\nDE33100205000001194700\nEUR123.45\n\n\nSpende fuer Wikipedia"
+        );
+    }
+
+    #[test]
+    fn test_build_epc_minimal_trims_trailing_fields() {
+        let payment = EpcPayment {
+            name: "Max Mustermann".to_string(),
+            iban: "DE89370400440532013000".to_string(),
+            ..Default::default()
+        };
+        let payload = build_epc(&payment).unwrap();
+        assert_eq!(payload, "BCD\n002\n1\nSCT\n\nMax Mustermann\nDE89370400440532013000");
+    }
+
+    #[test]
+    fn test_build_epc_normalizes_amount() {
+        let mut payment = sample_payment();
+        payment.amount = Some("5".to_string());
+        assert!(build_epc(&payment).unwrap().contains("\nEUR5.00\n"));
+
+        payment.amount = Some("0.5".to_string());
+        assert!(build_epc(&payment).unwrap().contains("\nEUR0.50\n"));
+    }
+
+    #[test]
+    fn test_build_epc_rejects_invalid_fields() {
+        let mut payment = sample_payment();
+        payment.iban = "DE33100205000001194701".to_string();
+        assert!(build_epc(&payment).unwrap_err().contains("checksum"));
+
+        let mut payment = sample_payment();
+        payment.bic = Some("BFSW".to_string());
+        assert!(build_epc(&payment).unwrap_err().contains("BIC"));
+
+        let mut payment = sample_payment();
+        payment.amount = Some("0".to_string());
+        assert!(build_epc(&payment).is_err());
+        payment.amount = Some("1000000000.00".to_string());
+        assert!(build_epc(&payment).is_err());
+        payment.amount = Some("1.234".to_string());
+        assert!(build_epc(&payment).is_err());
+
+        let mut payment = sample_payment();
+        payment.name = "x".repeat(71);
+        assert!(build_epc(&payment).unwrap_err().contains("maximum is 70"));
+
+        let mut payment = sample_payment();
+        payment.remittance = Some("x".repeat(141));
+        assert!(build_epc(&payment).unwrap_err().contains("maximum is 140"));
+
+        let mut payment = sample_payment();
+        payment.name = "  ".to_string();
+        assert!(build_epc(&payment).is_err());
+    }
+
+    #[test]
+    fn test_build_epc_reference_and_remittance_exclusive() {
+        let mut payment = sample_payment();
+        payment.reference = Some("RF18539007547034".to_string());
+        assert!(build_epc(&payment).unwrap_err().contains("not both"));
+
+        payment.remittance = None;
+        let payload = build_epc(&payment).unwrap();
+        assert!(payload.ends_with("\nRF18539007547034"));
+
+        payment.reference = Some("RF19539007547034".to_string());
+        assert!(build_epc(&payment).is_err());
+    }
+
+    #[test]
+    fn test_parse_epc_round_trip() {
+        let payload = build_epc(&sample_payment()).unwrap();
+        let parsed = parse_epc(&payload).unwrap();
+
+        assert_eq!(parsed.bic, Some("BFSWDE33BER".to_string()));
+        assert_eq!(parsed.name, "Wikimedia Foerdergesellschaft");
+        assert_eq!(parsed.iban, "DE33100205000001194700");
+        assert_eq!(parsed.amount, Some("123.45".to_string()));
+        assert_eq!(parsed.purpose, None);
+        assert_eq!(parsed.reference, None);
+        assert_eq!(parsed.remittance, Some("Spende fuer Wikipedia".to_string()));
+        assert_eq!(parsed.information, None);
+    }
+
+    #[test]
+    fn test_parse_epc_crlf_and_version_001() {
+        let content = "BCD\r\n001\r\n1\r\nSCT\r\nBFSWDE33BER\r\nName\r\nDE33100205000001194700\r\nEUR1\r\n";
+        let parsed = parse_epc(content).unwrap();
+        assert_eq!(parsed.name, "Name");
+        assert_eq!(parsed.amount, Some("1.00".to_string()));
+    }
+
+    #[test]
+    fn test_parse_epc_rejects_malformed() {
+        assert!(parse_epc("BCD\n003\n1\nSCT").is_err());
+        assert!(parse_epc("BCD\n002\n9\nSCT").is_err());
+        assert!(parse_epc("BCD\n002\n1\nINST").is_err());
+        assert!(parse_epc("hello").is_err());
+        assert!(parse_epc("BCD\n002\n1\nSCT\n\nName\nDE33100205000001194700\nUSD5").is_err());
+    }
+}
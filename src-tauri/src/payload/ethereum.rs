@@ -0,0 +1,273 @@
+use super::{format_units, non_empty, parse_units, query_pairs, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Wei per ether, as a number of decimal places
+pub const ETH_DECIMALS: u32 = 18;
+
+/// An EIP-681 `ethereum:` payment request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthereumUri {
+    pub address: String, // recipient, or the token contract for `transfer`
+    pub chain_id: Option<u64>,
+    pub function: Option<String>,
+    pub amount: Option<String>, // ETH, e.g. "1.5"
+    pub recipient: Option<String>, // `address` parameter of a token transfer
+    pub token_amount: Option<String>, // `uint256` parameter, in token base units
+    pub gas_limit: Option<String>,
+    pub gas_price: Option<String>,
+    #[serde(default)]
+    pub amount_wei: Option<String>, // filled in when parsing
+}
+
+/// Build an EIP-681 URI for a native payment or an ERC-20 `transfer`
+pub fn build_ethereum_uri(uri: &EthereumUri) -> Result<String, String> {
+    let address = normalize_target(&uri.address)?;
+
+    let mut out = format!("ethereum:{}", address);
+    if let Some(chain_id) = uri.chain_id {
+        out.push_str(&format!("@{}", chain_id));
+    }
+
+    let mut params = Vec::new();
+    match non_empty(&uri.function) {
+        Some("transfer") => {
+            let recipient = non_empty(&uri.recipient).ok_or("Token transfers need a recipient")?;
+            let token_amount = non_empty(&uri.token_amount).ok_or("Token transfers need an amount")?;
+            if !token_amount.bytes().all(|b| b.is_ascii_digit()) {
+                return Err("Token amount must be a whole number of base units".to_string());
+            }
+            out.push_str("/transfer");
+            params.push(("address", normalize_target(recipient)?));
+            params.push(("uint256", token_amount.to_string()));
+        }
+        Some(other) => return Err(format!("Unsupported function '{}'", other)),
+        None => {
+            if let Some(amount) = non_empty(&uri.amount) {
+                params.push(("value", parse_units(amount, ETH_DECIMALS)?.to_string()));
+            }
+        }
+    }
+
+    if let Some(gas_limit) = non_empty(&uri.gas_limit) {
+        params.push(("gasLimit", parse_number(gas_limit)?.to_string()));
+    }
+    if let Some(gas_price) = non_empty(&uri.gas_price) {
+        params.push(("gasPrice", parse_number(gas_price)?.to_string()));
+    }
+
+    for (i, (key, value)) in params.iter().enumerate() {
+        out.push(if i == 0 { '?' } else { '&' });
+        out.push_str(&format!("{}={}", key, value));
+    }
+    Ok(out)
+}
+
+/// Parse and validate an EIP-681 URI
+pub fn parse_ethereum_uri(content: &str) -> Result<EthereumUri, String> {
+    let content = content.trim();
+    let rest = strip_prefix_ci(content, "ethereum:").ok_or("Not an ethereum: URI")?;
+    let rest = rest.strip_prefix("pay-").unwrap_or(rest);
+
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (target, function) = match path.split_once('/') {
+        Some((target, function)) => (target, Some(function.to_string())),
+        None => (path, None),
+    };
+    let (address, chain_id) = match target.split_once('@') {
+        Some((address, chain)) => (
+            address,
+            Some(chain.parse().map_err(|_| format!("Invalid chain id '{}'", chain))?),
+        ),
+        None => (target, None),
+    };
+
+    let mut uri = EthereumUri {
+        address: normalize_target(address)?,
+        chain_id,
+        function,
+        ..Default::default()
+    };
+
+    for (key, value) in query_pairs(query) {
+        match key.as_str() {
+            "value" => {
+                let wei = parse_number(&value)?;
+                uri.amount = Some(format_units(wei, ETH_DECIMALS));
+                uri.amount_wei = Some(wei.to_string());
+            }
+            "address" => uri.recipient = Some(normalize_target(&value)?),
+            "uint256" => uri.token_amount = Some(parse_number(&value)?.to_string()),
+            "gas" | "gaslimit" => uri.gas_limit = Some(parse_number(&value)?.to_string()),
+            "gasprice" => uri.gas_price = Some(parse_number(&value)?.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(uri)
+}
+
+/// Validate an address, checking the EIP-55 checksum when it is mixed case.
+/// Returns the checksummed form.
+pub fn validate_ethereum_address(address: &str) -> Result<String, String> {
+    let hex = address
+        .strip_prefix("0x")
+        .filter(|hex| hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("'{}' is not a valid Ethereum address", address))?;
+
+    let checksummed = to_checksum_address(hex);
+    let is_single_case = hex == hex.to_lowercase() || hex == hex.to_uppercase();
+    if !is_single_case && checksummed[2..] != *hex {
+        return Err(format!("'{}' has an invalid EIP-55 checksum", address));
+    }
+
+    Ok(checksummed)
+}
+
+fn to_checksum_address(hex: &str) -> String {
+    let lower = hex.to_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+
+    let mut out = String::from("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        out.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    out
+}
+
+/// Accept a hex address or an ENS name
+fn normalize_target(target: &str) -> Result<String, String> {
+    let target = target.trim();
+    if target.starts_with("0x") {
+        validate_ethereum_address(target)
+    } else if target.contains('.') && !target.contains(char::is_whitespace) {
+        Ok(target.to_lowercase())
+    } else {
+        Err(format!("'{}' is not a valid Ethereum address or ENS name", target))
+    }
+}
+
+/// Parse an EIP-681 number, which may use scientific notation (e.g. "2.014e18")
+fn parse_number(value: &str) -> Result<u128, String> {
+    let invalid = || format!("Invalid number '{}'", value);
+
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<u32>().map_err(|_| invalid())?),
+        None => (value, 0),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", whole, fraction);
+    if whole.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    // The exponent must cover every fraction digit, since the result is an integer
+    let scale = exponent.checked_sub(fraction.len() as u32).ok_or_else(invalid)?;
+    digits
+        .parse::<u128>()
+        .ok()
+        .and_then(|n| n.checked_mul(10u128.checked_pow(scale)?))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_ethereum_address() {
+        // Examples from EIP-55
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(validate_ethereum_address(address).unwrap(), address);
+            assert_eq!(validate_ethereum_address(&address.to_lowercase()).unwrap(), address);
+        }
+
+        assert!(validate_ethereum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+        assert!(validate_ethereum_address("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_parse_ethereum_uri_payment() {
+        let uri = parse_ethereum_uri(
+            "ethereum:0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359?value=2.014e18",
+        )
+        .unwrap();
+
+        assert_eq!(uri.address, "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359");
+        assert_eq!(uri.amount, Some("2.014".to_string()));
+        assert_eq!(uri.amount_wei, Some("2014000000000000000".to_string()));
+        assert_eq!(uri.chain_id, None);
+
+        // Values are percent-decoded like every other URI payload
+        let uri = parse_ethereum_uri(
+            "ethereum:0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359?value=2%2E014e18&gasPrice=%32%31",
+        )
+        .unwrap();
+        assert_eq!(uri.amount, Some("2.014".to_string()));
+        assert_eq!(uri.gas_price, Some("21".to_string()));
+    }
+
+    #[test]
+    fn test_parse_ethereum_uri_token_transfer() {
+        let uri = parse_ethereum_uri(
+            "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7@1/transfer?address=0x8e23ee67d1332ad560396262c48ffbb01f93d052&uint256=1",
+        )
+        .unwrap();
+
+        assert_eq!(uri.chain_id, Some(1));
+        assert_eq!(uri.function, Some("transfer".to_string()));
+        assert_eq!(uri.token_amount, Some("1".to_string()));
+        assert!(uri.recipient.is_some());
+        assert_eq!(uri.amount, None);
+    }
+
+    #[test]
+    fn test_parse_ethereum_uri_rejects_invalid() {
+        assert!(parse_ethereum_uri("ethereum:0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+        assert!(parse_ethereum_uri("ethereum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed@main").is_err());
+        assert!(parse_ethereum_uri("ethereum:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed?value=1.5").is_err());
+        assert!(parse_ethereum_uri("bitcoin:1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").is_err());
+    }
+
+    #[test]
+    fn test_build_ethereum_uri() {
+        let payment = EthereumUri {
+            address: "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_string(),
+            chain_id: Some(1),
+            amount: Some("0.5".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_ethereum_uri(&payment).unwrap(),
+            "ethereum:0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed@1?value=500000000000000000"
+        );
+
+        let transfer = EthereumUri {
+            address: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+            function: Some("transfer".to_string()),
+            recipient: Some("alice.eth".to_string()),
+            token_amount: Some("1000000".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_ethereum_uri(&transfer).unwrap(),
+            "ethereum:0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed/transfer?address=alice.eth&uint256=1000000"
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("42").unwrap(), 42);
+        assert_eq!(parse_number("1e3").unwrap(), 1000);
+        assert_eq!(parse_number("2.5E2").unwrap(), 250);
+        assert!(parse_number("1.5").is_err());
+        assert!(parse_number("abc").is_err());
+    }
+}
@@ -0,0 +1,545 @@
+use super::{non_empty, query_pairs, strip_prefix_ci};
+use crate::barcode::gs1_check_digit;
+use serde::{Deserialize, Serialize};
+
+/// Resolver used for Digital Link URIs when no domain is given
+pub const GS1_RESOLVER: &str = "https://id.gs1.org";
+
+/// Ends a variable-length element when more elements follow (FNC1 in the symbol)
+const GS: char = '\u{1d}';
+
+/// AIs that identify the item and lead a Digital Link path
+const PRIMARY_KEYS: &[&str] = &["01", "00", "414", "8004"];
+
+/// Symbology identifiers a scanner may put before an element string
+const SYMBOLOGY_IDS: &[&str] = &["]d2", "]Q3", "]C1", "]e0", "]J1"];
+
+/// GS1 data built into, or parsed from, a Digital Link URI or element string
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gs1Data {
+    pub format: Option<String>, // "digital-link" (default) | "element-string"
+    pub domain: Option<String>, // Digital Link only; GS1_RESOLVER when absent
+    pub gtin: Option<String>,   // AI (01); GTIN-8, -12, -13 or -14 with its check digit
+    pub batch: Option<String>,  // AI (10)
+    pub expiry: Option<String>, // AI (17), YYMMDD or YYYY-MM-DD
+    pub serial: Option<String>, // AI (21)
+    #[serde(default)]
+    pub elements: Vec<Gs1Element>, // further AIs when building; every AI when parsed
+}
+
+/// One Application Identifier and its value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gs1Element {
+    pub ai: String,
+    #[serde(default)]
+    pub title: String, // GS1 data title, e.g. "BATCH/LOT"; filled in when parsed
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Digits(usize),      // exactly n digits
+    CheckDigits(usize), // exactly n digits, the last a GS1 check digit
+    Date,               // YYMMDD; a day of 00 means the end of the month
+    Numeric(usize),     // 1 to n digits
+    Text(usize),        // 1 to n characters of the GS1 82-character set
+}
+
+struct Ai {
+    code: &'static str,
+    title: &'static str,
+    format: Format,
+    decimals: bool, // a fourth digit gives the implied decimal point, e.g. 3103
+}
+
+const fn ai(code: &'static str, title: &'static str, format: Format, decimals: bool) -> Ai {
+    Ai { code, title, format, decimals }
+}
+
+/// The AIs this app understands
+const AIS: &[Ai] = &[
+    ai("00", "SSCC", Format::CheckDigits(18), false),
+    ai("01", "GTIN", Format::CheckDigits(14), false),
+    ai("02", "CONTENT", Format::CheckDigits(14), false),
+    ai("10", "BATCH/LOT", Format::Text(20), false),
+    ai("11", "PROD DATE", Format::Date, false),
+    ai("12", "DUE DATE", Format::Date, false),
+    ai("13", "PACK DATE", Format::Date, false),
+    ai("15", "BEST BEFORE or BEST BY", Format::Date, false),
+    ai("16", "SELL BY", Format::Date, false),
+    ai("17", "USE BY OR EXPIRY", Format::Date, false),
+    ai("20", "VARIANT", Format::Digits(2), false),
+    ai("21", "SERIAL", Format::Text(20), false),
+    ai("22", "CPV", Format::Text(20), false),
+    ai("235", "TPX", Format::Text(28), false),
+    ai("240", "ADDITIONAL ID", Format::Text(30), false),
+    ai("241", "CUST. PART No.", Format::Text(30), false),
+    ai("254", "GLN EXTENSION COMPONENT", Format::Text(20), false),
+    ai("30", "VAR. COUNT", Format::Numeric(8), false),
+    ai("310", "NET WEIGHT (kg)", Format::Digits(6), true),
+    ai("37", "COUNT", Format::Numeric(8), false),
+    ai("390", "AMOUNT", Format::Numeric(15), true),
+    ai("392", "PRICE", Format::Numeric(15), true),
+    ai("400", "ORDER NUMBER", Format::Text(30), false),
+    ai("410", "SHIP TO LOC", Format::CheckDigits(13), false),
+    ai("414", "LOC No.", Format::CheckDigits(13), false),
+    ai("422", "ORIGIN", Format::Digits(3), false),
+    ai("8004", "GIAI", Format::Text(30), false),
+    ai("8200", "PRODUCT URL", Format::Text(70), false),
+];
+
+impl Ai {
+    fn matches(&self, ai: &str) -> bool {
+        match ai.strip_prefix(self.code) {
+            Some(rest) if self.decimals => rest.len() == 1 && rest.as_bytes()[0].is_ascii_digit(),
+            Some(rest) => rest.is_empty(),
+            None => false,
+        }
+    }
+
+    fn check(&self, ai: &str, value: &str) -> Result<(), String> {
+        let digits = value.bytes().all(|b| b.is_ascii_digit());
+        let invalid = |what: String| Err(format!("({}) {} must be {}", ai, self.title, what));
+
+        match self.format {
+            Format::Digits(n) | Format::CheckDigits(n) if !digits || value.len() != n => {
+                invalid(format!("{} digits", n))
+            }
+            Format::CheckDigits(n) => {
+                let expected = gs1_check_digit(&value[..n - 1]);
+                if value[n - 1..] != expected.to_string() {
+                    return Err(format!(
+                        "Invalid check digit in ({}) {}: expected {}, got {}",
+                        ai,
+                        self.title,
+                        expected,
+                        &value[n - 1..]
+                    ));
+                }
+                Ok(())
+            }
+            Format::Date => {
+                let part = |i: usize| value.get(i..i + 2).and_then(|p| p.parse::<u32>().ok());
+                let valid = digits
+                    && value.len() == 6
+                    && part(2).is_some_and(|month| (1..=12).contains(&month))
+                    && part(4).is_some_and(|day| day <= 31);
+                if valid {
+                    Ok(())
+                } else {
+                    invalid("a date as YYMMDD".to_string())
+                }
+            }
+            Format::Numeric(max) if !digits || value.is_empty() || value.len() > max => {
+                invalid(format!("1 to {} digits", max))
+            }
+            Format::Text(max) if value.is_empty() || value.chars().count() > max || !value.chars().all(is_cset82) => {
+                invalid(format!("1 to {} characters from the GS1 character set", max))
+            }
+            Format::Digits(_) | Format::Numeric(_) | Format::Text(_) => Ok(()),
+        }
+    }
+}
+
+/// GS1 AI encodable character set 82
+fn is_cset82(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!\"%&'()*+,-./:;<=>?_".contains(c)
+}
+
+fn lookup(ai: &str) -> Result<&'static Ai, String> {
+    AIS.iter()
+        .find(|spec| spec.matches(ai))
+        .ok_or_else(|| format!("Unknown or unsupported AI ({})", ai))
+}
+
+/// The AI at the start of a raw element string, with its length
+fn ai_at(data: &str) -> Option<(&'static Ai, usize)> {
+    AIS.iter().find_map(|spec| {
+        let len = spec.code.len() + spec.decimals as usize;
+        data.get(..len).filter(|ai| spec.matches(ai)).map(|_| (spec, len))
+    })
+}
+
+/// Length of AI plus value for the AIs whose length is predefined by their
+/// first two digits. Only these may be followed by another AI without a GS.
+fn predefined_length(ai: &str) -> Option<usize> {
+    match ai.get(..2)? {
+        "00" => Some(20),
+        "01" | "02" | "41" => Some(16),
+        "11" | "12" | "13" | "15" | "16" | "17" => Some(8),
+        "20" => Some(4),
+        "31" | "32" | "33" | "34" | "35" | "36" => Some(10),
+        _ => None,
+    }
+}
+
+/// Qualifiers allowed in a Digital Link path after a primary key, in order
+fn qualifiers(primary_key: &str) -> &'static [&'static str] {
+    match primary_key {
+        "01" => &["22", "10", "21"],
+        "414" => &["254"],
+        _ => &[],
+    }
+}
+
+/// Left-pad a GTIN-8, -12 or -13 to the 14 digits of AI (01)
+fn pad_gtin(gtin: &str) -> Result<String, String> {
+    if !matches!(gtin.len(), 8 | 12 | 13 | 14) || !gtin.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("GTIN must be 8, 12, 13 or 14 digits".to_string());
+    }
+    Ok(format!("{:0>14}", gtin))
+}
+
+/// Accept ISO dates (YYYY-MM-DD) as well as GS1's YYMMDD
+fn gs1_date(date: &str) -> String {
+    let bytes = date.as_bytes();
+    if date.len() == 10 && bytes[4] == b'-' && bytes[7] == b'-' {
+        format!("{}{}{}", &date[2..4], &date[5..7], &date[8..10])
+    } else {
+        date.to_string()
+    }
+}
+
+/// Build a Digital Link URI or an element string. Element strings separate
+/// variable-length values with GS, as expected by GS1 Data Matrix.
+pub fn build_gs1(data: &Gs1Data) -> Result<String, String> {
+    let elements = collect_elements(data)?;
+
+    match non_empty(&data.format).unwrap_or("digital-link") {
+        "digital-link" => build_digital_link(non_empty(&data.domain).unwrap_or(GS1_RESOLVER), &elements),
+        "element-string" => Ok(element_string(&elements)),
+        other => Err(format!("Unknown GS1 format '{}', expected digital-link or element-string", other)),
+    }
+}
+
+/// Validated (AI, value) pairs, primary key first
+fn collect_elements(data: &Gs1Data) -> Result<Vec<(String, String)>, String> {
+    let named = [
+        ("01", non_empty(&data.gtin).map(str::to_string)),
+        ("10", non_empty(&data.batch).map(str::to_string)),
+        ("17", non_empty(&data.expiry).map(gs1_date)),
+        ("21", non_empty(&data.serial).map(str::to_string)),
+    ];
+    let given = named
+        .into_iter()
+        .filter_map(|(ai, value)| Some((ai.to_string(), value?)))
+        .chain(data.elements.iter().map(|e| {
+            let ai = e.ai.trim().trim_start_matches('(').trim_end_matches(')');
+            (ai.to_string(), e.value.trim().to_string())
+        }));
+
+    let mut elements: Vec<(String, String)> = Vec::new();
+    for (ai, value) in given {
+        let value = if ai == "01" { pad_gtin(&value)? } else { value };
+        lookup(&ai)?.check(&ai, &value)?;
+        match elements.iter().find(|(existing, _)| *existing == ai) {
+            // Parsed data lists the named AIs among its elements too
+            Some((_, existing)) if *existing == value => {}
+            Some(_) => return Err(format!("AI ({}) is given twice with different values", ai)),
+            None => elements.push((ai, value)),
+        }
+    }
+
+    let primary = elements
+        .iter()
+        .position(|(ai, _)| PRIMARY_KEYS.contains(&ai.as_str()))
+        .ok_or("A GTIN or another primary key, (00), (414) or (8004), is required")?;
+    let key = elements.remove(primary);
+    elements.insert(0, key);
+    Ok(elements)
+}
+
+fn build_digital_link(domain: &str, elements: &[(String, String)]) -> Result<String, String> {
+    let domain = domain.trim_end_matches('/');
+    if strip_prefix_ci(domain, "https://").or_else(|| strip_prefix_ci(domain, "http://")).is_none_or(str::is_empty) {
+        return Err("Digital Link domain must be an http:// or https:// address".to_string());
+    }
+
+    let (key, value) = &elements[0];
+    let mut uri = format!("{}/{}/{}", domain, key, urlencoding::encode(value));
+    let qualifiers = qualifiers(key);
+    for qualifier in qualifiers {
+        if let Some((ai, value)) = elements.iter().find(|(ai, _)| ai == qualifier) {
+            uri.push_str(&format!("/{}/{}", ai, urlencoding::encode(value)));
+        }
+    }
+
+    let mut query = Vec::new();
+    for (ai, value) in &elements[1..] {
+        if PRIMARY_KEYS.contains(&ai.as_str()) {
+            return Err(format!("A Digital Link has one primary key; ({}) and ({}) were given", key, ai));
+        }
+        if !qualifiers.contains(&ai.as_str()) {
+            query.push(format!("{}={}", ai, urlencoding::encode(value)));
+        }
+    }
+    if !query.is_empty() {
+        uri.push('?');
+        uri.push_str(&query.join("&"));
+    }
+    Ok(uri)
+}
+
+fn element_string(elements: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (i, (ai, value)) in elements.iter().enumerate() {
+        out.push_str(ai);
+        out.push_str(value);
+        if predefined_length(ai).is_none() && i + 1 < elements.len() {
+            out.push(GS);
+        }
+    }
+    out
+}
+
+/// Whether content is a GS1 Digital Link URI ("digital-link") or an element
+/// string that starts with a primary key ("element-string")
+pub fn gs1_format(content: &str) -> Option<&'static str> {
+    let format = parse_gs1(content).ok()?.format?;
+    ["digital-link", "element-string"].into_iter().find(|f| *f == format)
+}
+
+/// Parse a Digital Link URI, or an element string in raw form (GS separated)
+/// or bracketed form ("(01)09506000134352(10)ABC"), into named AIs
+pub fn parse_gs1(content: &str) -> Result<Gs1Data, String> {
+    let content = content.trim_matches(|c: char| c == ' ' || c == '\r' || c == '\n' || c == '\t');
+
+    let (format, domain, pairs) =
+        if strip_prefix_ci(content, "https://").or_else(|| strip_prefix_ci(content, "http://")).is_some() {
+            let (domain, pairs) = parse_digital_link(content)?;
+            ("digital-link", Some(domain), pairs)
+        } else {
+            let pairs = parse_element_string(content)?;
+            if !pairs.first().is_some_and(|(ai, _)| PRIMARY_KEYS.contains(&ai.as_str())) {
+                return Err("Element string doesn't start with a GTIN or another primary key".to_string());
+            }
+            ("element-string", None, pairs)
+        };
+
+    let mut elements = Vec::new();
+    for (ai, value) in pairs {
+        let spec = lookup(&ai)?;
+        spec.check(&ai, &value)?;
+        elements.push(Gs1Element {
+            title: spec.title.to_string(),
+            ai,
+            value,
+        });
+    }
+    let find = |ai: &str| elements.iter().find(|e| e.ai == ai).map(|e| e.value.clone());
+
+    Ok(Gs1Data {
+        format: Some(format.to_string()),
+        domain,
+        gtin: find("01"),
+        batch: find("10"),
+        expiry: find("17"),
+        serial: find("21"),
+        elements,
+    })
+}
+
+/// Domain (with any path before the primary key) and the AIs of a Digital Link
+fn parse_digital_link(content: &str) -> Result<(String, Vec<(String, String)>), String> {
+    let content = content.split('#').next().unwrap_or(content);
+    let (path, query) = content.split_once('?').unwrap_or((content, ""));
+    let path = path.trim_end_matches('/');
+
+    // "https:", "", host, then the path segments
+    let segments: Vec<&str> = path.split('/').collect();
+    let start = (3..segments.len().saturating_sub(1))
+        .find(|&i| PRIMARY_KEYS.contains(&segments[i]))
+        .ok_or("No GS1 primary key in the URI path")?;
+
+    let decode = |value: &str| {
+        urlencoding::decode(value)
+            .map(|v| v.into_owned())
+            .map_err(|_| format!("Invalid percent-encoding in '{}'", value))
+    };
+
+    let primary = segments[start];
+    let mut pairs = Vec::new();
+    for pair in segments[start..].chunks(2) {
+        let [ai, value] = pair else {
+            return Err(format!("AI ({}) has no value in the Digital Link path", pair[0]));
+        };
+        if pairs.is_empty() {
+            let value = decode(value)?;
+            let value = if *ai == "01" { pad_gtin(&value)? } else { value };
+            pairs.push((ai.to_string(), value));
+        } else if qualifiers(primary).contains(ai) {
+            pairs.push((ai.to_string(), decode(value)?));
+        } else {
+            return Err(format!("({}) can't follow ({}) in a Digital Link path", ai, primary));
+        }
+    }
+
+    // Non-numeric keys such as linkType are resolver options, not data
+    for (key, value) in query_pairs(query) {
+        if !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit()) {
+            pairs.push((key, value));
+        }
+    }
+
+    Ok((segments[..start].join("/"), pairs))
+}
+
+fn parse_element_string(content: &str) -> Result<Vec<(String, String)>, String> {
+    if !content.is_ascii() {
+        return Err("GS1 element strings hold ASCII characters only".to_string());
+    }
+    let mut pairs = Vec::new();
+
+    if content.starts_with('(') {
+        let mut rest = content;
+        while let Some(body) = rest.strip_prefix('(') {
+            let (ai, after) = body.split_once(')').ok_or("Unclosed '(' in element string")?;
+            let end = after.find('(').unwrap_or(after.len());
+            pairs.push((ai.to_string(), after[..end].to_string()));
+            rest = &after[end..];
+        }
+        return Ok(pairs);
+    }
+
+    let mut rest = SYMBOLOGY_IDS
+        .iter()
+        .find_map(|id| content.strip_prefix(id))
+        .unwrap_or(content)
+        .trim_start_matches(GS);
+    while !rest.is_empty() {
+        let (_, len) = ai_at(rest).ok_or_else(|| {
+            format!("Unknown or unsupported AI at '{}'", rest.chars().take(4).collect::<String>())
+        })?;
+        let (ai, after) = rest.split_at(len);
+        let end = match predefined_length(ai) {
+            Some(total) => (total - len).min(after.len()),
+            None => after.find(GS).unwrap_or(after.len()),
+        };
+        pairs.push((ai.to_string(), after[..end].to_string()));
+        rest = after[end..].trim_start_matches(GS);
+    }
+    if pairs.is_empty() {
+        return Err("Element string is empty".to_string());
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Gs1Data {
+        Gs1Data {
+            gtin: Some("9506000134352".to_string()),
+            batch: Some("LOT/42".to_string()),
+            expiry: Some("2026-12-31".to_string()),
+            serial: Some("S123".to_string()),
+            elements: vec![Gs1Element {
+                ai: "3103".to_string(),
+                value: "000525".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_digital_link() {
+        assert_eq!(
+            build_gs1(&sample()).unwrap(),
+            "https://id.gs1.org/01/09506000134352/10/LOT%2F42/21/S123?17=261231&3103=000525"
+        );
+
+        let data = Gs1Data {
+            domain: Some("https://brand.example/gs1/".to_string()),
+            ..sample()
+        };
+        assert!(build_gs1(&data).unwrap().starts_with("https://brand.example/gs1/01/09506000134352/"));
+    }
+
+    #[test]
+    fn test_build_element_string() {
+        let data = Gs1Data {
+            format: Some("element-string".to_string()),
+            ..sample()
+        };
+        // Only the variable-length batch and serial need a separator
+        assert_eq!(
+            build_gs1(&data).unwrap(),
+            "010950600013435210LOT/42\u{1d}1726123121S123\u{1d}3103000525"
+        );
+    }
+
+    #[test]
+    fn test_build_gs1_rejects_invalid_data() {
+        let with = |change: fn(&mut Gs1Data)| {
+            let mut data = sample();
+            change(&mut data);
+            build_gs1(&data).unwrap_err()
+        };
+
+        assert_eq!(
+            with(|d| d.gtin = Some("9506000134353".to_string())),
+            "Invalid check digit in (01) GTIN: expected 2, got 3"
+        );
+        assert_eq!(with(|d| d.gtin = Some("12345".to_string())), "GTIN must be 8, 12, 13 or 14 digits");
+        assert_eq!(with(|d| d.expiry = Some("261331".to_string())), "(17) USE BY OR EXPIRY must be a date as YYMMDD");
+        assert!(with(|d| d.batch = Some("LOT#1".to_string())).starts_with("(10) BATCH/LOT must be 1 to 20"));
+        assert_eq!(with(|d| d.elements[0].ai = "7003".to_string()), "Unknown or unsupported AI (7003)");
+        assert!(with(|d| d.gtin = None).contains("primary key"));
+        assert!(with(|d| d.elements.push(Gs1Element {
+            ai: "21".to_string(),
+            value: "OTHER".to_string(),
+            ..Default::default()
+        }))
+        .contains("twice"));
+        assert!(with(|d| d.format = Some("gs1-128".to_string())).starts_with("Unknown GS1 format"));
+    }
+
+    #[test]
+    fn test_parse_element_strings() {
+        let data = Gs1Data {
+            format: Some("element-string".to_string()),
+            ..sample()
+        };
+        let raw = build_gs1(&data).unwrap();
+        let parsed = parse_gs1(&format!("]d2{}", raw)).unwrap();
+        assert_eq!(parsed.gtin.as_deref(), Some("09506000134352"));
+        assert_eq!(parsed.batch.as_deref(), Some("LOT/42"));
+        assert_eq!(parsed.expiry.as_deref(), Some("261231"));
+        assert_eq!(parsed.serial.as_deref(), Some("S123"));
+        assert_eq!(parsed.elements[4].ai, "3103");
+        assert_eq!(parsed.elements[4].title, "NET WEIGHT (kg)");
+        // Parsed data builds back to the same content
+        assert_eq!(build_gs1(&parsed).unwrap(), raw);
+
+        let bracketed = parse_gs1("(01)09506000134352(17)261231(10)ABC123").unwrap();
+        assert_eq!(bracketed.elements, parse_gs1("01095060001343521726123110ABC123").unwrap().elements);
+        assert_eq!(bracketed.elements[1].title, "USE BY OR EXPIRY");
+
+        assert!(parse_gs1("10ABC123").is_err());
+        assert!(parse_gs1("(01)09506000134353").is_err());
+    }
+
+    #[test]
+    fn test_parse_digital_link() {
+        let parsed = parse_gs1("https://brand.example/gs1/01/9506000134352/10/LOT%2F42?17=261231&linkType=gs1:pip").unwrap();
+        assert_eq!(parsed.format.as_deref(), Some("digital-link"));
+        assert_eq!(parsed.domain.as_deref(), Some("https://brand.example/gs1"));
+        assert_eq!(parsed.gtin.as_deref(), Some("09506000134352"));
+        assert_eq!(parsed.batch.as_deref(), Some("LOT/42"));
+        assert_eq!(parsed.expiry.as_deref(), Some("261231"));
+        assert_eq!(parsed.elements.len(), 3);
+
+        let uri = build_gs1(&sample()).unwrap();
+        assert_eq!(build_gs1(&parse_gs1(&uri).unwrap()).unwrap(), uri);
+
+        assert!(parse_gs1("https://example.com/01/2024/report").is_err());
+        assert!(parse_gs1("https://id.gs1.org/01/09506000134352/17/261231").is_err());
+        assert!(parse_gs1("https://id.gs1.org/01/09506000134352?17=261399").is_err());
+        assert_eq!(gs1_format("https://id.gs1.org/00/106141412345678908"), Some("digital-link"));
+        assert_eq!(gs1_format("https://example.com/"), None);
+    }
+}
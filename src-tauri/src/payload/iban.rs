@@ -0,0 +1,123 @@
+/// Normalize an IBAN by removing spaces and upper-casing it
+pub fn normalize_iban(iban: &str) -> String {
+    iban.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Validate an IBAN (ISO 13616) and return it in normalized electronic form
+pub fn validate_iban(iban: &str) -> Result<String, String> {
+    let iban = normalize_iban(iban);
+
+    if iban.len() < 15 || iban.len() > 34 {
+        return Err(format!(
+            "IBAN must be 15-34 characters, got {}",
+            iban.len()
+        ));
+    }
+
+    let bytes = iban.as_bytes();
+    if !bytes[..2].iter().all(u8::is_ascii_uppercase) {
+        return Err("IBAN must start with a two-letter country code".to_string());
+    }
+    if !bytes[2..4].iter().all(u8::is_ascii_digit) {
+        return Err("IBAN check digits must be numeric".to_string());
+    }
+    if !bytes.iter().all(u8::is_ascii_alphanumeric) {
+        return Err("IBAN may only contain letters and digits".to_string());
+    }
+
+    // Move the country code and check digits to the end before computing mod 97
+    let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+    if mod97(&rearranged) != Some(1) {
+        return Err("IBAN checksum is invalid".to_string());
+    }
+
+    Ok(iban)
+}
+
+/// Validate an ISO 11649 creditor reference ("RF" followed by two check digits)
+pub fn validate_creditor_reference(reference: &str) -> Result<String, String> {
+    let reference = normalize_iban(reference);
+
+    if !reference.starts_with("RF") || reference.len() < 5 || reference.len() > 25 {
+        return Err("Creditor reference must be 'RF', two check digits and up to 21 characters".to_string());
+    }
+    if !reference.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err("Creditor reference may only contain letters and digits".to_string());
+    }
+
+    let rearranged = format!("{}{}", &reference[4..], &reference[..4]);
+    if mod97(&rearranged) != Some(1) {
+        return Err("Creditor reference checksum is invalid".to_string());
+    }
+
+    Ok(reference)
+}
+
+/// Compute the ISO 7064 MOD 97-10 remainder, mapping letters A-Z to 10-35
+pub fn mod97(s: &str) -> Option<u32> {
+    let mut remainder: u32 = 0;
+
+    for c in s.chars() {
+        let value = c.to_digit(36)?;
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+
+    Some(remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_iban_valid() {
+        assert_eq!(
+            validate_iban("DE89 3704 0044 0532 0130 00").unwrap(),
+            "DE89370400440532013000"
+        );
+        assert_eq!(
+            validate_iban("be71096123456769").unwrap(),
+            "BE71096123456769"
+        );
+        assert!(validate_iban("CH9300762011623852957").is_ok());
+    }
+
+    #[test]
+    fn test_validate_iban_bad_checksum() {
+        let err = validate_iban("DE89370400440532013001").unwrap_err();
+        assert!(err.contains("checksum"));
+    }
+
+    #[test]
+    fn test_validate_iban_bad_format() {
+        assert!(validate_iban("DE89").is_err());
+        assert!(validate_iban("1289370400440532013000").is_err());
+        assert!(validate_iban("DEXX370400440532013000").is_err());
+        assert!(validate_iban("DE89-3704-0044-0532-0130-00").is_err());
+    }
+
+    #[test]
+    fn test_validate_creditor_reference() {
+        assert_eq!(
+            validate_creditor_reference("RF18 5390 0754 7034").unwrap(),
+            "RF18539007547034"
+        );
+        assert!(validate_creditor_reference("RF19539007547034").is_err());
+        assert!(validate_creditor_reference("XX18539007547034").is_err());
+    }
+
+    #[test]
+    fn test_mod97() {
+        assert_eq!(mod97("0"), Some(0));
+        assert_eq!(mod97("97"), Some(0));
+        assert_eq!(mod97("A"), Some(10));
+        assert_eq!(mod97("!"), None);
+    }
+}
@@ -0,0 +1,344 @@
+use super::bech32::{self, Variant};
+use super::format_units;
+use serde::{Deserialize, Serialize};
+
+/// Millisatoshis per bitcoin, as a number of decimal places
+const MSAT_DECIMALS: u32 = 11;
+
+/// Expiry assumed when an invoice carries no `x` field, in seconds
+const DEFAULT_EXPIRY: u64 = 3600;
+
+/// Length of the recoverable signature at the end of an invoice, in 5-bit words
+const SIGNATURE_WORDS: usize = 104;
+
+/// A decoded BOLT11 invoice. The checksum and field layout are verified; the
+/// node signature is not, since that needs the payee's public key recovered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bolt11Invoice {
+    pub network: String,
+    pub amount: Option<String>, // BTC, e.g. "0.0025"
+    pub amount_msat: Option<u64>,
+    pub timestamp: u64,
+    pub created_at: String,
+    pub expiry: u64, // seconds after `timestamp`
+    pub expires_at: String,
+    pub payment_hash: String,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub payee: Option<String>,
+    pub min_final_cltv_expiry: Option<u64>,
+}
+
+/// A `lightning:` payment request: a BOLT11 invoice or an LNURL
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LightningPayment {
+    pub request: String,
+    #[serde(default)]
+    pub lnurl: Option<String>, // decoded LNURL endpoint, filled in when parsing
+    #[serde(default)]
+    pub invoice: Option<Bolt11Invoice>, // filled in when parsing
+}
+
+/// Build a `lightning:` URI for an existing invoice or LNURL.
+/// The request is uppercased so the QR code can use the denser alphanumeric mode.
+pub fn build_lightning(payment: &LightningPayment) -> Result<String, String> {
+    let request = strip_scheme(payment.request.trim());
+    if request.is_empty() {
+        return Err("Lightning invoice or LNURL is required".to_string());
+    }
+
+    parse_lightning(request)?;
+    Ok(format!("LIGHTNING:{}", request.to_uppercase()))
+}
+
+/// Parse a BOLT11 invoice or LNURL, with or without the `lightning:` scheme
+pub fn parse_lightning(content: &str) -> Result<LightningPayment, String> {
+    let request = strip_scheme(content.trim());
+    let lower = request.to_lowercase();
+
+    let mut payment = LightningPayment {
+        request: request.to_string(),
+        ..Default::default()
+    };
+
+    if lower.starts_with("lnurl1") || lower.contains("://") {
+        payment.lnurl = Some(decode_lnurl(request)?);
+    } else {
+        payment.invoice = Some(parse_bolt11(request)?);
+    }
+
+    Ok(payment)
+}
+
+/// Decode and validate a BOLT11 invoice
+pub fn parse_bolt11(invoice: &str) -> Result<Bolt11Invoice, String> {
+    let decoded = bech32::decode(strip_scheme(invoice.trim()))
+        .map_err(|e| format!("Invalid Lightning invoice: {}", e))?;
+    if decoded.variant != Variant::Bech32 {
+        return Err("Invalid Lightning invoice: wrong checksum variant".to_string());
+    }
+
+    let hrp = decoded
+        .hrp
+        .strip_prefix("ln")
+        .ok_or("Invalid Lightning invoice: missing 'ln' prefix")?;
+    let (network, amount) = [
+        ("bcrt", "regtest"),
+        ("bc", "mainnet"),
+        ("tbs", "signet"),
+        ("tb", "testnet"),
+        ("sb", "simnet"),
+    ]
+    .into_iter()
+    .find_map(|(prefix, network)| {
+        hrp.strip_prefix(prefix)
+            .filter(|rest| rest.is_empty() || rest.starts_with(|c: char| c.is_ascii_digit()))
+            .map(|rest| (network, rest))
+    })
+    .ok_or_else(|| format!("Unknown Lightning network prefix 'ln{}'", hrp))?;
+
+    let amount_msat = if amount.is_empty() {
+        None
+    } else {
+        Some(parse_invoice_amount(amount)?)
+    };
+
+    let data = &decoded.data;
+    if data.len() < 7 + SIGNATURE_WORDS {
+        return Err("Invalid Lightning invoice: too short".to_string());
+    }
+    let timestamp = words_to_int(&data[..7]);
+    let mut fields = &data[7..data.len() - SIGNATURE_WORDS];
+
+    let mut invoice = Bolt11Invoice {
+        network: network.to_string(),
+        amount: amount_msat.map(|msat| format_units(msat as u128, MSAT_DECIMALS)),
+        amount_msat,
+        timestamp,
+        expiry: DEFAULT_EXPIRY,
+        ..Default::default()
+    };
+
+    while !fields.is_empty() {
+        if fields.len() < 3 {
+            return Err("Invalid Lightning invoice: truncated field".to_string());
+        }
+        let len = (fields[1] as usize) << 5 | fields[2] as usize;
+        let value = fields
+            .get(3..3 + len)
+            .ok_or("Invalid Lightning invoice: truncated field")?;
+
+        match fields[0] {
+            // p: payment hash
+            1 if len == 52 => invoice.payment_hash = words_to_hex(value)?,
+            // d: description
+            13 => {
+                let bytes = bech32::convert_bits(value, 5, 8, false)?;
+                invoice.description = Some(
+                    String::from_utf8(bytes).map_err(|_| "Invoice description is not UTF-8")?,
+                );
+            }
+            // h: description hash
+            23 if len == 52 => invoice.description_hash = Some(words_to_hex(value)?),
+            // x: expiry
+            6 => invoice.expiry = words_to_int(value),
+            // n: payee public key
+            19 if len == 53 => invoice.payee = Some(words_to_hex(value)?),
+            // c: min_final_cltv_expiry
+            24 => invoice.min_final_cltv_expiry = Some(words_to_int(value)),
+            _ => {}
+        }
+
+        fields = &fields[3 + len..];
+    }
+
+    if invoice.payment_hash.is_empty() {
+        return Err("Invalid Lightning invoice: missing payment hash".to_string());
+    }
+    if invoice.description.is_none() && invoice.description_hash.is_none() {
+        return Err("Invalid Lightning invoice: missing description".to_string());
+    }
+
+    invoice.created_at = format_timestamp(timestamp)?;
+    invoice.expires_at = format_timestamp(timestamp.saturating_add(invoice.expiry))?;
+
+    Ok(invoice)
+}
+
+/// Decode an LNURL (bech32 or LUD-17 scheme) to its HTTPS endpoint
+pub fn decode_lnurl(lnurl: &str) -> Result<String, String> {
+    let lnurl = strip_scheme(lnurl.trim());
+
+    let url = if let Some((scheme, rest)) = lnurl.split_once("://") {
+        match scheme.to_lowercase().as_str() {
+            "lnurlc" | "lnurlw" | "lnurlp" | "keyauth" => {
+                let host = rest.split(['/', '?', ':']).next().unwrap_or("");
+                let http = if host.ends_with(".onion") { "http" } else { "https" };
+                format!("{}://{}", http, rest)
+            }
+            _ => return Err(format!("Unknown LNURL scheme '{}'", scheme)),
+        }
+    } else {
+        let decoded = bech32::decode(lnurl).map_err(|e| format!("Invalid LNURL: {}", e))?;
+        if decoded.hrp != "lnurl" {
+            return Err("Invalid LNURL: wrong prefix".to_string());
+        }
+        let bytes = bech32::convert_bits(&decoded.data, 5, 8, false)?;
+        String::from_utf8(bytes).map_err(|_| "Invalid LNURL: not UTF-8")?
+    };
+
+    let lower = url.to_lowercase();
+    let onion = lower
+        .strip_prefix("http://")
+        .and_then(|rest| rest.split(['/', '?', ':']).next())
+        .is_some_and(|host| host.ends_with(".onion"));
+    if !lower.starts_with("https://") && !onion {
+        return Err("LNURL must point to an https:// or .onion URL".to_string());
+    }
+
+    Ok(url)
+}
+
+/// Encode an HTTPS URL as a bech32 LNURL
+pub fn encode_lnurl(url: &str) -> Result<String, String> {
+    let data = bech32::convert_bits(url.as_bytes(), 8, 5, true)?;
+    let lnurl = bech32::encode("lnurl", &data, Variant::Bech32).to_uppercase();
+    decode_lnurl(&lnurl)?;
+    Ok(lnurl)
+}
+
+fn strip_scheme(content: &str) -> &str {
+    content
+        .get(..10)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("lightning:"))
+        .map_or(content, |_| &content[10..])
+}
+
+/// Convert an invoice amount such as "2500u" to millisatoshis
+fn parse_invoice_amount(amount: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid Lightning invoice amount '{}'", amount);
+
+    let (digits, multiplier) = match amount.chars().last() {
+        Some(c @ ('m' | 'u' | 'n' | 'p')) => (&amount[..amount.len() - 1], Some(c)),
+        _ => (amount, None),
+    };
+    if digits.is_empty() || digits.starts_with('0') || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let value: u64 = digits.parse().map_err(|_| invalid())?;
+
+    let msat = match multiplier {
+        None => value.checked_mul(100_000_000_000),
+        Some('m') => value.checked_mul(100_000_000),
+        Some('u') => value.checked_mul(100_000),
+        Some('n') => value.checked_mul(100),
+        // Pico-bitcoin amounts must land on a whole millisatoshi
+        _ if value.is_multiple_of(10) => Some(value / 10),
+        _ => None,
+    };
+
+    msat.ok_or_else(invalid)
+}
+
+fn words_to_int(words: &[u8]) -> u64 {
+    words.iter().fold(0u64, |acc, &w| (acc << 5) | w as u64)
+}
+
+fn words_to_hex(words: &[u8]) -> Result<String, String> {
+    let bytes = bech32::convert_bits(words, 5, 8, false)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn format_timestamp(seconds: u64) -> Result<String, String> {
+    i64::try_from(seconds)
+        .ok()
+        .and_then(|s| chrono::DateTime::from_timestamp(s, 0))
+        .map(|dt| dt.to_rfc3339())
+        .ok_or_else(|| "Invalid Lightning invoice timestamp".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "1 cup coffee" example from the BOLT11 specification
+    const COFFEE_INVOICE: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+
+    // LNURL example from LUD-01
+    const LNURL: &str = "LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS";
+
+    #[test]
+    fn test_parse_bolt11() {
+        let invoice = parse_bolt11(COFFEE_INVOICE).unwrap();
+
+        assert_eq!(invoice.network, "mainnet");
+        assert_eq!(invoice.amount, Some("0.0025".to_string()));
+        assert_eq!(invoice.amount_msat, Some(250_000_000));
+        assert_eq!(invoice.timestamp, 1496314658);
+        assert_eq!(invoice.created_at, "2017-06-01T10:57:38+00:00");
+        assert_eq!(invoice.expiry, 60);
+        assert_eq!(invoice.expires_at, "2017-06-01T10:58:38+00:00");
+        assert_eq!(
+            invoice.payment_hash,
+            "0001020304050607080900010203040506070809000102030405060708090102"
+        );
+        assert_eq!(invoice.description, Some("1 cup coffee".to_string()));
+    }
+
+    #[test]
+    fn test_parse_bolt11_rejects_invalid() {
+        // Flipped character breaks the checksum
+        let corrupted = COFFEE_INVOICE.replacen("2500u", "2600u", 1);
+        assert!(parse_bolt11(&corrupted).is_err());
+
+        assert!(parse_bolt11("lnbc1qqqqqqqq").is_err());
+        assert!(parse_bolt11("bitcoin").is_err());
+    }
+
+    #[test]
+    fn test_parse_invoice_amount() {
+        assert_eq!(parse_invoice_amount("1").unwrap(), 100_000_000_000);
+        assert_eq!(parse_invoice_amount("20m").unwrap(), 2_000_000_000);
+        assert_eq!(parse_invoice_amount("2500u").unwrap(), 250_000_000);
+        assert_eq!(parse_invoice_amount("10n").unwrap(), 1_000);
+        assert_eq!(parse_invoice_amount("10p").unwrap(), 1);
+
+        assert!(parse_invoice_amount("1p").is_err());
+        assert!(parse_invoice_amount("010u").is_err());
+        assert!(parse_invoice_amount("u").is_err());
+    }
+
+    #[test]
+    fn test_decode_lnurl() {
+        let url = "https://service.com/api?q=3fc3645b439ce8e7f2553a69e5267081d96dcd340693afabe04be7b0ccd178df";
+        assert_eq!(decode_lnurl(LNURL).unwrap(), url);
+        assert_eq!(encode_lnurl(url).unwrap(), LNURL);
+
+        assert_eq!(
+            decode_lnurl("lnurlp://service.com/pay/alice").unwrap(),
+            "https://service.com/pay/alice"
+        );
+        assert!(encode_lnurl("http://service.com").is_err());
+        assert!(decode_lnurl("ftp://service.com").is_err());
+    }
+
+    #[test]
+    fn test_build_and_parse_lightning() {
+        let built = build_lightning(&LightningPayment {
+            request: COFFEE_INVOICE.to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(built.starts_with("LIGHTNING:LNBC2500U1"));
+
+        let parsed = parse_lightning(&built).unwrap();
+        assert_eq!(parsed.invoice.unwrap().amount_msat, Some(250_000_000));
+
+        let parsed = parse_lightning(&format!("lightning:{}", LNURL)).unwrap();
+        assert!(parsed.lnurl.unwrap().starts_with("https://service.com/"));
+
+        assert!(build_lightning(&LightningPayment::default()).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use super::{query_pairs, split_fields, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+/// An e-mail parsed from a `mailto:` link or a MATMSG code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailMessage {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+/// A text message parsed from `sms:`, `SMSTO:` or `MMSTO:` content
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmsMessage {
+    pub number: String,
+    pub body: Option<String>,
+}
+
+/// Parse a `mailto:` link or a `MATMSG:TO:...;SUB:...;BODY:...;;` code
+pub fn parse_email(content: &str) -> Result<EmailMessage, String> {
+    let content = content.trim();
+    let mut email = EmailMessage::default();
+
+    if let Some(body) = strip_prefix_ci(content, "MATMSG:") {
+        for (key, value) in split_fields(body) {
+            let value = value.trim().to_string();
+            match key.as_str() {
+                "TO" => email.to.extend(split_addresses(&value)),
+                "CC" => email.cc.extend(split_addresses(&value)),
+                "SUB" => email.subject = Some(value).filter(|v| !v.is_empty()),
+                "BODY" => email.body = Some(value).filter(|v| !v.is_empty()),
+                _ => {}
+            }
+        }
+    } else if let Some(rest) = strip_prefix_ci(content, "mailto:") {
+        let (to, query) = rest.split_once('?').unwrap_or((rest, ""));
+        email.to = split_addresses(&urlencoding::decode(to).map_err(|e| e.to_string())?);
+
+        for (key, value) in query_pairs(query) {
+            match key.as_str() {
+                "to" => email.to.extend(split_addresses(&value)),
+                "cc" => email.cc.extend(split_addresses(&value)),
+                "subject" => email.subject = Some(value).filter(|v| !v.is_empty()),
+                "body" => email.body = Some(value).filter(|v| !v.is_empty()),
+                _ => {}
+            }
+        }
+    } else {
+        return Err("Not a mailto: link or MATMSG code".to_string());
+    }
+
+    if email.to.is_empty() {
+        return Err("E-mail has no recipient".to_string());
+    }
+    Ok(email)
+}
+
+/// Parse `sms:+1555?body=...`, `SMSTO:+1555:body` or `MMSTO:+1555:body`.
+/// In the colon form everything after the number is the message, colons included.
+pub fn parse_sms(content: &str) -> Result<SmsMessage, String> {
+    let content = content.trim();
+
+    let mut sms = if let Some(rest) =
+        strip_prefix_ci(content, "SMSTO:").or_else(|| strip_prefix_ci(content, "MMSTO:"))
+    {
+        let (number, body) = rest.split_once(':').unwrap_or((rest, ""));
+        SmsMessage {
+            number: number.trim().to_string(),
+            body: Some(body.to_string()).filter(|b| !b.is_empty()),
+        }
+    } else if let Some(rest) = strip_prefix_ci(content, "sms:") {
+        let (target, query) = rest.split_once('?').unwrap_or((rest, ""));
+        // Some generators use "sms:number:body" as well
+        let (number, inline_body) = target.split_once(':').unwrap_or((target, ""));
+        let body = query_pairs(query)
+            .into_iter()
+            .find(|(key, _)| key == "body")
+            .map(|(_, value)| value)
+            .or_else(|| Some(inline_body.to_string()));
+
+        SmsMessage {
+            number: number.trim().to_string(),
+            body: body.filter(|b| !b.is_empty()),
+        }
+    } else {
+        return Err("Not an sms: or SMSTO: code".to_string());
+    };
+
+    sms.number = urlencoding::decode(&sms.number)
+        .map(|n| n.into_owned())
+        .unwrap_or(sms.number);
+    if sms.number.is_empty() {
+        return Err("Text message has no phone number".to_string());
+    }
+    Ok(sms)
+}
+
+fn split_addresses(value: &str) -> Vec<String> {
+    value
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_email_matmsg() {
+        let email = parse_email("MATMSG:TO:alice@example.com;SUB:Meeting\\: today;BODY:See you at 3;;").unwrap();
+        assert_eq!(email.to, vec!["alice@example.com"]);
+        assert_eq!(email.subject, Some("Meeting: today".to_string()));
+        assert_eq!(email.body, Some("See you at 3".to_string()));
+    }
+
+    #[test]
+    fn test_parse_email_mailto() {
+        let email =
+            parse_email("mailto:alice@example.com,bob@example.com?cc=carol@example.com&subject=Hi%20there&body=Hello").unwrap();
+        assert_eq!(email.to, vec!["alice@example.com", "bob@example.com"]);
+        assert_eq!(email.cc, vec!["carol@example.com"]);
+        assert_eq!(email.subject, Some("Hi there".to_string()));
+        assert_eq!(email.body, Some("Hello".to_string()));
+
+        assert!(parse_email("mailto:?subject=nobody").is_err());
+        assert!(parse_email("https://example.com").is_err());
+    }
+
+    #[test]
+    fn test_parse_sms() {
+        let sms = parse_sms("SMSTO:+15551234567:Reply YES: to confirm").unwrap();
+        assert_eq!(sms.number, "+15551234567");
+        assert_eq!(sms.body, Some("Reply YES: to confirm".to_string()));
+
+        let sms = parse_sms("sms:+15551234567?body=Hello%20world").unwrap();
+        assert_eq!(sms.body, Some("Hello world".to_string()));
+
+        let sms = parse_sms("smsto:12345").unwrap();
+        assert_eq!(sms.number, "12345");
+        assert_eq!(sms.body, None);
+
+        assert!(parse_sms("SMSTO::just a body").is_err());
+    }
+}
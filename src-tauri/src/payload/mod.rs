@@ -0,0 +1,519 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub mod app_link;
+pub mod bech32;
+pub mod bitcoin;
+pub mod contact;
+pub mod emv;
+pub mod epc;
+pub mod ethereum;
+pub mod gs1;
+pub mod iban;
+pub mod lightning;
+pub mod message;
+pub mod otp;
+pub mod pix;
+pub mod swiss;
+pub mod upi;
+pub mod wifi;
+
+pub use app_link::*;
+pub use bitcoin::*;
+pub use contact::*;
+pub use emv::*;
+pub use epc::*;
+pub use ethereum::*;
+pub use gs1::*;
+pub use lightning::*;
+pub use message::*;
+pub use otp::*;
+pub use pix::*;
+pub use swiss::*;
+pub use upi::*;
+pub use wifi::*;
+
+/// Structured fields parsed out of decoded QR content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ParsedPayload {
+    Epc(EpcPayment),
+    SwissQrBill(Box<SwissQrBill>),
+    Upi(UpiPayment),
+    Pix(PixPayment),
+    Emvco(Box<EmvPayment>),
+    Bitcoin(BitcoinUri),
+    Lightning(Box<LightningPayment>),
+    Ethereum(EthereumUri),
+    Otp(OtpAuth),
+    Contact(Box<Contact>),
+    Email(EmailMessage),
+    Sms(SmsMessage),
+    Wifi(WifiNetwork),
+    AppLink(AppLink),
+    Gs1(Gs1Data),
+}
+
+impl ParsedPayload {
+    /// Copy with any secrets (such as OTP keys) masked for display
+    pub fn masked(&self) -> Self {
+        match self {
+            ParsedPayload::Otp(otp) => ParsedPayload::Otp(otp.masked()),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Content produced by a payload builder, along with any encoding requirements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltPayload {
+    pub qr_type: String,
+    pub content: String,
+    pub error_correction: Option<String>,
+}
+
+/// Error correction level mandated by a type's standard, if any
+pub fn required_error_correction(qr_type: &str) -> Option<&'static str> {
+    match qr_type {
+        "epc" => Some(EPC_ERROR_CORRECTION),
+        "swiss-qr-bill" => Some(SWISS_ERROR_CORRECTION),
+        _ => None,
+    }
+}
+
+/// Build QR content for a structured type from its form fields
+pub fn build_payload(
+    qr_type: &str,
+    fields: serde_json::Value,
+    error_correction: Option<&str>,
+) -> Result<BuiltPayload, String> {
+    let required = required_error_correction(qr_type);
+
+    if let (Some(requested), Some(required)) = (error_correction, required) {
+        if !requested.eq_ignore_ascii_case(required) {
+            return Err(format!(
+                "{} codes require error correction level {}",
+                qr_type.to_uppercase(),
+                required
+            ));
+        }
+    }
+
+    let content = match qr_type {
+        "epc" => build_epc(&from_fields(fields)?)?,
+        "swiss-qr-bill" => build_swiss_qr_bill(&from_fields(fields)?)?,
+        "upi" => build_upi(&from_fields(fields)?)?,
+        "pix" => build_pix(&from_fields(fields)?)?,
+        "emvco" => build_emv(&from_fields(fields)?)?,
+        "bitcoin" => build_bitcoin_uri(&from_fields(fields)?)?,
+        "lightning" => build_lightning(&from_fields(fields)?)?,
+        "ethereum" => build_ethereum_uri(&from_fields(fields)?)?,
+        "otp" => build_otp(&from_fields(fields)?)?,
+        "gs1" => build_gs1(&from_fields(fields)?)?,
+        _ => return Err(format!("No payload builder for type '{}'", qr_type)),
+    };
+
+    Ok(BuiltPayload {
+        qr_type: qr_type.to_string(),
+        content,
+        error_correction: required.map(str::to_string),
+    })
+}
+
+/// Parse decoded content of a structured type into its fields
+pub fn parse_payload(qr_type: &str, content: &str) -> Option<ParsedPayload> {
+    match qr_type {
+        "epc" => parse_epc(content).ok().map(ParsedPayload::Epc),
+        "swiss-qr-bill" => parse_swiss_qr_bill(content)
+            .ok()
+            .map(|bill| ParsedPayload::SwissQrBill(Box::new(bill))),
+        "upi" => parse_upi(content).ok().map(ParsedPayload::Upi),
+        "pix" => parse_pix(content).ok().map(ParsedPayload::Pix),
+        "emvco" => parse_emv(content)
+            .ok()
+            .map(|payment| ParsedPayload::Emvco(Box::new(payment))),
+        "bitcoin" => parse_bitcoin_uri(content).ok().map(ParsedPayload::Bitcoin),
+        "lightning" => parse_lightning(content)
+            .ok()
+            .map(|payment| ParsedPayload::Lightning(Box::new(payment))),
+        "ethereum" => parse_ethereum_uri(content).ok().map(ParsedPayload::Ethereum),
+        "otp" => parse_otp(content).ok().map(ParsedPayload::Otp),
+        "vcard" => parse_contact(content)
+            .ok()
+            .map(|contact| ParsedPayload::Contact(Box::new(contact))),
+        "email" => parse_email(content).ok().map(ParsedPayload::Email),
+        "sms" => parse_sms(content).ok().map(ParsedPayload::Sms),
+        "wifi" => parse_wifi(content).ok().map(ParsedPayload::Wifi),
+        "app-link" => parse_app_link(content).ok().map(ParsedPayload::AppLink),
+        "gs1" => parse_gs1(content).ok().map(ParsedPayload::Gs1),
+        _ => None,
+    }
+}
+
+/// Mask secrets in raw content of a given type before it is displayed
+pub fn mask_content(qr_type: &str, content: &str) -> String {
+    match qr_type {
+        "otp" => mask_otp_uri(content),
+        _ => content.to_string(),
+    }
+}
+
+/// Largest amount accepted by the payment standards, in cents (999,999,999.99)
+const MAX_AMOUNT_CENTS: u64 = 99_999_999_999;
+
+/// Parse a decimal amount with at most two fraction digits into cents
+fn parse_amount(amount: &str) -> Result<u64, String> {
+    let amount = amount.trim();
+    let invalid = || format!("Invalid amount '{}'", amount);
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty()
+        || fraction.len() > 2
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let whole: u64 = whole.parse().map_err(|_| invalid())?;
+    let fraction: u64 = format!("{:0<2}", fraction).parse().map_err(|_| invalid())?;
+    let cents = whole
+        .checked_mul(100)
+        .and_then(|c| c.checked_add(fraction))
+        .ok_or_else(invalid)?;
+
+    if cents == 0 || cents > MAX_AMOUNT_CENTS {
+        return Err("Amount must be between 0.01 and 999999999.99".to_string());
+    }
+
+    Ok(cents)
+}
+
+fn format_cents(cents: u64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Parse a decimal amount into integer base units (e.g. satoshis or wei)
+fn parse_units(amount: &str, decimals: u32) -> Result<u128, String> {
+    let amount = amount.trim();
+    let invalid = || format!("Invalid amount '{}'", amount);
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty()
+        || fraction.len() > decimals as usize
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    format!("{}{:0<width$}", whole, fraction, width = decimals as usize)
+        .parse::<u128>()
+        .map_err(|_| invalid())
+}
+
+/// Format integer base units as a decimal amount without trailing zeros
+fn format_units(value: u128, decimals: u32) -> String {
+    let scale = 10u128.pow(decimals);
+    let fraction = format!("{:0width$}", value % scale, width = decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        (value / scale).to_string()
+    } else {
+        format!("{}.{}", value / scale, fraction)
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn check_length(field: &str, value: &str, max: usize) -> Result<(), String> {
+    let len = value.chars().count();
+    if len > max {
+        Err(format!("{} is {} characters, maximum is {}", field, len, max))
+    } else {
+        Ok(())
+    }
+}
+
+/// Split a `KEY:value;KEY:value;;` body as used by MECARD, MATMSG and WIFI codes.
+/// Keys are uppercased; backslash escapes are resolved in both keys and values.
+fn split_fields(body: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let (mut key, mut value) = (String::new(), String::new());
+    let mut in_value = false;
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        let target = if in_value { &mut value } else { &mut key };
+        match c {
+            '\\' => target.extend(chars.next()),
+            ':' if !in_value => in_value = true,
+            ';' => {
+                if !key.trim().is_empty() {
+                    fields.push((key.trim().to_uppercase(), std::mem::take(&mut value)));
+                }
+                key.clear();
+                value.clear();
+                in_value = false;
+            }
+            _ => target.push(c),
+        }
+    }
+    if !key.trim().is_empty() {
+        fields.push((key.trim().to_uppercase(), value));
+    }
+
+    fields
+}
+
+/// Strip a case-insensitive prefix such as a URI scheme
+fn strip_prefix_ci<'a>(content: &'a str, prefix: &str) -> Option<&'a str> {
+    content
+        .get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &content[prefix.len()..])
+}
+
+/// Split a URI query string into decoded key/value pairs, with lowercase keys
+fn query_pairs(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = urlencoding::decode(&value.replace('+', " "))
+                .map(|v| v.into_owned())
+                .unwrap_or_else(|_| value.to_string());
+            (key.to_lowercase(), value)
+        })
+        .collect()
+}
+
+fn from_fields<T: DeserializeOwned>(fields: serde_json::Value) -> Result<T, String> {
+    serde_json::from_value(fields).map_err(|e| format!("Invalid fields: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_payload_epc() {
+        let fields = json!({
+            "name": "Max Mustermann",
+            "iban": "DE89370400440532013000",
+            "amount": "10"
+        });
+
+        let built = build_payload("epc", fields, None).unwrap();
+        assert_eq!(built.qr_type, "epc");
+        assert_eq!(built.error_correction, Some("M".to_string()));
+        assert!(built.content.starts_with("BCD\n002\n1\nSCT\n"));
+        assert!(built.content.ends_with("\nEUR10.00"));
+    }
+
+    #[test]
+    fn test_build_payload_swiss_qr_bill() {
+        let fields = json!({
+            "iban": "CH9300762011623852957",
+            "creditor": {
+                "name": "Robert Schneider AG",
+                "postalCode": "2501",
+                "town": "Biel",
+                "country": "CH"
+            },
+            "currency": "CHF"
+        });
+
+        let built = build_payload("swiss-qr-bill", fields, None).unwrap();
+        assert_eq!(built.error_correction, Some("M".to_string()));
+        assert!(built.content.starts_with("SPC\n0200\n1\nCH9300762011623852957\nS\n"));
+        assert!(built.content.ends_with("\nNON\n\n\nEPD"));
+
+        match parse_payload("swiss-qr-bill", &built.content) {
+            Some(ParsedPayload::SwissQrBill(bill)) => assert_eq!(bill.creditor.town, "Biel"),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_payload_enforces_error_correction() {
+        let fields = json!({ "name": "Max", "iban": "DE89370400440532013000" });
+
+        assert!(build_payload("epc", fields.clone(), Some("m")).is_ok());
+        let err = build_payload("epc", fields, Some("H")).unwrap_err();
+        assert!(err.contains("level M"));
+    }
+
+    #[test]
+    fn test_build_payload_invalid_fields() {
+        let err = build_payload("epc", json!({ "iban": 5 }), None).unwrap_err();
+        assert!(err.starts_with("Invalid fields"));
+    }
+
+    #[test]
+    fn test_build_payload_merchant_types() {
+        let upi = build_payload(
+            "upi",
+            json!({ "payeeAddress": "shop@ybl", "payeeName": "Shop", "amount": "5" }),
+            None,
+        )
+        .unwrap();
+        assert_eq!(upi.content, "upi://pay?pa=shop%40ybl&pn=Shop&am=5.00&cu=INR");
+        assert_eq!(upi.error_correction, None);
+
+        let pix = build_payload(
+            "pix",
+            json!({
+                "key": "fulano@example.com",
+                "merchantName": "Fulano",
+                "merchantCity": "BRASILIA"
+            }),
+            None,
+        )
+        .unwrap();
+        match parse_payload("pix", &pix.content) {
+            Some(ParsedPayload::Pix(parsed)) => assert_eq!(parsed.key, "fulano@example.com"),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+
+        assert!(parse_payload("emvco", &pix.content).is_some());
+        assert!(parse_payload("emvco", &pix.content.replace("BRASILIA", "BRASILIO")).is_none());
+    }
+
+    #[test]
+    fn test_build_payload_crypto_types() {
+        let bitcoin = build_payload(
+            "bitcoin",
+            json!({ "address": "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", "amount": "1.5" }),
+            None,
+        )
+        .unwrap();
+        assert_eq!(bitcoin.content, "bitcoin:bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4?amount=1.5");
+
+        match parse_payload("bitcoin", &bitcoin.content) {
+            Some(ParsedPayload::Bitcoin(uri)) => assert_eq!(uri.amount_sats, Some(150_000_000)),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+
+        let ethereum = build_payload(
+            "ethereum",
+            json!({ "address": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", "amount": "1" }),
+            None,
+        )
+        .unwrap();
+        let value = serde_json::to_value(parse_payload("ethereum", &ethereum.content).unwrap()).unwrap();
+        assert_eq!(value["kind"], "ethereum");
+        assert_eq!(value["amountWei"], "1000000000000000000");
+
+        assert!(build_payload("lightning", json!({ "request": "lnbc1invalid" }), None).is_err());
+        assert!(parse_payload("bitcoin", "bitcoin:notanaddress").is_none());
+    }
+
+    #[test]
+    fn test_parse_and_format_units() {
+        assert_eq!(parse_units("1.5", 8).unwrap(), 150_000_000);
+        assert_eq!(parse_units("0.000000000000000001", 18).unwrap(), 1);
+        assert!(parse_units("1.123", 2).is_err());
+        assert!(parse_units(".5", 2).is_err());
+
+        assert_eq!(format_units(150_000_000, 8), "1.5");
+        assert_eq!(format_units(100_000_000, 8), "1");
+        assert_eq!(format_units(1, 18), "0.000000000000000001");
+    }
+
+    #[test]
+    fn test_otp_payload_masking() {
+        let built = build_payload(
+            "otp",
+            json!({ "issuer": "ACME", "account": "alice", "secret": "JBSWY3DPEHPK3PXP" }),
+            None,
+        )
+        .unwrap();
+        assert_eq!(built.content, "otpauth://totp/ACME:alice?secret=JBSWY3DPEHPK3PXP&issuer=ACME");
+
+        let masked = parse_payload("otp", &built.content).unwrap().masked();
+        let value = serde_json::to_value(&masked).unwrap();
+        assert_eq!(value["kind"], "otp");
+        assert_eq!(value["secret"], "****3PXP");
+        assert_eq!(value["secretMasked"], true);
+
+        assert_eq!(
+            mask_content("otp", &built.content),
+            "otpauth://totp/ACME:alice?secret=****3PXP&issuer=ACME"
+        );
+        assert_eq!(mask_content("text", "secret=JBSWY3DPEHPK3PXP"), "secret=JBSWY3DPEHPK3PXP");
+    }
+
+    #[test]
+    fn test_parse_payload_legacy_formats() {
+        match parse_payload("vcard", "MECARD:N:Doe,John;TEL:+15551234567;;") {
+            Some(ParsedPayload::Contact(contact)) => assert_eq!(contact.name, "John Doe"),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+
+        let value = serde_json::to_value(parse_payload("app-link", "market://details?id=com.example").unwrap()).unwrap();
+        assert_eq!(value["kind"], "appLink");
+        assert_eq!(value["appId"], "com.example");
+
+        assert!(parse_payload("email", "MATMSG:TO:a@example.com;;").is_some());
+        assert!(parse_payload("sms", "SMSTO:+1555:hi").is_some());
+        assert!(parse_payload("wifi", "WIFI:S:Net;T:WPA;P:pw;;").is_some());
+    }
+
+    #[test]
+    fn test_build_payload_gs1() {
+        let built = build_payload("gs1", json!({ "gtin": "9506000134352", "batch": "ABC123" }), None).unwrap();
+        assert_eq!(built.content, "https://id.gs1.org/01/09506000134352/10/ABC123");
+
+        let value = serde_json::to_value(parse_payload("gs1", &built.content).unwrap()).unwrap();
+        assert_eq!(value["kind"], "gs1");
+        assert_eq!(value["elements"][1]["title"], "BATCH/LOT");
+    }
+
+    #[test]
+    fn test_split_fields() {
+        assert_eq!(
+            split_fields("N:Doe,John;TEL:+1555;NOTE:a\\;b\\:c;;"),
+            vec![
+                ("N".to_string(), "Doe,John".to_string()),
+                ("TEL".to_string(), "+1555".to_string()),
+                ("NOTE".to_string(), "a;b:c".to_string()),
+            ]
+        );
+        assert_eq!(split_fields("s:net"), vec![("S".to_string(), "net".to_string())]);
+        assert!(split_fields(";;").is_empty());
+    }
+
+    #[test]
+    fn test_build_payload_unknown_type() {
+        assert!(build_payload("text", json!({}), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_payload() {
+        let content = "BCD\n002\n1\nSCT\n\nMax\nDE89370400440532013000";
+        match parse_payload("epc", content) {
+            Some(ParsedPayload::Epc(payment)) => assert_eq!(payment.name, "Max"),
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+
+        assert!(parse_payload("epc", "BCD\nbroken").is_none());
+        assert!(parse_payload("url", "https://example.com").is_none());
+    }
+
+    #[test]
+    fn test_parsed_payload_serialization() {
+        let content = "BCD\n002\n1\nSCT\n\nMax\nDE89370400440532013000";
+        let parsed = parse_payload("epc", content).unwrap();
+        let value = serde_json::to_value(&parsed).unwrap();
+
+        assert_eq!(value["kind"], "epc");
+        assert_eq!(value["iban"], "DE89370400440532013000");
+    }
+}
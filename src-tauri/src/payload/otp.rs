@@ -0,0 +1,329 @@
+use super::{non_empty, query_pairs, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Shortest secret accepted, in base32 characters (80 bits)
+const MIN_SECRET_CHARS: usize = 16;
+
+/// Number of trailing secret characters left visible when masking
+const VISIBLE_SECRET_CHARS: usize = 4;
+
+/// An `otpauth://` authenticator enrolment code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtpAuth {
+    #[serde(default = "default_otp_type")]
+    pub otp_type: String, // "totp" | "hotp"
+    pub issuer: Option<String>,
+    pub account: String,
+    pub secret: String, // base32
+    pub algorithm: Option<String>, // SHA1 (default), SHA256 or SHA512
+    pub digits: Option<u32>,
+    pub period: Option<u32>, // TOTP only, seconds
+    pub counter: Option<u64>, // HOTP only
+    #[serde(default)]
+    pub secret_masked: bool,
+}
+
+fn default_otp_type() -> String {
+    "totp".to_string()
+}
+
+impl OtpAuth {
+    /// Copy with the secret replaced by a masked form
+    pub fn masked(&self) -> Self {
+        Self {
+            secret: mask_secret(&self.secret),
+            secret_masked: true,
+            ..self.clone()
+        }
+    }
+}
+
+/// Build an `otpauth://totp` or `otpauth://hotp` URI. Parameters at their
+/// default values are left out to keep the code small.
+pub fn build_otp(otp: &OtpAuth) -> Result<String, String> {
+    let otp_type = otp.otp_type.trim().to_lowercase();
+    if otp_type != "totp" && otp_type != "hotp" {
+        return Err(format!("Unknown OTP type '{}', expected totp or hotp", otp.otp_type));
+    }
+
+    let account = otp.account.trim();
+    if account.is_empty() {
+        return Err("Account name is required".to_string());
+    }
+    let issuer = non_empty(&otp.issuer);
+    if issuer.is_some_and(|i| i.contains(':')) || account.contains(':') {
+        return Err("Issuer and account name cannot contain ':'".to_string());
+    }
+
+    let secret = normalize_secret(&otp.secret)?;
+
+    let label = match issuer {
+        Some(issuer) => format!("{}:{}", urlencoding::encode(issuer), urlencoding::encode(account)),
+        None => urlencoding::encode(account).into_owned(),
+    };
+    let mut out = format!("otpauth://{}/{}?secret={}", otp_type, label, secret);
+
+    if let Some(issuer) = issuer {
+        out.push_str(&format!("&issuer={}", urlencoding::encode(issuer)));
+    }
+    if let Some(algorithm) = non_empty(&otp.algorithm) {
+        let algorithm = normalize_algorithm(algorithm)?;
+        if algorithm != "SHA1" {
+            out.push_str(&format!("&algorithm={}", algorithm));
+        }
+    }
+    if let Some(digits) = otp.digits {
+        if !(6..=8).contains(&digits) {
+            return Err("Digits must be 6, 7 or 8".to_string());
+        }
+        if digits != 6 {
+            out.push_str(&format!("&digits={}", digits));
+        }
+    }
+
+    if otp_type == "totp" {
+        if let Some(period) = otp.period {
+            if !(1..=3600).contains(&period) {
+                return Err("Period must be between 1 and 3600 seconds".to_string());
+            }
+            if period != 30 {
+                out.push_str(&format!("&period={}", period));
+            }
+        }
+    } else {
+        // Counter is required for HOTP
+        out.push_str(&format!("&counter={}", otp.counter.unwrap_or(0)));
+    }
+
+    Ok(out)
+}
+
+/// Parse an `otpauth://` URI into its fields, filling in defaults
+pub fn parse_otp(content: &str) -> Result<OtpAuth, String> {
+    let content = content.trim();
+    let rest = strip_prefix_ci(content, "otpauth://").ok_or("Not an otpauth:// URI")?;
+
+    let (otp_type, rest) = rest.split_once('/').ok_or("otpauth URI has no label")?;
+    let otp_type = otp_type.to_lowercase();
+    if otp_type != "totp" && otp_type != "hotp" {
+        return Err(format!("Unknown OTP type '{}'", otp_type));
+    }
+
+    let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let label = decode(label);
+    let (label_issuer, account) = match label.split_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+        None => (None, label.trim().to_string()),
+    };
+
+    let mut otp = OtpAuth {
+        otp_type,
+        issuer: label_issuer,
+        account,
+        ..Default::default()
+    };
+
+    for (key, value) in query_pairs(query) {
+        match key.as_str() {
+            "secret" => otp.secret = normalize_secret(&value)?,
+            // The issuer parameter takes precedence over the label prefix
+            "issuer" if !value.is_empty() => otp.issuer = Some(value),
+            "algorithm" => otp.algorithm = Some(normalize_algorithm(&value)?.to_string()),
+            "digits" => otp.digits = Some(value.parse().map_err(|_| "Invalid OTP digits")?),
+            "period" => otp.period = Some(value.parse().map_err(|_| "Invalid OTP period")?),
+            "counter" => otp.counter = Some(value.parse().map_err(|_| "Invalid HOTP counter")?),
+            _ => {}
+        }
+    }
+
+    if otp.secret.is_empty() {
+        return Err("otpauth URI has no secret".to_string());
+    }
+    if otp.otp_type == "hotp" && otp.counter.is_none() {
+        return Err("HOTP URI has no counter".to_string());
+    }
+
+    otp.algorithm.get_or_insert_with(|| "SHA1".to_string());
+    otp.digits.get_or_insert(6);
+    if otp.otp_type == "totp" {
+        otp.period.get_or_insert(30);
+    }
+
+    Ok(otp)
+}
+
+/// Validate a base32 secret, returning it uppercased without spaces or padding
+pub fn normalize_secret(secret: &str) -> Result<String, String> {
+    let secret: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase();
+    let secret = secret.trim_end_matches('=');
+
+    if let Some(c) = secret.bytes().find(|b| !BASE32_ALPHABET.contains(b)) {
+        return Err(format!("Secret contains '{}', which is not valid base32", c as char));
+    }
+    // Lengths that leave a partial byte cannot come from encoding whole bytes
+    if matches!(secret.len() % 8, 1 | 3 | 6) {
+        return Err("Secret has an invalid base32 length".to_string());
+    }
+    if secret.len() < MIN_SECRET_CHARS {
+        return Err(format!(
+            "Secret is {} characters, minimum is {} (80 bits)",
+            secret.len(),
+            MIN_SECRET_CHARS
+        ));
+    }
+
+    Ok(secret.to_string())
+}
+
+/// Mask all but the last few characters of a secret
+pub fn mask_secret(secret: &str) -> String {
+    let visible = secret.len().saturating_sub(VISIBLE_SECRET_CHARS);
+    format!("****{}", secret.get(visible..).unwrap_or(""))
+}
+
+/// Replace the secret in an otpauth URI with its masked form.
+/// Content that isn't an otpauth URI is returned unchanged.
+pub fn mask_otp_uri(content: &str) -> String {
+    let Some((base, query)) = content.split_once('?') else {
+        return content.to_string();
+    };
+
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if key.eq_ignore_ascii_case("secret") => {
+                format!("{}={}", key, mask_secret(&decode(value)))
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{}?{}", base, query)
+}
+
+fn normalize_algorithm(algorithm: &str) -> Result<&'static str, String> {
+    match algorithm.trim().to_uppercase().replace('-', "").as_str() {
+        "SHA1" => Ok("SHA1"),
+        "SHA256" => Ok("SHA256"),
+        "SHA512" => Ok("SHA512"),
+        _ => Err(format!("Unsupported OTP algorithm '{}'", algorithm)),
+    }
+}
+
+fn decode(value: &str) -> String {
+    urlencoding::decode(value)
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_otp() -> OtpAuth {
+        OtpAuth {
+            otp_type: "totp".to_string(),
+            issuer: Some("ACME Co".to_string()),
+            account: "john.doe@email.com".to_string(),
+            secret: "hxdm vjec jjws rb3h wizr 4ifu gftm xboz".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_otp() {
+        assert_eq!(
+            build_otp(&sample_otp()).unwrap(),
+            "otpauth://totp/ACME%20Co:john.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co"
+        );
+
+        let mut otp = sample_otp();
+        otp.otp_type = "hotp".to_string();
+        otp.algorithm = Some("sha-256".to_string());
+        otp.digits = Some(8);
+        otp.counter = Some(5);
+        assert!(build_otp(&otp)
+            .unwrap()
+            .ends_with("&algorithm=SHA256&digits=8&counter=5"));
+    }
+
+    #[test]
+    fn test_build_otp_rejects_invalid_fields() {
+        let mut otp = sample_otp();
+        otp.secret = "not base32!".to_string();
+        assert!(build_otp(&otp).unwrap_err().contains("base32"));
+
+        let mut otp = sample_otp();
+        otp.secret = "JBSWY3DP".to_string();
+        assert!(build_otp(&otp).unwrap_err().contains("minimum"));
+
+        let mut otp = sample_otp();
+        otp.digits = Some(4);
+        assert!(build_otp(&otp).is_err());
+
+        let mut otp = sample_otp();
+        otp.otp_type = "sms".to_string();
+        assert!(build_otp(&otp).is_err());
+
+        let mut otp = sample_otp();
+        otp.account = String::new();
+        assert!(build_otp(&otp).is_err());
+    }
+
+    #[test]
+    fn test_parse_otp() {
+        let otp = parse_otp(
+            "otpauth://totp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Example&period=60",
+        )
+        .unwrap();
+
+        assert_eq!(otp.otp_type, "totp");
+        assert_eq!(otp.issuer, Some("Example".to_string()));
+        assert_eq!(otp.account, "alice@google.com");
+        assert_eq!(otp.secret, "JBSWY3DPEHPK3PXP");
+        assert_eq!(otp.algorithm, Some("SHA1".to_string()));
+        assert_eq!(otp.digits, Some(6));
+        assert_eq!(otp.period, Some(60));
+        assert_eq!(otp.counter, None);
+    }
+
+    #[test]
+    fn test_parse_otp_round_trip() {
+        let built = build_otp(&sample_otp()).unwrap();
+        let parsed = parse_otp(&built).unwrap();
+        assert_eq!(parsed.issuer, Some("ACME Co".to_string()));
+        assert_eq!(parsed.account, "john.doe@email.com");
+        assert_eq!(parsed.secret, "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+    }
+
+    #[test]
+    fn test_parse_otp_rejects_invalid() {
+        assert!(parse_otp("otpauth://totp/Example:alice").is_err());
+        assert!(parse_otp("otpauth://hotp/Example:alice?secret=JBSWY3DPEHPK3PXP").is_err());
+        assert!(parse_otp("otpauth://totp/alice?secret=JBSWY3DP1HPK3PXP").is_err());
+        assert!(parse_otp("otpauth://yubikey/alice?secret=JBSWY3DPEHPK3PXP").is_err());
+    }
+
+    #[test]
+    fn test_masking() {
+        assert_eq!(mask_secret("JBSWY3DPEHPK3PXP"), "****3PXP");
+
+        let otp = parse_otp("otpauth://totp/alice?secret=JBSWY3DPEHPK3PXP").unwrap().masked();
+        assert_eq!(otp.secret, "****3PXP");
+        assert!(otp.secret_masked);
+
+        assert_eq!(
+            mask_otp_uri("otpauth://totp/alice?secret=JBSWY3DPEHPK3PXP&issuer=Example"),
+            "otpauth://totp/alice?secret=****3PXP&issuer=Example"
+        );
+        assert_eq!(mask_otp_uri("otpauth://totp/alice"), "otpauth://totp/alice");
+    }
+}
@@ -0,0 +1,263 @@
+use super::emv::{build_emv, parse_emv, EmvField, EmvMerchantAccount, EmvPayment};
+use super::{format_cents, non_empty, parse_amount, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+/// Globally unique identifier of the PIX arrangement in the merchant account template
+pub const PIX_GUI: &str = "br.gov.bcb.pix";
+
+/// A Brazilian PIX payment (BR Code), carried in an EMVCo payload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PixPayment {
+    pub key: String,
+    pub merchant_name: String,
+    pub merchant_city: String,
+    pub amount: Option<String>,
+    pub txid: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>, // dynamic BR Codes carry a payload URL instead of a key
+}
+
+/// Build a PIX BR Code: a static one around the key, or a dynamic one
+/// pointing at a payload URL when `url` is set
+pub fn build_pix(pix: &PixPayment) -> Result<String, String> {
+    let url = match non_empty(&pix.url) {
+        Some(url) => Some(validate_pix_url(url)?),
+        None => None,
+    };
+
+    let amount = match non_empty(&pix.amount) {
+        Some(amount) => Some(format_cents(parse_amount(amount)?)),
+        None => None,
+    };
+
+    let txid = non_empty(&pix.txid).unwrap_or("***");
+    if txid != "***" && (txid.len() > 25 || !txid.bytes().all(|b| b.is_ascii_alphanumeric())) {
+        return Err("PIX txid must be up to 25 letters or digits".to_string());
+    }
+
+    let mut fields = Vec::new();
+    if let Some(url) = &url {
+        // The payload behind the URL carries the key and any description
+        if !pix.key.trim().is_empty() || non_empty(&pix.description).is_some() {
+            return Err("A dynamic PIX code carries a payload URL instead of a key or description".to_string());
+        }
+        fields.push(EmvField {
+            id: "25".to_string(),
+            value: url.clone(),
+        });
+    } else {
+        fields.push(EmvField {
+            id: "01".to_string(),
+            value: validate_pix_key(&pix.key)?,
+        });
+        if let Some(description) = non_empty(&pix.description) {
+            fields.push(EmvField {
+                id: "02".to_string(),
+                value: description.to_string(),
+            });
+        }
+    }
+
+    build_emv(&EmvPayment {
+        merchant_name: pix.merchant_name.clone(),
+        merchant_city: pix.merchant_city.clone(),
+        country_code: "BR".to_string(),
+        currency: "986".to_string(),
+        amount,
+        reference_label: Some(txid.to_string()),
+        dynamic: url.is_some(),
+        merchant_accounts: vec![EmvMerchantAccount {
+            id: "26".to_string(),
+            gui: PIX_GUI.to_string(),
+            fields,
+        }],
+        ..Default::default()
+    })
+}
+
+/// Parse a PIX BR Code, verifying the EMV CRC
+pub fn parse_pix(content: &str) -> Result<PixPayment, String> {
+    let emv = parse_emv(content)?;
+
+    let account = emv
+        .merchant_accounts
+        .iter()
+        .find(|a| a.gui.eq_ignore_ascii_case(PIX_GUI))
+        .ok_or("Payload has no PIX merchant account")?;
+
+    let field = |id: &str| {
+        account
+            .fields
+            .iter()
+            .find(|f| f.id == id)
+            .map(|f| f.value.clone())
+    };
+
+    Ok(PixPayment {
+        key: field("01").unwrap_or_default(),
+        merchant_name: emv.merchant_name,
+        merchant_city: emv.merchant_city,
+        amount: emv.amount,
+        txid: emv.reference_label,
+        description: field("02"),
+        url: field("25"),
+    })
+}
+
+/// Validate a dynamic PIX payload URL, which BR Codes carry without the
+/// https:// scheme
+pub fn validate_pix_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let location = strip_prefix_ci(url, "https://").unwrap_or(url);
+    if location.is_empty()
+        || location.len() > 77
+        || location.contains("://")
+        || location.contains(char::is_whitespace)
+        || !location.split('/').next().is_some_and(|host| host.contains('.'))
+    {
+        return Err(format!("'{}' is not a valid PIX payload URL", url));
+    }
+    Ok(location.to_string())
+}
+
+/// Validate a PIX key: CPF/CNPJ, phone (+55...), e-mail or random (EVP) key
+pub fn validate_pix_key(key: &str) -> Result<String, String> {
+    let key = key.trim();
+
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let is_tax_id = is_digits(key) && (key.len() == 11 || key.len() == 14);
+    let is_phone = key
+        .strip_prefix('+')
+        .is_some_and(|n| is_digits(n) && (12..=14).contains(&n.len()));
+    let is_email = key.len() <= 77
+        && key
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'));
+    let is_evp = key.len() == 36
+        && key.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+
+    if is_tax_id || is_phone || is_email || is_evp {
+        Ok(key.to_string())
+    } else {
+        Err(format!("'{}' is not a valid PIX key", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pix() -> PixPayment {
+        PixPayment {
+            key: "123e4567-e12b-12d1-a456-426655440000".to_string(),
+            merchant_name: "Fulano de Tal".to_string(),
+            merchant_city: "BRASILIA".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_pix_matches_reference_example() {
+        // Static example from the Banco Central do Brasil manual
+        assert_eq!(
+            build_pix(&sample_pix()).unwrap(),
+            "00020126580014br.gov.bcb.pix0136123e4567-e12b-12d1-a456-4266554400005204000053039865802BR5913Fulano de Tal6008BRASILIA62070503***63041D3D"
+        );
+    }
+
+    #[test]
+    fn test_build_pix_dynamic_url() {
+        let pix = PixPayment {
+            merchant_name: "Fulano de Tal".to_string(),
+            merchant_city: "BRASILIA".to_string(),
+            url: Some("https://pix.example.com/qr/v2/9d36b84f".to_string()),
+            ..Default::default()
+        };
+        let payload = build_pix(&pix).unwrap();
+        assert!(payload.starts_with("000201010212"));
+        assert!(payload.contains("26520014br.gov.bcb.pix2530pix.example.com/qr/v2/9d36b84f"));
+
+        let parsed = parse_pix(&payload).unwrap();
+        assert_eq!(parsed.key, "");
+        assert_eq!(parsed.url.as_deref(), Some("pix.example.com/qr/v2/9d36b84f"));
+
+        // The key lives behind the URL, not next to it
+        let mut with_key = pix.clone();
+        with_key.key = sample_pix().key;
+        assert!(build_pix(&with_key).is_err());
+
+        let mut bad = pix;
+        bad.url = Some("ftp://example.com".to_string());
+        assert!(build_pix(&bad).is_err());
+    }
+
+    #[test]
+    fn test_build_pix_with_amount_round_trip() {
+        let mut pix = sample_pix();
+        pix.amount = Some("10.5".to_string());
+        pix.txid = Some("PEDIDO42".to_string());
+        pix.description = Some("Pedido 42".to_string());
+
+        let payload = build_pix(&pix).unwrap();
+        assert!(payload.contains("540510.50"));
+
+        let parsed = parse_pix(&payload).unwrap();
+        assert_eq!(parsed.key, pix.key);
+        assert_eq!(parsed.amount, Some("10.50".to_string()));
+        assert_eq!(parsed.txid, Some("PEDIDO42".to_string()));
+        assert_eq!(parsed.description, Some("Pedido 42".to_string()));
+        assert_eq!(parsed.url, None);
+    }
+
+    #[test]
+    fn test_build_pix_rejects_invalid_fields() {
+        let mut pix = sample_pix();
+        pix.key = "not a key".to_string();
+        assert!(build_pix(&pix).is_err());
+
+        let mut pix = sample_pix();
+        pix.txid = Some("has spaces".to_string());
+        assert!(build_pix(&pix).is_err());
+
+        let mut pix = sample_pix();
+        pix.merchant_city = "x".repeat(16);
+        assert!(build_pix(&pix).is_err());
+    }
+
+    #[test]
+    fn test_validate_pix_key() {
+        assert!(validate_pix_key("12345678901").is_ok()); // CPF
+        assert!(validate_pix_key("12345678000195").is_ok()); // CNPJ
+        assert!(validate_pix_key("+5561912345678").is_ok());
+        assert!(validate_pix_key("fulano@example.com").is_ok());
+        assert!(validate_pix_key("123e4567-e12b-12d1-a456-426655440000").is_ok());
+
+        assert!(validate_pix_key("").is_err());
+        assert!(validate_pix_key("12345").is_err());
+        assert!(validate_pix_key("fulano@localhost").is_err());
+    }
+
+    #[test]
+    fn test_parse_pix_requires_pix_account() {
+        let payload = build_emv(&EmvPayment {
+            merchant_name: "Shop".to_string(),
+            merchant_city: "City".to_string(),
+            country_code: "SG".to_string(),
+            currency: "SGD".to_string(),
+            merchant_accounts: vec![EmvMerchantAccount {
+                id: "26".to_string(),
+                gui: "sg.paynow".to_string(),
+                fields: vec![],
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(parse_pix(&payload).is_err());
+    }
+}
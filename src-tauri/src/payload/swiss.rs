@@ -0,0 +1,456 @@
+use super::iban::{validate_creditor_reference, validate_iban};
+use super::{check_length, format_cents, non_empty, parse_amount};
+use serde::{Deserialize, Serialize};
+
+/// The Swiss Implementation Guidelines mandate error correction level M
+pub const SWISS_ERROR_CORRECTION: &str = "M";
+
+/// Highest QR version allowed on a QR-bill
+pub const SWISS_MAX_VERSION: i16 = 25;
+
+/// Maximum payload length allowed by the Swiss Implementation Guidelines
+const MAX_PAYLOAD_CHARS: usize = 997;
+
+/// Number of elements up to and including the "EPD" trailer
+const REQUIRED_ELEMENTS: usize = 31;
+
+/// Structured ("S") postal address used for creditor and debtor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwissAddress {
+    pub name: String,
+    pub street: Option<String>,
+    pub building_number: Option<String>,
+    pub postal_code: String,
+    pub town: String,
+    pub country: String,
+}
+
+/// A Swiss QR-bill payment as encoded in the SPC payload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwissQrBill {
+    pub iban: String,
+    pub creditor: SwissAddress,
+    pub amount: Option<String>,
+    pub currency: String, // "CHF" | "EUR"
+    pub debtor: Option<SwissAddress>,
+    pub reference_type: Option<String>, // "QRR" | "SCOR" | "NON", inferred when omitted
+    pub reference: Option<String>,
+    pub message: Option<String>,
+    pub billing_info: Option<String>,
+    #[serde(default)]
+    pub alternative_schemes: Vec<String>,
+}
+
+/// Build an SPC (version 2.0) payload, validating it against the Swiss Implementation Guidelines
+pub fn build_swiss_qr_bill(bill: &SwissQrBill) -> Result<String, String> {
+    let iban = validate_iban(&bill.iban)?;
+    if !iban.starts_with("CH") && !iban.starts_with("LI") {
+        return Err("QR-bill IBAN must be a Swiss (CH) or Liechtenstein (LI) account".to_string());
+    }
+
+    let currency = bill.currency.trim().to_uppercase();
+    if currency != "CHF" && currency != "EUR" {
+        return Err("Currency must be CHF or EUR".to_string());
+    }
+
+    let amount = match non_empty(&bill.amount) {
+        Some(amount) => format_cents(parse_amount(amount)?),
+        None => String::new(),
+    };
+
+    let reference = non_empty(&bill.reference).map(|r| r.replace(' ', ""));
+    let reference_type = match non_empty(&bill.reference_type) {
+        Some(tp) => tp.to_uppercase(),
+        None if is_qr_iban(&iban) => "QRR".to_string(),
+        None if reference.is_some() => "SCOR".to_string(),
+        None => "NON".to_string(),
+    };
+
+    let reference = match (reference_type.as_str(), reference) {
+        ("QRR", Some(reference)) => validate_qr_reference(&reference)?,
+        ("QRR", None) => return Err("A QR reference is required for QRR".to_string()),
+        ("SCOR", Some(reference)) => validate_creditor_reference(&reference)?,
+        ("SCOR", None) => return Err("A creditor reference is required for SCOR".to_string()),
+        ("NON", None) => String::new(),
+        ("NON", Some(_)) => return Err("Reference type NON must not carry a reference".to_string()),
+        (other, _) => return Err(format!("Unknown reference type '{}'", other)),
+    };
+
+    if is_qr_iban(&iban) != (reference_type == "QRR") {
+        return Err(if is_qr_iban(&iban) {
+            "A QR-IBAN must be used with a QRR reference".to_string()
+        } else {
+            "QRR references require a QR-IBAN".to_string()
+        });
+    }
+
+    let message = non_empty(&bill.message).unwrap_or("");
+    let billing_info = non_empty(&bill.billing_info).unwrap_or("");
+    check_length(
+        "Message and billing information",
+        &format!("{}{}", message, billing_info),
+        140,
+    )?;
+
+    if bill.alternative_schemes.len() > 2 {
+        return Err("At most two alternative schemes are allowed".to_string());
+    }
+    for scheme in &bill.alternative_schemes {
+        check_length("Alternative scheme", scheme, 100)?;
+    }
+
+    let mut lines = vec![
+        "SPC".to_string(),
+        "0200".to_string(),
+        "1".to_string(), // UTF-8
+        iban,
+    ];
+    lines.extend(address_lines("Creditor", Some(&bill.creditor))?);
+    lines.extend(address_lines("Ultimate creditor", None)?);
+    lines.push(amount);
+    lines.push(currency);
+    lines.extend(address_lines("Debtor", bill.debtor.as_ref())?);
+    lines.push(reference_type);
+    lines.push(reference);
+    lines.push(message.to_string());
+    lines.push("EPD".to_string());
+
+    if !billing_info.is_empty() || !bill.alternative_schemes.is_empty() {
+        lines.push(billing_info.to_string());
+    }
+    lines.extend(bill.alternative_schemes.iter().map(|s| s.trim().to_string()));
+
+    let payload = lines.join("\n");
+    let len = payload.chars().count();
+    if len > MAX_PAYLOAD_CHARS {
+        return Err(format!(
+            "QR-bill payload is {} characters, maximum is {}",
+            len, MAX_PAYLOAD_CHARS
+        ));
+    }
+
+    Ok(payload)
+}
+
+/// Parse an SPC payload back into its fields
+pub fn parse_swiss_qr_bill(content: &str) -> Result<SwissQrBill, String> {
+    let lines: Vec<&str> = content
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r'))
+        .collect();
+
+    if lines.first().map(|l| l.trim()) != Some("SPC") {
+        return Err("Missing SPC header".to_string());
+    }
+    if !lines.get(1).is_some_and(|v| v.starts_with("02")) {
+        return Err("Unsupported QR-bill version".to_string());
+    }
+    if lines.len() < REQUIRED_ELEMENTS || lines[REQUIRED_ELEMENTS - 1].trim() != "EPD" {
+        return Err("QR-bill payload is truncated (missing EPD trailer)".to_string());
+    }
+
+    let field = |idx: usize| lines.get(idx).map(|s| s.trim()).unwrap_or("");
+    let optional = |idx: usize| Some(field(idx).to_string()).filter(|s| !s.is_empty());
+
+    Ok(SwissQrBill {
+        iban: field(3).to_string(),
+        creditor: parse_address(&lines[4..11]).unwrap_or_default(),
+        amount: optional(18),
+        currency: field(19).to_string(),
+        debtor: parse_address(&lines[20..27]),
+        reference_type: optional(27),
+        reference: optional(28),
+        message: optional(29),
+        billing_info: optional(31),
+        alternative_schemes: lines
+            .iter()
+            .skip(32)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    })
+}
+
+/// Validate an SPC payload by parsing it and checking every field
+pub fn validate_swiss_qr_bill(content: &str) -> Result<SwissQrBill, String> {
+    let bill = parse_swiss_qr_bill(content)?;
+    build_swiss_qr_bill(&bill)?;
+    Ok(bill)
+}
+
+/// Whether an IBAN is a QR-IBAN (institution ID 30000-31999)
+pub fn is_qr_iban(iban: &str) -> bool {
+    (iban.starts_with("CH") || iban.starts_with("LI"))
+        && iban
+            .get(4..9)
+            .and_then(|iid| iid.parse::<u32>().ok())
+            .is_some_and(|iid| (30000..=31999).contains(&iid))
+}
+
+/// Validate a 27-digit QR reference with its modulo 10 recursive check digit
+pub fn validate_qr_reference(reference: &str) -> Result<String, String> {
+    let reference = reference.replace(' ', "");
+
+    if reference.len() != 27 || !reference.bytes().all(|b| b.is_ascii_digit()) {
+        return Err("QR reference must be 27 digits".to_string());
+    }
+
+    let (body, check) = reference.split_at(26);
+    if mod10_recursive(body).to_string() != check {
+        return Err("QR reference check digit is invalid".to_string());
+    }
+
+    Ok(reference)
+}
+
+/// Compute the modulo 10 recursive check digit used by QR references
+pub fn mod10_recursive(digits: &str) -> u32 {
+    const TABLE: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+
+    let carry = digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .fold(0, |carry, digit| TABLE[((carry + digit) % 10) as usize]);
+
+    (10 - carry) % 10
+}
+
+fn address_lines(role: &str, address: Option<&SwissAddress>) -> Result<Vec<String>, String> {
+    let Some(address) = address else {
+        return Ok(vec![String::new(); 7]);
+    };
+
+    let name = address.name.trim();
+    if name.is_empty() {
+        return Err(format!("{} name is required", role));
+    }
+    check_length(&format!("{} name", role), name, 70)?;
+
+    let street = non_empty(&address.street).unwrap_or("");
+    check_length(&format!("{} street", role), street, 70)?;
+
+    let building = non_empty(&address.building_number).unwrap_or("");
+    check_length(&format!("{} building number", role), building, 16)?;
+
+    let postal_code = address.postal_code.trim();
+    let town = address.town.trim();
+    if postal_code.is_empty() || town.is_empty() {
+        return Err(format!("{} postal code and town are required", role));
+    }
+    check_length(&format!("{} postal code", role), postal_code, 16)?;
+    check_length(&format!("{} town", role), town, 35)?;
+
+    let country = address.country.trim().to_uppercase();
+    if country.len() != 2 || !country.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(format!("{} country must be a two-letter ISO code", role));
+    }
+
+    Ok(vec![
+        "S".to_string(),
+        name.to_string(),
+        street.to_string(),
+        building.to_string(),
+        postal_code.to_string(),
+        town.to_string(),
+        country,
+    ])
+}
+
+/// Parse a 7-line address block; combined ("K") addresses keep their second line in `town`
+fn parse_address(lines: &[&str]) -> Option<SwissAddress> {
+    let field = |idx: usize| lines.get(idx).map(|s| s.trim()).unwrap_or("");
+    let optional = |idx: usize| Some(field(idx).to_string()).filter(|s| !s.is_empty());
+
+    match field(0) {
+        "S" => Some(SwissAddress {
+            name: field(1).to_string(),
+            street: optional(2),
+            building_number: optional(3),
+            postal_code: field(4).to_string(),
+            town: field(5).to_string(),
+            country: field(6).to_string(),
+        }),
+        "K" => Some(SwissAddress {
+            name: field(1).to_string(),
+            street: optional(2),
+            building_number: None,
+            postal_code: String::new(),
+            town: field(3).to_string(),
+            country: field(6).to_string(),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creditor() -> SwissAddress {
+        SwissAddress {
+            name: "Robert Schneider AG".to_string(),
+            street: Some("Rue du Lac".to_string()),
+            building_number: Some("1268".to_string()),
+            postal_code: "2501".to_string(),
+            town: "Biel".to_string(),
+            country: "CH".to_string(),
+        }
+    }
+
+    fn qrr_bill() -> SwissQrBill {
+        SwissQrBill {
+            iban: "CH44 3199 9123 0008 8901 2".to_string(),
+            creditor: creditor(),
+            amount: Some("1949.75".to_string()),
+            currency: "CHF".to_string(),
+            reference: Some("21 00000 00003 13947 14300 09017".to_string()),
+            message: Some("Order of 15 June 2020".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mod10_recursive() {
+        assert_eq!(mod10_recursive("21000000000313947143000901"), 7);
+        assert_eq!(mod10_recursive("00000000000000000000000000"), 0);
+    }
+
+    #[test]
+    fn test_validate_qr_reference() {
+        assert_eq!(
+            validate_qr_reference("21 00000 00003 13947 14300 09017").unwrap(),
+            "210000000003139471430009017"
+        );
+        assert!(validate_qr_reference("210000000003139471430009018").is_err());
+        assert!(validate_qr_reference("12345").is_err());
+    }
+
+    #[test]
+    fn test_is_qr_iban() {
+        assert!(is_qr_iban("CH4431999123000889012"));
+        assert!(!is_qr_iban("CH9300762011623852957"));
+        assert!(!is_qr_iban("DE89370400440532013000"));
+    }
+
+    #[test]
+    fn test_build_swiss_qr_bill_qrr() {
+        let payload = build_swiss_qr_bill(&qrr_bill()).unwrap();
+        let lines: Vec<&str> = payload.split('\n').collect();
+
+        assert_eq!(lines.len(), 31);
+        assert_eq!(&lines[..4], &["SPC", "0200", "1", "CH4431999123000889012"]);
+        assert_eq!(&lines[4..11], &["S", "Robert Schneider AG", "Rue du Lac", "1268", "2501", "Biel", "CH"]);
+        assert_eq!(lines[18], "1949.75");
+        assert_eq!(lines[19], "CHF");
+        assert_eq!(lines[27], "QRR");
+        assert_eq!(lines[28], "210000000003139471430009017");
+        assert_eq!(lines[30], "EPD");
+    }
+
+    #[test]
+    fn test_build_swiss_qr_bill_scor_and_non() {
+        let mut bill = qrr_bill();
+        bill.iban = "CH93 0076 2011 6238 5295 7".to_string();
+        bill.reference = Some("RF18 5390 0754 7034".to_string());
+        let payload = build_swiss_qr_bill(&bill).unwrap();
+        assert!(payload.contains("\nSCOR\nRF18539007547034\n"));
+
+        bill.reference = None;
+        bill.billing_info = Some("//S1/10/10201409".to_string());
+        let payload = build_swiss_qr_bill(&bill).unwrap();
+        assert!(payload.contains("\nNON\n\n"));
+        assert!(payload.ends_with("\nEPD\n//S1/10/10201409"));
+    }
+
+    #[test]
+    fn test_build_swiss_qr_bill_reference_rules() {
+        // QR-IBAN without a QR reference
+        let mut bill = qrr_bill();
+        bill.reference = None;
+        assert!(build_swiss_qr_bill(&bill).is_err());
+
+        // QR-IBAN with a creditor reference
+        let mut bill = qrr_bill();
+        bill.reference_type = Some("SCOR".to_string());
+        bill.reference = Some("RF18539007547034".to_string());
+        assert!(build_swiss_qr_bill(&bill).unwrap_err().contains("QR-IBAN"));
+
+        // Regular IBAN with a QR reference
+        let mut bill = qrr_bill();
+        bill.iban = "CH9300762011623852957".to_string();
+        bill.reference_type = Some("QRR".to_string());
+        assert!(build_swiss_qr_bill(&bill).unwrap_err().contains("require a QR-IBAN"));
+
+        // Bad QR reference check digit
+        let mut bill = qrr_bill();
+        bill.reference = Some("210000000003139471430009018".to_string());
+        assert!(build_swiss_qr_bill(&bill).unwrap_err().contains("check digit"));
+    }
+
+    #[test]
+    fn test_build_swiss_qr_bill_rejects_invalid_fields() {
+        let mut bill = qrr_bill();
+        bill.iban = "DE89370400440532013000".to_string();
+        assert!(build_swiss_qr_bill(&bill).is_err());
+
+        let mut bill = qrr_bill();
+        bill.currency = "USD".to_string();
+        assert!(build_swiss_qr_bill(&bill).is_err());
+
+        let mut bill = qrr_bill();
+        bill.creditor.country = "Switzerland".to_string();
+        assert!(build_swiss_qr_bill(&bill).is_err());
+
+        let mut bill = qrr_bill();
+        bill.creditor.town = "x".repeat(36);
+        assert!(build_swiss_qr_bill(&bill).unwrap_err().contains("maximum is 35"));
+
+        let mut bill = qrr_bill();
+        bill.message = Some("x".repeat(100));
+        bill.billing_info = Some("y".repeat(41));
+        assert!(build_swiss_qr_bill(&bill).unwrap_err().contains("maximum is 140"));
+
+        let mut bill = qrr_bill();
+        bill.alternative_schemes = vec!["a".into(), "b".into(), "c".into()];
+        assert!(build_swiss_qr_bill(&bill).is_err());
+    }
+
+    #[test]
+    fn test_parse_swiss_qr_bill_round_trip() {
+        let mut bill = qrr_bill();
+        bill.debtor = Some(SwissAddress {
+            name: "Pia-Maria Rutschmann-Schnyder".to_string(),
+            street: Some("Grosse Marktgasse".to_string()),
+            building_number: Some("28".to_string()),
+            postal_code: "9400".to_string(),
+            town: "Rorschach".to_string(),
+            country: "CH".to_string(),
+        });
+        bill.alternative_schemes = vec!["eBill/B/41010560425610173".to_string()];
+
+        let payload = build_swiss_qr_bill(&bill).unwrap();
+        let parsed = parse_swiss_qr_bill(&payload).unwrap();
+
+        assert_eq!(parsed.iban, "CH4431999123000889012");
+        assert_eq!(parsed.creditor, creditor());
+        assert_eq!(parsed.debtor, bill.debtor);
+        assert_eq!(parsed.amount, Some("1949.75".to_string()));
+        assert_eq!(parsed.currency, "CHF");
+        assert_eq!(parsed.reference_type, Some("QRR".to_string()));
+        assert_eq!(parsed.reference, Some("210000000003139471430009017".to_string()));
+        assert_eq!(parsed.message, Some("Order of 15 June 2020".to_string()));
+        assert_eq!(parsed.billing_info, None);
+        assert_eq!(parsed.alternative_schemes, bill.alternative_schemes);
+
+        assert!(validate_swiss_qr_bill(&payload).is_ok());
+    }
+
+    #[test]
+    fn test_parse_swiss_qr_bill_rejects_malformed() {
+        assert!(parse_swiss_qr_bill("SPC\n0200\n1").is_err());
+        assert!(parse_swiss_qr_bill("SPC\n0100\n1").is_err());
+        assert!(parse_swiss_qr_bill("BCD\n002").is_err());
+    }
+}
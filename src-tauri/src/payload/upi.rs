@@ -0,0 +1,179 @@
+use super::{check_length, format_cents, non_empty, parse_amount, query_pairs, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+/// An Indian UPI payment request (`upi://pay?...`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpiPayment {
+    pub payee_address: String, // VPA, e.g. "merchant@bank"
+    pub payee_name: String,
+    pub amount: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>, // only INR is accepted
+    pub note: Option<String>,
+    pub transaction_ref: Option<String>,
+    pub merchant_code: Option<String>,
+}
+
+/// Build a UPI deep link following the NPCI linking specification
+pub fn build_upi(upi: &UpiPayment) -> Result<String, String> {
+    let address = validate_vpa(&upi.payee_address)?;
+
+    let name = upi.payee_name.trim();
+    if name.is_empty() {
+        return Err("Payee name is required".to_string());
+    }
+    check_length("Payee name", name, 99)?;
+
+    let currency = non_empty(&upi.currency).unwrap_or("INR").to_uppercase();
+    if currency != "INR" {
+        return Err("UPI payments must be in INR".to_string());
+    }
+
+    let mut params = vec![("pa", address), ("pn", name.to_string())];
+
+    if let Some(code) = non_empty(&upi.merchant_code) {
+        if code.len() != 4 || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("Merchant code must be a 4-digit MCC".to_string());
+        }
+        params.push(("mc", code.to_string()));
+    }
+    if let Some(reference) = non_empty(&upi.transaction_ref) {
+        check_length("Transaction reference", reference, 35)?;
+        params.push(("tr", reference.to_string()));
+    }
+    if let Some(note) = non_empty(&upi.note) {
+        check_length("Note", note, 80)?;
+        params.push(("tn", note.to_string()));
+    }
+    if let Some(amount) = non_empty(&upi.amount) {
+        params.push(("am", format_cents(parse_amount(amount)?)));
+    }
+    params.push(("cu", currency));
+
+    let query = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    Ok(format!("upi://pay?{}", query))
+}
+
+/// Parse a `upi://pay` link into its fields
+pub fn parse_upi(content: &str) -> Result<UpiPayment, String> {
+    let content = content.trim();
+    let query = strip_prefix_ci(content, "upi://pay?").ok_or("Not a upi://pay link")?;
+
+    let mut upi = UpiPayment::default();
+
+    for (key, value) in query_pairs(query) {
+        let value = Some(value).filter(|v| !v.is_empty());
+
+        match key.as_str() {
+            "pa" => upi.payee_address = value.unwrap_or_default(),
+            "pn" => upi.payee_name = value.unwrap_or_default(),
+            "am" => upi.amount = value,
+            "cu" => upi.currency = value,
+            "tn" => upi.note = value,
+            "tr" => upi.transaction_ref = value,
+            "mc" => upi.merchant_code = value,
+            _ => {}
+        }
+    }
+
+    if upi.payee_address.is_empty() {
+        return Err("UPI link is missing the payee address (pa)".to_string());
+    }
+
+    Ok(upi)
+}
+
+/// Validate a UPI virtual payment address (`handle@provider`)
+fn validate_vpa(vpa: &str) -> Result<String, String> {
+    let vpa = vpa.trim();
+
+    let valid = vpa.split_once('@').is_some_and(|(handle, provider)| {
+        (2..=256).contains(&handle.len())
+            && handle
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-' || b == b'_')
+            && (2..=64).contains(&provider.len())
+            && provider.bytes().all(|b| b.is_ascii_alphanumeric())
+    });
+
+    if valid {
+        Ok(vpa.to_string())
+    } else {
+        Err(format!("'{}' is not a valid UPI address", vpa))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_upi() -> UpiPayment {
+        UpiPayment {
+            payee_address: "chai.stall@okaxis".to_string(),
+            payee_name: "Chai Stall".to_string(),
+            amount: Some("49".to_string()),
+            note: Some("2 chai & samosa".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_upi() {
+        assert_eq!(
+            build_upi(&sample_upi()).unwrap(),
+            "upi://pay?pa=chai.stall%40okaxis&pn=Chai%20Stall&tn=2%20chai%20%26%20samosa&am=49.00&cu=INR"
+        );
+    }
+
+    #[test]
+    fn test_build_upi_rejects_invalid_fields() {
+        let mut upi = sample_upi();
+        upi.payee_address = "not-a-vpa".to_string();
+        assert!(build_upi(&upi).is_err());
+
+        let mut upi = sample_upi();
+        upi.currency = Some("USD".to_string());
+        assert!(build_upi(&upi).unwrap_err().contains("INR"));
+
+        let mut upi = sample_upi();
+        upi.merchant_code = Some("12".to_string());
+        assert!(build_upi(&upi).is_err());
+
+        let mut upi = sample_upi();
+        upi.amount = Some("-5".to_string());
+        assert!(build_upi(&upi).is_err());
+    }
+
+    #[test]
+    fn test_parse_upi_round_trip() {
+        let mut upi = sample_upi();
+        upi.merchant_code = Some("5812".to_string());
+        upi.transaction_ref = Some("ORDER-17".to_string());
+
+        let parsed = parse_upi(&build_upi(&upi).unwrap()).unwrap();
+        assert_eq!(parsed.payee_address, "chai.stall@okaxis");
+        assert_eq!(parsed.payee_name, "Chai Stall");
+        assert_eq!(parsed.amount, Some("49.00".to_string()));
+        assert_eq!(parsed.currency, Some("INR".to_string()));
+        assert_eq!(parsed.note, Some("2 chai & samosa".to_string()));
+        assert_eq!(parsed.transaction_ref, Some("ORDER-17".to_string()));
+        assert_eq!(parsed.merchant_code, Some("5812".to_string()));
+    }
+
+    #[test]
+    fn test_parse_upi_real_world_link() {
+        let parsed = parse_upi("UPI://PAY?pa=shop@ybl&pn=My+Shop&cu=INR").unwrap();
+        assert_eq!(parsed.payee_address, "shop@ybl");
+        assert_eq!(parsed.payee_name, "My Shop");
+        assert_eq!(parsed.amount, None);
+
+        assert!(parse_upi("upi://pay?pn=Nobody").is_err());
+        assert!(parse_upi("upi://mandate?pa=x@y").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use super::{split_fields, strip_prefix_ci};
+use serde::{Deserialize, Serialize};
+
+/// Wi-Fi credentials parsed from a `WIFI:` code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiNetwork {
+    pub ssid: String,
+    pub security: String, // "WPA" | "WEP" | "SAE" | "nopass", as found in the code
+    pub password: Option<String>,
+    pub hidden: bool,
+    pub eap_method: Option<String>, // WPA2-Enterprise only
+    pub identity: Option<String>,
+    pub anonymous_identity: Option<String>,
+    pub phase2: Option<String>,
+}
+
+/// Parse a `WIFI:` code. Fields may appear in any order.
+pub fn parse_wifi(content: &str) -> Result<WifiNetwork, String> {
+    let body = strip_prefix_ci(content.trim(), "WIFI:").ok_or("Not a WIFI: code")?;
+
+    let mut wifi = WifiNetwork::default();
+    for (key, value) in split_fields(body) {
+        let value = unquote(&value);
+        let optional = Some(value.clone()).filter(|v| !v.is_empty());
+        match key.as_str() {
+            "S" => wifi.ssid = value,
+            "T" => wifi.security = value,
+            "P" => wifi.password = optional,
+            "H" => wifi.hidden = value.eq_ignore_ascii_case("true"),
+            "E" => wifi.eap_method = optional,
+            "I" => wifi.identity = optional,
+            "A" => wifi.anonymous_identity = optional,
+            "PH2" => wifi.phase2 = optional,
+            _ => {}
+        }
+    }
+
+    if wifi.ssid.is_empty() {
+        return Err("Wi-Fi code has no network name (S)".to_string());
+    }
+
+    wifi.security = match wifi.security.to_uppercase().as_str() {
+        "" | "NOPASS" | "NONE" | "OPEN" if wifi.password.is_none() => "nopass".to_string(),
+        // Codes that omit T but carry a password are almost always WPA
+        "" => "WPA".to_string(),
+        "WPA2" | "WPA/WPA2" => "WPA".to_string(),
+        "WPA3" => "SAE".to_string(),
+        other => other.to_string(),
+    };
+
+    Ok(wifi)
+}
+
+/// Some generators wrap the SSID or password in double quotes
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .filter(|v| !v.is_empty())
+        .unwrap_or(value)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wifi_standard_order() {
+        let wifi = parse_wifi("WIFI:T:WPA;S:Home Network;P:secret123;H:false;;").unwrap();
+        assert_eq!(wifi.ssid, "Home Network");
+        assert_eq!(wifi.security, "WPA");
+        assert_eq!(wifi.password, Some("secret123".to_string()));
+        assert!(!wifi.hidden);
+    }
+
+    #[test]
+    fn test_parse_wifi_unusual_order_and_escapes() {
+        let wifi = parse_wifi("wifi:P:pa\\;ss\\:word;H:true;S:\"Caf\\\\e\";T:WEP;;").unwrap();
+        assert_eq!(wifi.ssid, "Caf\\e");
+        assert_eq!(wifi.security, "WEP");
+        assert_eq!(wifi.password, Some("pa;ss:word".to_string()));
+        assert!(wifi.hidden);
+    }
+
+    #[test]
+    fn test_parse_wifi_security_defaults() {
+        assert_eq!(parse_wifi("WIFI:S:Open;;").unwrap().security, "nopass");
+        assert_eq!(parse_wifi("WIFI:S:Net;P:pw;;").unwrap().security, "WPA");
+        assert_eq!(parse_wifi("WIFI:T:WPA3;S:Net;P:pw;;").unwrap().security, "SAE");
+    }
+
+    #[test]
+    fn test_parse_wifi_enterprise() {
+        let wifi = parse_wifi("WIFI:T:WPA2-EAP;S:Corp;E:PEAP;PH2:MSCHAPV2;I:alice;P:pw;;").unwrap();
+        assert_eq!(wifi.security, "WPA2-EAP");
+        assert_eq!(wifi.eap_method, Some("PEAP".to_string()));
+        assert_eq!(wifi.phase2, Some("MSCHAPV2".to_string()));
+        assert_eq!(wifi.identity, Some("alice".to_string()));
+    }
+
+    #[test]
+    fn test_parse_wifi_requires_ssid() {
+        assert!(parse_wifi("WIFI:T:WPA;P:pw;;").is_err());
+    }
+}
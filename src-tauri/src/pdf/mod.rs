@@ -0,0 +1,129 @@
+use image::DynamicImage;
+use pdfium_render::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Resolution used when the caller doesn't ask for one
+pub const DEFAULT_DPI: f32 = 150.0;
+
+/// Accepted resolution range. Small codes on A4 need ~150 dpi; 600 is plenty for any print.
+pub const MIN_DPI: f32 = 36.0;
+pub const MAX_DPI: f32 = 600.0;
+
+/// Longest side of a rendered page, to keep poster-sized pages in memory
+const MAX_PAGE_PIXELS: f32 = 12_000.0;
+
+/// Whether a path looks like a PDF by its extension
+pub fn is_pdf_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+}
+
+/// Whether bytes start with the PDF header
+pub fn is_pdf_data(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF-")
+}
+
+/// Clamp a requested DPI into the supported range
+pub fn clamp_dpi(dpi: Option<f32>) -> f32 {
+    match dpi {
+        Some(dpi) if dpi.is_finite() => dpi.clamp(MIN_DPI, MAX_DPI),
+        _ => DEFAULT_DPI,
+    }
+}
+
+/// Rasterise every page of a PDF at `dpi`, calling `visit` with the 1-based page
+/// number and the image. Pages are rendered one at a time. Returns the page count.
+pub fn for_each_page(
+    bytes: &[u8],
+    dpi: f32,
+    mut visit: impl FnMut(u32, DynamicImage),
+) -> Result<u32, String> {
+    let pdfium = bind_pdfium()?;
+    let document = pdfium
+        .load_pdf_from_byte_slice(bytes, None)
+        .map_err(|e| match e {
+            PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) => {
+                "PDF is password protected".to_string()
+            }
+            other => format!("Failed to open PDF: {}", other),
+        })?;
+
+    let pages = document.pages();
+    for (index, page) in pages.iter().enumerate() {
+        let scale = dpi / 72.0;
+        let width = page.width().value * scale;
+        let height = page.height().value * scale;
+        // Shrink oversized pages rather than failing on them
+        let fit = (MAX_PAGE_PIXELS / width.max(height)).min(1.0);
+
+        let config = PdfRenderConfig::new()
+            .set_target_width((width * fit).round().max(1.0) as Pixels)
+            .set_target_height((height * fit).round().max(1.0) as Pixels);
+
+        let bitmap = page
+            .render_with_config(&config)
+            .map_err(|e| format!("Failed to render page {}: {}", index + 1, e))?;
+
+        visit(index as u32 + 1, bitmap.as_image());
+    }
+
+    Ok(pages.len() as u32)
+}
+
+/// Load PDFium from the bundle's `pdfium` resources (see
+/// scripts/fetch-pdfium.mjs), falling back to a copy next to the app binary
+/// and then to a system-wide install
+fn bind_pdfium() -> Result<Pdfium, String> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    // Resource directories: next to the binary on Windows and in development
+    // builds, Contents/Resources on macOS and /usr/lib/<product name> on Linux
+    let candidates: Vec<PathBuf> = exe_dir
+        .iter()
+        .flat_map(|dir| {
+            [
+                dir.join("pdfium"),
+                dir.join("../Resources/pdfium"),
+                dir.join("../lib/QR Foundry/pdfium"),
+                dir.clone(),
+                dir.join("../lib"),
+            ]
+        })
+        .map(|dir| Pdfium::pdfium_platform_library_name_at_path(&dir))
+        .collect();
+
+    candidates
+        .iter()
+        .find_map(|path| Pdfium::bind_to_library(path).ok())
+        .or_else(|| Pdfium::bind_to_system_library().ok())
+        .map(Pdfium::new)
+        .ok_or_else(|| {
+            "PDF support needs the PDFium library, which could not be found".to_string()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_pdf() {
+        assert!(is_pdf_path("/tmp/Flyer.PDF"));
+        assert!(!is_pdf_path("/tmp/flyer.png"));
+        assert!(!is_pdf_path("/tmp/pdf"));
+        assert!(is_pdf_data(b"%PDF-1.7\n..."));
+        assert!(!is_pdf_data(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_clamp_dpi() {
+        assert_eq!(clamp_dpi(None), DEFAULT_DPI);
+        assert_eq!(clamp_dpi(Some(300.0)), 300.0);
+        assert_eq!(clamp_dpi(Some(5000.0)), MAX_DPI);
+        assert_eq!(clamp_dpi(Some(1.0)), MIN_DPI);
+        assert_eq!(clamp_dpi(Some(f32::NAN)), DEFAULT_DPI);
+    }
+}
@@ -0,0 +1,282 @@
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, Luma};
+
+/// Strategies in the order they are tried. Cheap fixes for common photo
+/// problems come first; cropping is the most expensive and goes last.
+pub const STRATEGIES: &[&str] = &[
+    "original",
+    "contrast",
+    "adaptive-threshold",
+    "inverted",
+    "red-channel",
+    "green-channel",
+    "blue-channel",
+    "upscaled",
+    "cropped",
+];
+
+/// Images with a shorter side than this are upscaled before retrying
+const SMALL_IMAGE_SIDE: u32 = 400;
+
+/// Size of the cells used to look for busy, high-contrast regions
+const REGION_CELL: u32 = 24;
+
+/// Most regions cropped out of one image
+const MAX_REGIONS: usize = 4;
+
+/// Build the images for one strategy. Strategies that can't help (an
+/// already high-contrast image, a large image for "upscaled") yield nothing.
+pub fn apply(strategy: &str, img: &DynamicImage) -> Vec<GrayImage> {
+    match strategy {
+        "original" => vec![img.to_luma8()],
+        "contrast" => stretch_contrast(&img.to_luma8()).into_iter().collect(),
+        "adaptive-threshold" => vec![adaptive_threshold(&img.to_luma8())],
+        "inverted" => {
+            let mut gray = img.to_luma8();
+            imageops::invert(&mut gray);
+            vec![gray]
+        }
+        "red-channel" => vec![channel(img, 0)],
+        "green-channel" => vec![channel(img, 1)],
+        "blue-channel" => vec![channel(img, 2)],
+        "upscaled" => upscale(&img.to_luma8()).into_iter().collect(),
+        "cropped" => {
+            let gray = img.to_luma8();
+            candidate_regions(&gray)
+                .into_iter()
+                .map(|(x, y, w, h)| {
+                    let crop = imageops::crop_imm(&gray, x, y, w, h).to_image();
+                    upscale(&crop).unwrap_or(crop)
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// Stretch the 1st–99th percentile of brightness to the full range, or
+/// `None` when the image already uses most of it
+fn stretch_contrast(gray: &GrayImage) -> Option<GrayImage> {
+    let mut histogram = [0u64; 256];
+    for p in gray.pixels() {
+        histogram[p[0] as usize] += 1;
+    }
+
+    let total = gray.pixels().len() as u64;
+    let percentile = |fraction: f64| {
+        let target = (total as f64 * fraction) as u64;
+        let mut seen = 0;
+        histogram
+            .iter()
+            .position(|&count| {
+                seen += count;
+                seen > target
+            })
+            .unwrap_or(255) as u8
+    };
+    let (low, high) = (percentile(0.01), percentile(0.99));
+    if high <= low || (low < 16 && high > 240) {
+        return None;
+    }
+
+    let range = (high - low) as f32;
+    let mut out = gray.clone();
+    for p in out.pixels_mut() {
+        let v = (p[0].clamp(low, high) - low) as f32 / range;
+        p[0] = (v * 255.0).round() as u8;
+    }
+    Some(out)
+}
+
+/// Binarise against the mean of each pixel's neighbourhood, which evens out
+/// glare and shadows across the code
+fn adaptive_threshold(gray: &GrayImage) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let radius = (width.min(height) / 16).max(7) as i64;
+    let stride = width as usize + 1;
+
+    // Summed-area table with a zero row and column
+    let mut integral = vec![0u64; stride * (height as usize + 1)];
+    for y in 0..height as usize {
+        let mut row = 0u64;
+        for x in 0..width as usize {
+            row += gray.get_pixel(x as u32, y as u32)[0] as u64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row;
+        }
+    }
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let clamp = |v: i64, max: u32| v.clamp(0, max as i64) as usize;
+        let (x0, x1) = (clamp(x as i64 - radius, width), clamp(x as i64 + radius + 1, width));
+        let (y0, y1) = (clamp(y as i64 - radius, height), clamp(y as i64 + radius + 1, height));
+
+        let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+            - integral[y0 * stride + x1]
+            - integral[y1 * stride + x0];
+        let mean = sum / ((x1 - x0) * (y1 - y0)) as u64;
+
+        // A small bias keeps flat areas white instead of speckled
+        let dark = (gray.get_pixel(x, y)[0] as u64) + 8 < mean;
+        Luma([if dark { 0 } else { 255 }])
+    })
+}
+
+/// One colour channel as a grayscale image, for colored codes whose
+/// brightness barely differs from the background
+fn channel(img: &DynamicImage, index: usize) -> GrayImage {
+    let rgb = img.to_rgb8();
+    GrayImage::from_fn(rgb.width(), rgb.height(), |x, y| {
+        Luma([rgb.get_pixel(x, y)[index]])
+    })
+}
+
+/// Enlarge small images so each module spans several pixels
+fn upscale(gray: &GrayImage) -> Option<GrayImage> {
+    let side = gray.width().min(gray.height());
+    if side == 0 || side >= SMALL_IMAGE_SIDE {
+        return None;
+    }
+    let factor = SMALL_IMAGE_SIDE.div_ceil(side).min(8);
+    Some(imageops::resize(
+        gray,
+        gray.width() * factor,
+        gray.height() * factor,
+        FilterType::Triangle,
+    ))
+}
+
+/// Bounding boxes (x, y, width, height) of busy, high-contrast areas, largest
+/// first. Codes are dense in edges, unlike most of a typical photo.
+fn candidate_regions(gray: &GrayImage) -> Vec<(u32, u32, u32, u32)> {
+    let (cols, rows) = (gray.width() / REGION_CELL, gray.height() / REGION_CELL);
+    if cols < 2 || rows < 2 {
+        return vec![];
+    }
+
+    let busy: Vec<bool> = (0..rows * cols)
+        .map(|i| {
+            let (cx, cy) = ((i % cols) * REGION_CELL, (i / cols) * REGION_CELL);
+            let (mut min, mut max, mut edges) = (255u8, 0u8, 0u32);
+            for y in cy..cy + REGION_CELL {
+                let mut previous = gray.get_pixel(cx, y)[0];
+                for x in cx..cx + REGION_CELL {
+                    let v = gray.get_pixel(x, y)[0];
+                    min = min.min(v);
+                    max = max.max(v);
+                    if v.abs_diff(previous) > 40 {
+                        edges += 1;
+                    }
+                    previous = v;
+                }
+            }
+            max - min > 64 && edges >= REGION_CELL
+        })
+        .collect();
+
+    // Group neighbouring busy cells into regions
+    let mut seen = vec![false; busy.len()];
+    let mut regions = Vec::new();
+    for start in 0..busy.len() {
+        if !busy[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut stack = vec![start];
+        let (mut x0, mut y0, mut x1, mut y1) = (cols, rows, 0, 0);
+        let mut cells = 0;
+
+        while let Some(i) = stack.pop() {
+            let (cx, cy) = (i as u32 % cols, i as u32 / cols);
+            (x0, y0, x1, y1) = (x0.min(cx), y0.min(cy), x1.max(cx), y1.max(cy));
+            cells += 1;
+
+            let neighbours = [
+                (cx > 0).then(|| i - 1),
+                (cx + 1 < cols).then(|| i + 1),
+                (cy > 0).then(|| i - cols as usize),
+                (cy + 1 < rows).then(|| i + cols as usize),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if busy[n] && !seen[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        // A code needs at least a few cells; single specks are noise
+        if cells >= 4 {
+            regions.push((cells, x0, y0, x1, y1));
+        }
+    }
+
+    regions.sort_by_key(|r| std::cmp::Reverse(r.0));
+    regions
+        .into_iter()
+        .take(MAX_REGIONS)
+        .map(|(_, x0, y0, x1, y1)| {
+            // Pad by one cell to keep the quiet zone
+            let x = x0.saturating_sub(1) * REGION_CELL;
+            let y = y0.saturating_sub(1) * REGION_CELL;
+            let right = ((x1 + 2) * REGION_CELL).min(gray.width());
+            let bottom = ((y1 + 2) * REGION_CELL).min(gray.height());
+            (x, y, right - x, bottom - y)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stretch_contrast() {
+        let dim = GrayImage::from_fn(10, 10, |x, _| Luma([if x < 5 { 100 } else { 140 }]));
+        let stretched = stretch_contrast(&dim).unwrap();
+        assert_eq!(stretched.get_pixel(0, 0)[0], 0);
+        assert_eq!(stretched.get_pixel(9, 0)[0], 255);
+
+        let full = GrayImage::from_fn(10, 10, |x, _| Luma([if x < 5 { 0 } else { 255 }]));
+        assert!(stretch_contrast(&full).is_none());
+        assert!(stretch_contrast(&GrayImage::from_pixel(4, 4, Luma([90]))).is_none());
+    }
+
+    #[test]
+    fn test_adaptive_threshold_evens_out_gradient() {
+        // A dark module on a background fading from black to white
+        let img = GrayImage::from_fn(200, 100, |x, y| {
+            let background = (x * 255 / 199) as u8;
+            let inside = (45..55).contains(&x) && (45..55).contains(&y);
+            Luma([if inside { background / 3 } else { background }])
+        });
+        let binary = adaptive_threshold(&img);
+
+        assert_eq!(binary.get_pixel(50, 50)[0], 0);
+        assert_eq!(binary.get_pixel(150, 10)[0], 255);
+        assert_eq!(binary.get_pixel(20, 10)[0], 255);
+    }
+
+    #[test]
+    fn test_candidate_regions_find_busy_area() {
+        // A checkerboard patch in the lower right of a flat image
+        let img = GrayImage::from_fn(480, 480, |x, y| {
+            let patch = x >= 288 && y >= 288 && x < 432 && y < 432;
+            let dark = patch && ((x / 6) + (y / 6)) % 2 == 0;
+            Luma([if dark { 0 } else { 230 }])
+        });
+
+        let regions = candidate_regions(&img);
+        assert_eq!(regions.len(), 1);
+        let (x, y, w, h) = regions[0];
+        assert!(x <= 288 && y <= 288);
+        assert!(x + w >= 432 && y + h >= 432);
+        assert!(w < 240 && h < 240);
+    }
+
+    #[test]
+    fn test_unknown_or_useless_strategies_yield_nothing() {
+        let large = DynamicImage::ImageLuma8(GrayImage::from_pixel(500, 500, Luma([255])));
+        assert!(apply("upscaled", &large).is_empty());
+        assert!(apply("sharpen", &large).is_empty());
+        assert_eq!(apply("original", &large).len(), 1);
+    }
+}
@@ -0,0 +1,764 @@
+use crate::micro::Fit;
+use crate::render::{self, ModuleMatrix};
+use encoding_rs::Encoding;
+use qrcode::bits::Bits;
+use qrcode::canvas::{Canvas, MaskPattern};
+use qrcode::ec;
+use qrcode::optimize::{total_encoded_len, Segment};
+use qrcode::types::{Color, Mode, QrError};
+use qrcode::{EcLevel, Version};
+use rqrr::{BitGrid, Grid};
+use rxing::qrcode::decoder::qrcode_decoder;
+use serde::{Deserialize, Serialize};
+
+mod segment;
+pub use segment::*;
+
+/// Character sets that can be declared with an ECI header, with their designators
+pub const CHARSETS: &[(&str, u32)] = &[
+    ("iso-8859-1", 3),
+    ("iso-8859-2", 4),
+    ("iso-8859-5", 7),
+    ("shift-jis", 20),
+    ("windows-1251", 22),
+    ("windows-1252", 23),
+    ("utf-8", 26),
+    ("big5", 28),
+    ("gb18030", 29),
+    ("euc-kr", 30),
+];
+
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// QR mask patterns, by their number in the format information
+const MASKS: [MaskPattern; 8] = [
+    MaskPattern::Checkerboard,
+    MaskPattern::HorizontalLines,
+    MaskPattern::VerticalLines,
+    MaskPattern::DiagonalLines,
+    MaskPattern::LargeCheckerboard,
+    MaskPattern::Fields,
+    MaskPattern::Diamonds,
+    MaskPattern::Meadow,
+];
+
+/// Segment mode, character set, mask and error correction choices for QR codes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QrOptions {
+    pub mode: Option<String>,    // "auto" (default) | "byte" | "kanji"
+    pub charset: Option<String>, // byte data encoding, see CHARSETS; "utf-8" by default
+    pub eci: Option<bool>, // by default declared only for non-ASCII bytes in a charset other than UTF-8
+    pub mask: Option<u8>,  // 0..=7; the lowest penalty score wins by default
+    pub boost_ec: Option<bool>, // raise the error correction level as far as the version allows
+}
+
+/// An encoded QR code and the choices made for it
+#[derive(Debug, Clone)]
+pub struct QrSymbol {
+    pub matrix: ModuleMatrix,
+    pub fit: Fit,
+    pub ec_level: EcLevel, // after any boost
+    pub mask: u8,
+}
+
+/// Text read from a QR code and the character set an ECI header declared
+#[derive(Debug, Clone, PartialEq)]
+pub struct QrText {
+    pub content: String,
+    pub eci: Option<&'static str>,
+}
+
+/// Canonical name and ECI designator of a character set
+fn charset(name: &str) -> Result<(&'static str, u32), String> {
+    let name = name.trim().to_lowercase().replace('_', "-");
+    let name = match name.as_str() {
+        "utf8" => "utf-8",
+        "sjis" | "shiftjis" => "shift-jis",
+        "latin1" | "latin-1" => "iso-8859-1",
+        other => other,
+    };
+    CHARSETS
+        .iter()
+        .find(|(charset, _)| *charset == name)
+        .copied()
+        .ok_or_else(|| format!("Unsupported character set '{}'", name))
+}
+
+fn charset_for_eci(designator: u32) -> Option<&'static str> {
+    match designator {
+        // ECI 1 is the older designator for ISO-8859-1
+        1 => Some("iso-8859-1"),
+        _ => CHARSETS
+            .iter()
+            .find(|(_, eci)| *eci == designator)
+            .map(|(name, _)| *name),
+    }
+}
+
+/// encoding_rs codec for a charset; ISO-8859-1 is handled by hand because
+/// encoding_rs treats it as Windows-1252
+fn encoding(charset: &str) -> Option<&'static Encoding> {
+    match charset {
+        "iso-8859-2" => Some(encoding_rs::ISO_8859_2),
+        "iso-8859-5" => Some(encoding_rs::ISO_8859_5),
+        "shift-jis" => Some(encoding_rs::SHIFT_JIS),
+        "windows-1251" => Some(encoding_rs::WINDOWS_1251),
+        "windows-1252" => Some(encoding_rs::WINDOWS_1252),
+        "utf-8" => Some(encoding_rs::UTF_8),
+        "big5" => Some(encoding_rs::BIG5),
+        "gb18030" => Some(encoding_rs::GB18030),
+        "euc-kr" => Some(encoding_rs::EUC_KR),
+        _ => None,
+    }
+}
+
+/// Encode one character, failing when the charset can't represent it
+fn encode_char(c: char, charset: &str) -> Result<Vec<u8>, String> {
+    let unrepresentable = || format!("'{}' can't be encoded in {}", c, charset.to_uppercase());
+    match encoding(charset) {
+        Some(encoding) => {
+            let mut buffer = [0; 4];
+            let (bytes, _, had_errors) = encoding.encode(c.encode_utf8(&mut buffer));
+            if had_errors {
+                return Err(unrepresentable());
+            }
+            Ok(bytes.into_owned())
+        }
+        None => u8::try_from(u32::from(c))
+            .map(|b| vec![b])
+            .map_err(|_| unrepresentable()),
+    }
+}
+
+fn decode_bytes(bytes: &[u8], charset: &str) -> Option<String> {
+    match encoding(charset) {
+        Some(encoding) => encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.into_owned()),
+        None => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+    }
+}
+
+/// Shift JIS double-byte characters that Kanji mode can hold
+fn is_kanji(bytes: &[u8]) -> bool {
+    let [high, low] = bytes else {
+        return false;
+    };
+    let code = u16::from(*high) << 8 | u16::from(*low);
+    ((0x8140..=0x9ffc).contains(&code) || (0xe040..=0xebbf).contains(&code)) && *low != 0x7f
+}
+
+/// Encode content as a QR code at the given error correction level. Byte
+/// data is written in `options.charset`; "kanji" mode writes Shift JIS and
+/// puts its double-byte characters in Kanji mode segments. Content is split
+/// into the segments that take the fewest bits. The smallest version that
+/// holds the content is used unless `version` names one, "1" to "40".
+pub fn encode(
+    content: &str,
+    ec_level: EcLevel,
+    version: Option<&str>,
+    options: &QrOptions,
+) -> Result<QrSymbol, String> {
+    let version = version.map(parse_version).transpose()?;
+    let mask = match options.mask {
+        Some(mask) if mask > 7 => {
+            return Err(format!(
+                "Mask {} doesn't exist; QR masks run from 0 to 7",
+                mask
+            ))
+        }
+        mask => mask,
+    };
+    let plan = plan(content, ec_level, version, options)?;
+    let qr_version = Version::Normal(plan.version);
+    let encode_error = |e: QrError| format!("Failed to encode QR code: {}", e);
+
+    let data = plan.bits().map_err(encode_error)?.into_bytes();
+    let (data, ec_codewords) =
+        ec::construct_codewords(&data, qr_version, plan.ec_level).map_err(encode_error)?;
+    let mut canvas = Canvas::new(qr_version, plan.ec_level);
+    canvas.draw_all_functional_patterns();
+    canvas.draw_data(&data, &ec_codewords);
+    let mask = mask.map_or_else(|| best_mask(&canvas), usize::from);
+    canvas.apply_mask(MASKS[mask]);
+
+    let width = qr_version.width() as usize;
+    let modules = canvas
+        .into_colors()
+        .into_iter()
+        .map(|c| c == Color::Dark)
+        .collect();
+    Ok(QrSymbol {
+        matrix: ModuleMatrix::new(width, width, modules),
+        fit: Fit {
+            version: plan.version.to_string(),
+            width,
+            height: width,
+            capacity_bits: capacity(qr_version, plan.ec_level),
+            used_bits: Some(plan.len),
+        },
+        ec_level: plan.ec_level,
+        mask: mask as u8,
+    })
+}
+
+/// Data bits for content, with the version and mask left to the qrcode crate
+pub fn encode_bits(content: &str, ec_level: EcLevel, options: &QrOptions) -> Result<Bits, String> {
+    plan(content, ec_level, None, options)?
+        .bits()
+        .map_err(|e| format!("Failed to encode QR code: {}", e))
+}
+
+fn parse_version(version: &str) -> Result<i16, String> {
+    version
+        .trim()
+        .parse()
+        .ok()
+        .filter(|v| (1..=40).contains(v))
+        .ok_or_else(|| format!("Unknown QR version '{}', expected 1 to 40", version))
+}
+
+/// The mask the qrcode crate picks, with the lowest penalty score
+fn best_mask(canvas: &Canvas) -> usize {
+    let best = canvas.apply_best_mask().into_colors();
+    MASKS
+        .iter()
+        .position(|&mask| {
+            let mut masked = canvas.clone();
+            masked.apply_mask(mask);
+            masked.into_colors() == best
+        })
+        .unwrap_or(0)
+}
+
+/// Content laid out in segments for the smallest version that holds it
+struct Plan {
+    version: i16,
+    ec_level: EcLevel,
+    eci: Option<u32>,
+    data: Vec<u8>,
+    segments: Vec<Segment>,
+    modes: Vec<Mode>, // one per character
+    len: usize,       // data bits before the terminator
+}
+
+impl Plan {
+    fn bits(&self) -> Result<Bits, QrError> {
+        let mut bits = Bits::new(Version::Normal(self.version));
+        if let Some(eci) = self.eci {
+            bits.push_eci_designator(eci)?;
+        }
+        bits.push_segments(&self.data, self.segments.iter().copied())?;
+        bits.push_terminator(self.ec_level)?;
+        Ok(bits)
+    }
+}
+
+fn plan(
+    content: &str,
+    ec_level: EcLevel,
+    version: Option<i16>,
+    options: &QrOptions,
+) -> Result<Plan, String> {
+    let mode = options.mode.as_deref().map(|m| m.trim().to_lowercase());
+    let requested = options.charset.as_deref().map(charset).transpose()?;
+
+    let (charset, designator) = match mode.as_deref().unwrap_or("auto") {
+        "kanji" => match requested {
+            Some((name, _)) if name != "shift-jis" => {
+                return Err("Kanji mode always uses Shift JIS".to_string());
+            }
+            _ => charset("shift-jis")?,
+        },
+        "auto" | "byte" => requested.unwrap_or(("utf-8", 26)),
+        other => {
+            return Err(format!(
+                "Unknown QR mode '{}', expected auto, byte or kanji",
+                other
+            ))
+        }
+    };
+
+    let (data, mut chars) = char_units(content, charset)?;
+    if mode.as_deref() == Some("byte") {
+        chars.iter_mut().for_each(|c| c.mode = Mode::Byte);
+    }
+
+    // Character count fields only change size at versions 10 and 27
+    for versions in [1..=9, 10..=26, 27..=40] {
+        let versions = match version {
+            Some(v) if versions.contains(&v) => v..=v,
+            Some(_) => continue,
+            None => versions,
+        };
+        let modes = segment::optimal_modes(&chars, Version::Normal(*versions.start()));
+        let segments = segment::merge(&chars, &modes);
+        let needs_eci = charset != "utf-8"
+            && segments
+                .iter()
+                .any(|s| s.mode == Mode::Byte && !data[s.begin..s.end].is_ascii());
+        let eci = options.eci.unwrap_or(needs_eci).then_some(designator);
+        let len = |v| eci.map_or(0, eci_len) + total_encoded_len(&segments, Version::Normal(v));
+
+        if let Some(v) = versions
+            .clone()
+            .find(|&v| fits(len(v), Version::Normal(v), ec_level))
+        {
+            let len = len(v);
+            let ec_level = if options.boost_ec.unwrap_or(false) {
+                boosted(ec_level, |level| fits(len, Version::Normal(v), level))
+            } else {
+                ec_level
+            };
+            return Ok(Plan {
+                version: v,
+                ec_level,
+                eci,
+                data,
+                segments,
+                modes,
+                len,
+            });
+        }
+        if let Some(v) = version {
+            let needed = plan(content, ec_level, None, options).map(|plan| plan.version);
+            return Err(match needed {
+                Ok(needed) => format!(
+                    "Content needs {} data bits but QR version {} holds {} at error correction level {}; use version {} or higher",
+                    len(v),
+                    v,
+                    capacity(Version::Normal(v), ec_level),
+                    render::ec_level_name(ec_level),
+                    needed
+                ),
+                Err(e) => e,
+            });
+        }
+    }
+    Err(format!(
+        "Failed to encode QR code: {}",
+        QrError::DataTooLong
+    ))
+}
+
+/// The highest error correction level from `ec_level` up that still fits
+fn boosted(ec_level: EcLevel, fits: impl Fn(EcLevel) -> bool) -> EcLevel {
+    [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H]
+        .into_iter()
+        .filter(|&level| level >= ec_level && fits(level))
+        .last()
+        .unwrap_or(ec_level)
+}
+
+/// Data bits a version holds at an error correction level
+fn capacity(version: Version, ec_level: EcLevel) -> usize {
+    Bits::new(version).max_len(ec_level).unwrap_or(0)
+}
+
+/// Whether data bits fit a version at an error correction level
+fn fits(bits: usize, version: Version, ec_level: EcLevel) -> bool {
+    Bits::new(version)
+        .max_len(ec_level)
+        .is_ok_and(|max| bits <= max)
+}
+
+/// Bits an ECI header takes
+fn eci_len(designator: u32) -> usize {
+    4 + match designator {
+        0..=127 => 8,
+        128..=16383 => 16,
+        _ => 24,
+    }
+}
+
+/// Encode content into one segment per character, in the narrowest mode the
+/// character allows. Kanji mode is only used when the whole stream is Shift
+/// JIS, so that merging never mixes two encodings in one byte segment.
+fn char_units(content: &str, charset: &str) -> Result<(Vec<u8>, Vec<Segment>), String> {
+    let mut data = Vec::new();
+    let mut chars = Vec::new();
+
+    for c in content.chars() {
+        let bytes = encode_char(c, charset)?;
+        let mode = if c.is_ascii_digit() {
+            Mode::Numeric
+        } else if c.is_ascii() && ALPHANUMERIC.contains(&(c as u8)) {
+            Mode::Alphanumeric
+        } else if charset == "shift-jis" && is_kanji(&bytes) {
+            Mode::Kanji
+        } else {
+            Mode::Byte
+        };
+
+        let begin = data.len();
+        data.extend(bytes);
+        chars.push(Segment {
+            mode,
+            begin,
+            end: data.len(),
+        });
+    }
+    Ok((data, chars))
+}
+
+/// Modules of a grid located by rqrr
+pub fn grid_modules(grid: &impl BitGrid) -> ModuleMatrix {
+    let size = grid.size();
+    // rqrr's bit() takes the row first
+    ModuleMatrix::new(
+        size,
+        size,
+        (0..size * size)
+            .map(|i| grid.bit(i / size, i % size))
+            .collect(),
+    )
+}
+
+/// Decode a grid located by rqrr, falling back to rqrr's own decoder when
+/// the data can't be read segment by segment
+pub fn decode_grid<G: BitGrid>(grid: &Grid<G>) -> Result<QrText, String> {
+    decode_modules(&grid_modules(&grid.grid)).or_else(|_| {
+        grid.decode()
+            .map(|(_, content)| QrText { content, eci: None })
+            .map_err(|e| format!("Failed to decode QR: {:?}", e))
+    })
+}
+
+/// Error correction level a grid located by rqrr was written at, read from
+/// its format information
+pub fn grid_ec_level<G: BitGrid>(grid: &Grid<G>) -> Option<EcLevel> {
+    // The two format bits: L is 01, M 00, Q 11 and H 10
+    let bits = grid.decode().ok()?.0.ecc_level;
+    [EcLevel::M, EcLevel::L, EcLevel::H, EcLevel::Q]
+        .get(bits as usize)
+        .copied()
+}
+
+/// Decode a QR code from its modules, honouring ECI headers and Kanji mode
+pub fn decode_modules(modules: &ModuleMatrix) -> Result<QrText, String> {
+    let size = modules.width();
+    if size < 21 || !(size - 17).is_multiple_of(4) {
+        return Err(format!("{} modules is not a QR code size", size));
+    }
+    let rows: Vec<Vec<bool>> = (0..size)
+        .map(|y| (0..size).map(|x| modules.is_dark(x, y)).collect())
+        .collect();
+    let decoded = qrcode_decoder::decode_bool_array(&rows)
+        .map_err(|e| format!("Failed to decode QR: {}", e))?;
+    decode_data(decoded.getRawBytes(), (size - 17) / 4)
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize, // in bits
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: usize) -> Result<u32, String> {
+        if self.position + count > self.data.len() * 8 {
+            return Err("QR data ends in the middle of a segment".to_string());
+        }
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = self.data[self.position / 8] >> (7 - self.position % 8) & 1;
+            value = value << 1 | u32::from(bit);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+}
+
+/// Decode the error-corrected data codewords of a symbol. Byte data without
+/// an ECI header is read as UTF-8 when valid, then Shift JIS, then ISO-8859-1.
+fn decode_data(data: &[u8], version: usize) -> Result<QrText, String> {
+    let class = match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        _ => 2,
+    };
+    let mut reader = BitReader { data, position: 0 };
+    let mut content = String::new();
+    let mut eci = None;
+
+    while reader.remaining() >= 4 {
+        match reader.read(4)? {
+            0 => break,
+            // Numeric
+            1 => {
+                let mut count = reader.read([10, 12, 14][class])? as usize;
+                while count > 0 {
+                    let digits = count.min(3);
+                    let value = reader.read([0, 4, 7, 10][digits])?;
+                    content.push_str(&format!("{:0width$}", value, width = digits));
+                    count -= digits;
+                }
+            }
+            // Alphanumeric
+            2 => {
+                let mut count = reader.read([9, 11, 13][class])? as usize;
+                while count > 0 {
+                    let chars = count.min(2);
+                    let value = reader.read([0, 6, 11][chars])? as usize;
+                    let indexes = if chars == 2 {
+                        vec![value / 45, value % 45]
+                    } else {
+                        vec![value]
+                    };
+                    for index in indexes {
+                        let c = ALPHANUMERIC.get(index).ok_or("Invalid alphanumeric data")?;
+                        content.push(*c as char);
+                    }
+                    count -= chars;
+                }
+            }
+            // Byte
+            4 => {
+                let count = reader.read([8, 16, 16][class])? as usize;
+                let bytes = (0..count)
+                    .map(|_| reader.read(8).map(|b| b as u8))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let text = match eci {
+                    Some(charset) => decode_bytes(&bytes, charset).ok_or_else(|| {
+                        format!("Byte data is not valid {}", charset.to_uppercase())
+                    })?,
+                    None => ["utf-8", "shift-jis", "iso-8859-1"]
+                        .iter()
+                        .find_map(|charset| decode_bytes(&bytes, charset))
+                        .unwrap_or_default(),
+                };
+                content.push_str(&text);
+            }
+            // Kanji, always Shift JIS
+            8 => {
+                let count = reader.read([8, 10, 12][class])? as usize;
+                let mut bytes = Vec::with_capacity(count * 2);
+                for _ in 0..count {
+                    let value = reader.read(13)?;
+                    let packed = (value / 0xc0) << 8 | (value % 0xc0);
+                    let code = if packed + 0x8140 <= 0x9ffc {
+                        packed + 0x8140
+                    } else {
+                        packed + 0xc140
+                    };
+                    bytes.extend([(code >> 8) as u8, code as u8]);
+                }
+                content.push_str(&decode_bytes(&bytes, "shift-jis").ok_or("Invalid Kanji data")?);
+            }
+            // ECI
+            7 => {
+                let first = reader.read(8)?;
+                let designator = if first & 0x80 == 0 {
+                    first
+                } else if first & 0xc0 == 0x80 {
+                    (first & 0x3f) << 8 | reader.read(8)?
+                } else {
+                    (first & 0x1f) << 16 | reader.read(16)?
+                };
+                eci = Some(
+                    charset_for_eci(designator)
+                        .ok_or_else(|| format!("Unsupported ECI {}", designator))?,
+                );
+            }
+            // Structured Append header, FNC1 in first and second position
+            3 => {
+                reader.read(16)?;
+            }
+            5 => {}
+            9 => {
+                reader.read(8)?;
+            }
+            mode => return Err(format!("Unsupported QR segment mode {}", mode)),
+        }
+    }
+
+    Ok(QrText { content, eci })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &str, options: &QrOptions) -> QrText {
+        let symbol = encode(content, EcLevel::M, None, options).unwrap();
+        decode_modules(&symbol.matrix).unwrap()
+    }
+
+    fn options(mode: &str, charset: Option<&str>, eci: Option<bool>) -> QrOptions {
+        QrOptions {
+            mode: Some(mode.to_string()),
+            charset: charset.map(str::to_string),
+            eci,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_utf8_round_trip_without_eci() {
+        for content in [
+            "HELLO 123",
+            "https://example.com/a?b=1",
+            "日本語のテキスト",
+            "Größe: 42 €",
+        ] {
+            let text = round_trip(content, &QrOptions::default());
+            assert_eq!(
+                text,
+                QrText {
+                    content: content.to_string(),
+                    eci: None
+                },
+                "{}",
+                content
+            );
+        }
+        let text = round_trip("日本語", &options("byte", None, Some(true)));
+        assert_eq!(text.eci, Some("utf-8"));
+    }
+
+    #[test]
+    fn test_kanji_mode_is_smaller() {
+        let content = "東京都千代田区丸の内一丁目九番一号";
+        let utf8 = encode(content, EcLevel::M, None, &QrOptions::default()).unwrap();
+        let kanji = encode(content, EcLevel::M, None, &options("kanji", None, None)).unwrap();
+        assert!(kanji.matrix.width() < utf8.matrix.width());
+
+        // Only double-byte characters go in Kanji mode; no ECI is needed
+        let text = round_trip("品番 A-100 東京", &options("kanji", None, None));
+        assert_eq!(
+            text,
+            QrText {
+                content: "品番 A-100 東京".to_string(),
+                eci: None
+            }
+        );
+
+        assert!(
+            encode("Größe", EcLevel::M, None, &options("kanji", None, None))
+                .is_err_and(|e| e.contains("SHIFT-JIS"))
+        );
+        assert!(encode(
+            "東京",
+            EcLevel::M,
+            None,
+            &options("kanji", Some("utf-8"), None)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_other_charsets_declare_eci() {
+        for (content, charset) in [
+            ("Größe", "iso-8859-1"),
+            ("半角ｶﾅ", "shift-jis"),
+            ("Привет", "windows-1251"),
+            ("中文内容", "gb18030"),
+            ("한국어", "euc-kr"),
+        ] {
+            let text = round_trip(content, &options("byte", Some(charset), None));
+            assert_eq!(
+                text,
+                QrText {
+                    content: content.to_string(),
+                    eci: Some(charset)
+                },
+                "{}",
+                charset
+            );
+        }
+        assert!(encode(
+            "中文",
+            EcLevel::M,
+            None,
+            &options("auto", Some("iso-8859-1"), None)
+        )
+        .is_err());
+        assert!(encode(
+            "x",
+            EcLevel::M,
+            None,
+            &options("auto", Some("ebcdic"), None)
+        )
+        .is_err());
+        assert!(encode("x", EcLevel::M, None, &options("hanzi", None, None)).is_err());
+    }
+
+    #[test]
+    fn test_undeclared_shift_jis_is_recognised() {
+        let symbol = encode(
+            "ｶﾀｶﾅ",
+            EcLevel::M,
+            None,
+            &options("byte", Some("shift-jis"), Some(false)),
+        )
+        .unwrap();
+        let text = decode_modules(&symbol.matrix).unwrap();
+        assert_eq!(
+            text,
+            QrText {
+                content: "ｶﾀｶﾅ".to_string(),
+                eci: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_forced_version_and_mask() {
+        let options = QrOptions {
+            mask: Some(5),
+            ..Default::default()
+        };
+        let symbol = encode("SKU-0042", EcLevel::M, Some("7"), &options).unwrap();
+        assert_eq!(symbol.matrix.width(), 45);
+        assert_eq!(symbol.fit.version, "7");
+        assert_eq!(symbol.mask, 5);
+        assert_eq!(decode_modules(&symbol.matrix).unwrap().content, "SKU-0042");
+
+        // The same content and choices always give the same modules
+        let again = encode("SKU-0042", EcLevel::M, Some("7"), &options).unwrap();
+        assert_eq!(again.matrix, symbol.matrix);
+        let other = encode("SKU-0042", EcLevel::M, Some("7"), &QrOptions::default()).unwrap();
+        assert_ne!(other.mask, 5);
+        assert_ne!(other.matrix, symbol.matrix);
+
+        let error = encode(&"x".repeat(100), EcLevel::H, Some("3"), &options).unwrap_err();
+        assert!(error.contains("use version 10 or higher"), "{}", error);
+        assert!(encode("x", EcLevel::M, Some("41"), &options).is_err());
+        assert!(encode("x", EcLevel::M, Some("M3"), &options).is_err());
+        let options = QrOptions {
+            mask: Some(8),
+            ..Default::default()
+        };
+        assert!(encode("x", EcLevel::M, None, &options).is_err());
+    }
+
+    #[test]
+    fn test_boost_ec_keeps_the_version() {
+        let options = QrOptions {
+            boost_ec: Some(true),
+            ..Default::default()
+        };
+        // 6 bytes fit version 1 at every level
+        let symbol = encode("ABC123", EcLevel::L, None, &options).unwrap();
+        assert_eq!(
+            (symbol.fit.version.as_str(), symbol.ec_level),
+            ("1", EcLevel::H)
+        );
+
+        // 40 bytes need version 3 at L, which holds them up to Q
+        let content = "https://example.com/products/item-000042";
+        let plain = encode(content, EcLevel::L, None, &QrOptions::default()).unwrap();
+        let symbol = encode(content, EcLevel::L, None, &options).unwrap();
+        assert_eq!(symbol.fit.version, plain.fit.version);
+        assert!(symbol.ec_level > EcLevel::L);
+        assert!(symbol.fit.fits());
+        assert_eq!(decode_modules(&symbol.matrix).unwrap().content, content);
+
+        let symbol = encode(content, EcLevel::L, Some("10"), &options).unwrap();
+        assert_eq!(symbol.ec_level, EcLevel::H);
+    }
+}
@@ -0,0 +1,280 @@
+use super::{eci_len, fits, plan, QrOptions};
+use qrcode::optimize::{total_encoded_len, Segment};
+use qrcode::types::Mode;
+use qrcode::{EcLevel, Version};
+use serde::{Deserialize, Serialize};
+
+/// Modes a segment can be in while the optimiser walks the content. Numeric
+/// and alphanumeric states also track how many characters sit past the last
+/// full group, since a group's size in bits depends on how full it is.
+const STATES: [(Mode, usize); 7] = [
+    (Mode::Numeric, 0),
+    (Mode::Numeric, 1),
+    (Mode::Numeric, 2),
+    (Mode::Alphanumeric, 0),
+    (Mode::Alphanumeric, 1),
+    (Mode::Byte, 0),
+    (Mode::Kanji, 0),
+];
+
+/// How QR content was split into segments and what that saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentReport {
+    pub segments: Vec<SegmentSummary>,
+    pub version: i16,
+    pub total_bits: usize, // ECI header and every segment, before the terminator
+    pub byte_bits: usize,  // the same data as one byte segment in the same version
+    pub bits_saved: usize,
+    pub byte_version: Option<i16>, // smallest version one byte segment fits, None if none does
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentSummary {
+    pub mode: String, // "numeric" | "alphanumeric" | "byte" | "kanji"
+    pub text: String,
+    pub bits: usize, // including the mode indicator and character count
+}
+
+/// Whether a character whose narrowest mode is `narrowest` can be written in `mode`
+fn allows(narrowest: Mode, mode: Mode) -> bool {
+    narrowest == mode
+        || mode == Mode::Byte
+        || (narrowest == Mode::Numeric && mode == Mode::Alphanumeric)
+}
+
+/// Bits a character adds to a segment that is already open
+fn continued_bits(state: (Mode, usize), len: usize) -> usize {
+    match state {
+        (Mode::Numeric, 1) => 4,
+        (Mode::Numeric, _) => 3,
+        (Mode::Alphanumeric, 1) => 6,
+        (Mode::Alphanumeric, _) => 5,
+        (Mode::Byte, _) => 8 * len,
+        (Mode::Kanji, _) => 13,
+    }
+}
+
+/// The mode each character should be written in so that the segments take
+/// the fewest bits in `version`. `chars` holds one segment per character in
+/// the narrowest mode it allows.
+pub(crate) fn optimal_modes(chars: &[Segment], version: Version) -> Vec<Mode> {
+    let mut cost: [Option<usize>; 7] = [None; 7];
+    // For each character, the state the previous character was in
+    let mut previous: Vec<[usize; 7]> = Vec::with_capacity(chars.len());
+
+    for (i, c) in chars.iter().enumerate() {
+        let len = c.end - c.begin;
+        let mut next = [None; 7];
+        let mut from = [0; 7];
+
+        for (state, &(mode, filled)) in STATES.iter().enumerate() {
+            if !allows(c.mode, mode) {
+                continue;
+            }
+            let added = continued_bits((mode, filled), len);
+            // Continue the open segment: a numeric state with one digit past
+            // the last group follows the state with none, and so on
+            let before = match (mode, filled) {
+                (Mode::Numeric, 0) => 2,
+                (Mode::Numeric, 1) => 0,
+                (Mode::Numeric, _) => 1,
+                (Mode::Alphanumeric, 0) => 4,
+                (Mode::Alphanumeric, _) => 3,
+                _ => state,
+            };
+            if let Some(bits) = cost[before].filter(|_| i > 0) {
+                next[state] = Some(bits + added);
+                from[state] = before;
+            }
+            // Or start a new segment after one in another mode
+            let starts = matches!(
+                (mode, filled),
+                (Mode::Numeric | Mode::Alphanumeric, 1) | (Mode::Byte | Mode::Kanji, _)
+            );
+            if !starts {
+                continue;
+            }
+            let header = 4 + mode.length_bits_count(version) + added;
+            let best = if i == 0 {
+                Some((0, 0))
+            } else {
+                (0..STATES.len())
+                    .filter(|&s| STATES[s].0 != mode)
+                    .filter_map(|s| cost[s].map(|bits| (bits, s)))
+                    .min()
+            };
+            if let Some((bits, s)) = best {
+                if next[state].is_none_or(|current| bits + header < current) {
+                    next[state] = Some(bits + header);
+                    from[state] = s;
+                }
+            }
+        }
+        cost = next;
+        previous.push(from);
+    }
+
+    let Some(mut state) = (0..STATES.len())
+        .filter(|&s| cost[s].is_some())
+        .min_by_key(|&s| cost[s])
+    else {
+        return Vec::new();
+    };
+    let mut modes = vec![Mode::Byte; chars.len()];
+    for i in (0..chars.len()).rev() {
+        modes[i] = STATES[state].0;
+        state = previous[i][state];
+    }
+    modes
+}
+
+/// Join consecutive characters written in the same mode into segments
+pub(crate) fn merge(chars: &[Segment], modes: &[Mode]) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for (c, &mode) in chars.iter().zip(modes) {
+        match segments.last_mut() {
+            Some(last) if last.mode == mode => last.end = c.end,
+            _ => segments.push(Segment {
+                mode,
+                begin: c.begin,
+                end: c.end,
+            }),
+        }
+    }
+    segments
+}
+
+fn mode_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Numeric => "numeric",
+        Mode::Alphanumeric => "alphanumeric",
+        Mode::Byte => "byte",
+        Mode::Kanji => "kanji",
+    }
+}
+
+/// Segments the encoder would use for content, compared with writing it all
+/// as one byte segment
+pub fn segment_report(
+    content: &str,
+    ec_level: EcLevel,
+    options: &QrOptions,
+) -> Result<SegmentReport, String> {
+    let plan = plan(content, ec_level, None, options)?;
+    let version = Version::Normal(plan.version);
+
+    let mut texts: Vec<String> = Vec::new();
+    let mut last = None;
+    for (c, &mode) in content.chars().zip(&plan.modes) {
+        match texts.last_mut() {
+            Some(text) if last == Some(mode) => text.push(c),
+            _ => texts.push(c.to_string()),
+        }
+        last = Some(mode);
+    }
+    let segments = plan
+        .segments
+        .iter()
+        .zip(texts)
+        .map(|(segment, text)| SegmentSummary {
+            mode: mode_name(segment.mode).to_string(),
+            text,
+            bits: segment.encoded_len(version),
+        })
+        .collect();
+
+    let eci_bits = plan.eci.map_or(0, eci_len);
+    let byte = Segment {
+        mode: Mode::Byte,
+        begin: 0,
+        end: plan.data.len(),
+    };
+    let byte_bits = |version| eci_bits + byte.encoded_len(version);
+    let total_bits = eci_bits + total_encoded_len(&plan.segments, version);
+
+    Ok(SegmentReport {
+        segments,
+        version: plan.version,
+        total_bits,
+        byte_bits: byte_bits(version),
+        bits_saved: byte_bits(version).saturating_sub(total_bits),
+        byte_version: (1..=40)
+            .find(|&v| fits(byte_bits(Version::Normal(v)), Version::Normal(v), ec_level)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{char_units, decode_modules, encode};
+    use super::*;
+    use qrcode::optimize::Optimizer;
+
+    fn modes(report: &SegmentReport) -> Vec<(&str, &str)> {
+        report
+            .segments
+            .iter()
+            .map(|s| (s.mode.as_str(), s.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_numeric_run_in_url() {
+        let content = "https://ex.co/o/20250101000123456789";
+        let report = segment_report(content, EcLevel::M, &QrOptions::default()).unwrap();
+        assert_eq!(
+            modes(&report),
+            vec![
+                ("byte", "https://ex.co/o/"),
+                ("numeric", "20250101000123456789")
+            ]
+        );
+        // 20 digits take 160 bits as bytes and 81 as a numeric segment
+        assert_eq!(report.bits_saved, 160 - 81);
+        assert!(report.version < report.byte_version.unwrap());
+
+        let symbol = encode(content, EcLevel::M, None, &QrOptions::default()).unwrap();
+        assert_eq!(symbol.fit.version, report.version.to_string());
+        assert_eq!(decode_modules(&symbol.matrix).unwrap().content, content);
+    }
+
+    #[test]
+    fn test_short_runs_stay_in_the_open_segment() {
+        let report =
+            segment_report("Order 12 of 34 shipped", EcLevel::M, &QrOptions::default()).unwrap();
+        assert_eq!(modes(&report), vec![("byte", "Order 12 of 34 shipped")]);
+        assert_eq!(report.bits_saved, 0);
+
+        let report = segment_report("HELLO WORLD 12", EcLevel::M, &QrOptions::default()).unwrap();
+        assert_eq!(modes(&report), vec![("alphanumeric", "HELLO WORLD 12")]);
+    }
+
+    #[test]
+    fn test_never_worse_than_greedy_merging() {
+        for content in [
+            "https://example.com/track/000123456789?ref=ABC",
+            "WIFI:S:Home;T:WPA;P:12345678;;",
+            "1234ABCDabcd5678EFGH",
+            "A1B2C3D4E5F6G7H8",
+            "品番12345東京ABC",
+        ] {
+            let (data, chars) = char_units(content, "shift-jis").unwrap();
+            for version in [1, 10, 27].map(Version::Normal) {
+                let optimal = merge(&chars, &optimal_modes(&chars, version));
+                let greedy: Vec<Segment> = Optimizer::new(
+                    merge(&chars, &chars.iter().map(|c| c.mode).collect::<Vec<_>>()).into_iter(),
+                    version,
+                )
+                .collect();
+                assert!(
+                    total_encoded_len(&optimal, version) <= total_encoded_len(&greedy, version),
+                    "{} in {:?}",
+                    content,
+                    version
+                );
+                assert_eq!(optimal.last().map(|s| s.end), Some(data.len()));
+            }
+        }
+    }
+}
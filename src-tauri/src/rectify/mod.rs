@@ -0,0 +1,184 @@
+use image::{GrayImage, Luma};
+
+/// A projective mapping from a square in code coordinates onto the four
+/// corners of the code as it appears in a photo
+#[derive(Debug, Clone, Copy)]
+pub struct Perspective {
+    h: [f64; 8],
+}
+
+impl Perspective {
+    /// Map the square `[0, side]²` onto `corners`, given as top-left,
+    /// top-right, bottom-right, bottom-left. `None` for degenerate corners.
+    pub fn from_square(side: f64, corners: [(f64, f64); 4]) -> Option<Self> {
+        Self::between([(0.0, 0.0), (side, 0.0), (side, side), (0.0, side)], corners)
+    }
+
+    /// The mapping that takes each of the `from` points to the matching `to` point
+    pub fn between(from: [(f64, f64); 4], to: [(f64, f64); 4]) -> Option<Self> {
+        // Eight equations, one pair per point, with the last coefficient fixed at 1
+        let mut rows = [[0.0f64; 9]; 8];
+        for (i, (&(x, y), &(u, v))) in from.iter().zip(&to).enumerate() {
+            rows[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+            rows[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+        }
+
+        solve(rows).map(|h| Self { h })
+    }
+
+    /// Image position of a point in code coordinates
+    pub fn map(&self, x: f64, y: f64) -> (f64, f64) {
+        let h = &self.h;
+        let w = h[6] * x + h[7] * y + 1.0;
+        ((h[0] * x + h[1] * y + h[2]) / w, (h[3] * x + h[4] * y + h[5]) / w)
+    }
+}
+
+/// Gaussian elimination with partial pivoting on an augmented 8x9 system
+fn solve(mut rows: [[f64; 9]; 8]) -> Option<[f64; 8]> {
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&a, &b| rows[a][col].abs().total_cmp(&rows[b][col].abs()))?;
+        if rows[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        rows.swap(col, pivot);
+
+        let pivot_row = rows[col];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != col {
+                let factor = row[col] / pivot_row[col];
+                for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(col) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+
+    let mut h = [0.0; 8];
+    for (i, value) in h.iter_mut().enumerate() {
+        *value = rows[i][8] / rows[i][i];
+    }
+    Some(h)
+}
+
+/// Resample the square from `start` to `start + span` in code coordinates into
+/// an upright image with `scale` pixels per unit. Areas outside the photo are white.
+pub fn warp(gray: &GrayImage, perspective: &Perspective, start: f64, span: f64, scale: u32) -> GrayImage {
+    let side = (span * scale as f64).round().max(1.0) as u32;
+
+    GrayImage::from_fn(side, side, |x, y| {
+        let u = start + (x as f64 + 0.5) / scale as f64;
+        let v = start + (y as f64 + 0.5) / scale as f64;
+        let (sx, sy) = perspective.map(u, v);
+        Luma([sample(gray, sx - 0.5, sy - 0.5)])
+    })
+}
+
+/// Bilinear sample at a pixel position, white outside the image
+fn sample(gray: &GrayImage, x: f64, y: f64) -> u8 {
+    let (width, height) = gray.dimensions();
+    if !(x > -1.0 && y > -1.0 && x < width as f64 && y < height as f64) {
+        return 255;
+    }
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let pixel = |px: f64, py: f64| {
+        if px < 0.0 || py < 0.0 || px >= width as f64 || py >= height as f64 {
+            255.0
+        } else {
+            gray.get_pixel(px as u32, py as u32)[0] as f64
+        }
+    };
+
+    let top = pixel(x0, y0) * (1.0 - fx) + pixel(x0 + 1.0, y0) * fx;
+    let bottom = pixel(x0, y0 + 1.0) * (1.0 - fx) + pixel(x0 + 1.0, y0 + 1.0) * fx;
+    (top * (1.0 - fy) + bottom * fy).round() as u8
+}
+
+/// Black and white version of an image, split at Otsu's threshold
+pub fn binarise(gray: &GrayImage) -> GrayImage {
+    let threshold = otsu_threshold(gray);
+    GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+        Luma([if gray.get_pixel(x, y)[0] <= threshold { 0 } else { 255 }])
+    })
+}
+
+/// The brightness that best separates dark from light pixels
+fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for p in gray.pixels() {
+        histogram[p[0] as usize] += 1;
+    }
+
+    let total = gray.pixels().len() as f64;
+    let sum_all: f64 = histogram.iter().enumerate().map(|(v, &c)| v as f64 * c as f64).sum();
+    let (mut weight_dark, mut sum_dark) = (0.0, 0.0);
+    let (mut best, mut best_variance) = (127u8, -1.0);
+
+    for (value, &count) in histogram.iter().enumerate() {
+        weight_dark += count as f64;
+        sum_dark += value as f64 * count as f64;
+        let weight_light = total - weight_dark;
+        if weight_dark == 0.0 || weight_light == 0.0 {
+            continue;
+        }
+
+        let mean_dark = sum_dark / weight_dark;
+        let mean_light = (sum_all - sum_dark) / weight_light;
+        let variance = weight_dark * weight_light * (mean_dark - mean_light).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = value as u8;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_perspective_maps_corners() {
+        let corners = [(10.0, 20.0), (110.0, 30.0), (120.0, 140.0), (5.0, 125.0)];
+        let perspective = Perspective::from_square(10.0, corners).unwrap();
+
+        for (point, expected) in [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)].iter().zip(corners) {
+            let (x, y) = perspective.map(point.0, point.1);
+            assert!((x - expected.0).abs() < 1e-6 && (y - expected.1).abs() < 1e-6);
+        }
+
+        assert!(Perspective::from_square(10.0, [(0.0, 0.0); 4]).is_none());
+    }
+
+    #[test]
+    fn test_warp_straightens_skewed_square() {
+        // A dark parallelogram on white, leaning to the right
+        let photo = GrayImage::from_fn(200, 120, |x, y| {
+            let left = 40 + y / 2;
+            let inside = (20..100).contains(&y) && x >= left && x < left + 80;
+            Luma([if inside { 30 } else { 220 }])
+        });
+        let corners = [(50.0, 20.0), (130.0, 20.0), (170.0, 100.0), (90.0, 100.0)];
+        let perspective = Perspective::from_square(1.0, corners).unwrap();
+
+        // Include a quarter of the square's width around it
+        let upright = binarise(&warp(&photo, &perspective, -0.25, 1.5, 40));
+        assert_eq!(upright.dimensions(), (60, 60));
+        for (x, y) in [(12, 12), (47, 12), (30, 30), (12, 47), (47, 47)] {
+            assert_eq!(upright.get_pixel(x, y)[0], 0);
+        }
+        for (x, y) in [(3, 3), (56, 3), (30, 5), (5, 56), (56, 56)] {
+            assert_eq!(upright.get_pixel(x, y)[0], 255);
+        }
+    }
+
+    #[test]
+    fn test_binarise_splits_two_levels() {
+        let img = GrayImage::from_fn(10, 1, |x, _| Luma([if x < 5 { 70 } else { 160 }]));
+        let binary = binarise(&img);
+        assert_eq!(binary.get_pixel(0, 0)[0], 0);
+        assert_eq!(binary.get_pixel(9, 0)[0], 255);
+    }
+}
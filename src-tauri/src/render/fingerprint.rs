@@ -0,0 +1,130 @@
+use crate::qr::QrOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// Changes whenever the renderers change their output for the same content
+/// and style, so that older fingerprints stop matching
+const RENDERER_VERSION: &str = "qr-foundry-render-2";
+
+/// What a code was rendered as and with which encoder choices. Inputs the
+/// caller doesn't know are left as `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderTarget {
+    pub symbology: String, // see symbology::SYMBOLOGIES
+    pub format: String,    // "png" | "svg"
+    pub module_size: Option<u32>,
+    pub ec_level: Option<String>,
+    pub version: Option<String>,
+    pub mask: Option<u8>,
+    pub gs1: bool,
+    pub qr: Option<QrOptions>,
+}
+
+/// Style JSON with object keys sorted, insignificant whitespace removed and
+/// whole-number floats written as integers. Text that isn't JSON is only
+/// trimmed.
+pub fn normalize_style_json(style_json: &str) -> String {
+    match serde_json::from_str::<Value>(style_json) {
+        Ok(value) => normalize(value).to_string(),
+        Err(_) => style_json.trim().to_string(),
+    }
+}
+
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(entries.into_iter().map(|(key, value)| (key, normalize(value))).collect::<Map<_, _>>())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize).collect()),
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() && float.fract() == 0.0 && float.abs() < 9_007_199_254_740_992.0 => {
+                Value::from(float as i64)
+            }
+            _ => Value::Number(number),
+        },
+        other => other,
+    }
+}
+
+/// Hex SHA-256 of the renderer version, the content, the normalized style
+/// and the render target. Renders with the same fingerprint produce
+/// byte-identical files as far as the target records every encoder choice.
+pub fn render_fingerprint(content: &str, style_json: &str, target: &RenderTarget) -> String {
+    let style = normalize_style_json(style_json);
+    let target = serde_json::to_string(target).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(RENDERER_VERSION);
+    // Length prefixes keep the fields from running into each other
+    for field in [content, style.as_str(), target.as_str()] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_style_json() {
+        assert_eq!(
+            normalize_style_json(r##"{ "size": 512.0, "colors": { "fg": "#000", "bg": "#fff" }, "margin": 4.5 }"##),
+            r##"{"colors":{"bg":"#fff","fg":"#000"},"margin":4.5,"size":512}"##
+        );
+        assert_eq!(normalize_style_json("  not json "), "not json");
+    }
+
+    fn qr_png() -> RenderTarget {
+        RenderTarget {
+            symbology: "qr".to_string(),
+            format: "png".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_fingerprint() {
+        let target = qr_png();
+        let a = render_fingerprint("https://example.com", r#"{"size":512,"ecLevel":"M"}"#, &target);
+        let b = render_fingerprint("https://example.com", "{\n  \"ecLevel\": \"M\",\n  \"size\": 512.0\n}", &target);
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+
+        assert_ne!(a, render_fingerprint("https://example.org", r#"{"size":512,"ecLevel":"M"}"#, &target));
+        assert_ne!(a, render_fingerprint("https://example.com", r#"{"size":256,"ecLevel":"M"}"#, &target));
+        // Content and style can't trade characters
+        assert_ne!(render_fingerprint("ab", "c", &target), render_fingerprint("a", "bc", &target));
+    }
+
+    #[test]
+    fn test_render_fingerprint_covers_target() {
+        let fingerprint = |target: &RenderTarget| render_fingerprint("LOT-7", "{}", target);
+        let base = fingerprint(&qr_png());
+
+        let targets = [
+            RenderTarget { symbology: "data-matrix".to_string(), ..qr_png() },
+            RenderTarget { format: "svg".to_string(), ..qr_png() },
+            RenderTarget { module_size: Some(8), ..qr_png() },
+            RenderTarget { ec_level: Some("H".to_string()), ..qr_png() },
+            RenderTarget { version: Some("5".to_string()), ..qr_png() },
+            RenderTarget { mask: Some(3), ..qr_png() },
+            RenderTarget { gs1: true, ..qr_png() },
+            RenderTarget {
+                qr: Some(QrOptions {
+                    charset: Some("shift-jis".to_string()),
+                    ..Default::default()
+                }),
+                ..qr_png()
+            },
+        ];
+        for target in &targets {
+            assert_ne!(fingerprint(target), base, "{:?}", target);
+        }
+        assert_eq!(fingerprint(&qr_png()), base);
+    }
+}
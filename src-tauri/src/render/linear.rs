@@ -0,0 +1,255 @@
+use super::font::{self, GLYPH_HEIGHT};
+use super::{encode_png, fmt_number, pdf};
+use crate::barcode::Barcode;
+use image::{GrayImage, Luma};
+
+/// Narrowest bar of an EAN-13 at 100% magnification
+pub const DEFAULT_BAR_WIDTH_MM: f64 = 0.33;
+
+/// Bar height of an EAN-13 at 100% magnification
+pub const DEFAULT_BAR_HEIGHT_MM: f64 = 22.85;
+
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// Printed geometry of a linear barcode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarLayout {
+    pub bar_width_mm: f64, // narrowest bar, the module width
+    pub height_mm: f64,    // bars only, without the text line
+    pub show_text: bool,
+}
+
+impl Default for BarLayout {
+    fn default() -> Self {
+        Self {
+            bar_width_mm: DEFAULT_BAR_WIDTH_MM,
+            height_mm: DEFAULT_BAR_HEIGHT_MM,
+            show_text: true,
+        }
+    }
+}
+
+impl BarLayout {
+    /// Reject sizes no printer or scanner can cope with
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.1..=2.0).contains(&self.bar_width_mm) {
+            return Err("Bar width must be between 0.1 and 2 mm".to_string());
+        }
+        if !(2.0..=200.0).contains(&self.height_mm) {
+            return Err("Bar height must be between 2 and 200 mm".to_string());
+        }
+        Ok(())
+    }
+
+    /// Gap between the bars and the text line
+    fn text_gap_mm(&self) -> f64 {
+        self.bar_width_mm * 2.0
+    }
+
+    /// Height of the text line, scaled with the bars like printed OCR-B
+    fn text_size_mm(&self) -> f64 {
+        self.bar_width_mm * 9.0
+    }
+
+    /// Overall width and height, quiet zones and text line included
+    pub fn size_mm(&self, barcode: &Barcode) -> (f64, f64) {
+        let modules = barcode.bars.len() + 2 * barcode.kind.quiet_zone();
+        let text = if self.show_text { self.text_gap_mm() + self.text_size_mm() } else { 0.0 };
+        (modules as f64 * self.bar_width_mm, self.height_mm + text)
+    }
+}
+
+/// Each bar as (first module, width in modules), quiet zone included
+fn bar_runs(barcode: &Barcode) -> Vec<(usize, usize)> {
+    let offset = barcode.kind.quiet_zone();
+    let mut runs = Vec::new();
+    let mut x = 0;
+    while x < barcode.bars.len() {
+        let start = x;
+        while x < barcode.bars.len() && barcode.bars[x] == barcode.bars[start] {
+            x += 1;
+        }
+        if barcode.bars[start] {
+            runs.push((offset + start, x - start));
+        }
+    }
+    runs
+}
+
+/// Render a barcode as SVG sized in millimetres
+pub fn render_barcode_svg(barcode: &Barcode, layout: &BarLayout) -> String {
+    let (width, height) = layout.size_mm(barcode);
+    let module = layout.bar_width_mm;
+
+    let path: String = bar_runs(barcode)
+        .into_iter()
+        .map(|(x, w)| format!("M{} 0h{}v{}h-{}z", x, w, fmt_number(layout.height_mm / module, 4), w))
+        .collect();
+
+    let text = if layout.show_text {
+        let escaped = barcode
+            .text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        format!(
+            r##"<text x="{}" y="{}" font-family="OCR-B, monospace" font-size="{}" text-anchor="middle" fill="#000">{}</text>"##,
+            fmt_number(width / 2.0, 4),
+            fmt_number(height - layout.text_size_mm() * 0.2, 4),
+            fmt_number(layout.text_size_mm(), 4),
+            escaped
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}mm" height="{h}mm" viewBox="0 0 {w} {h}">"#,
+            r##"<rect width="{w}" height="{h}" fill="#fff"/>"##,
+            r##"<path transform="scale({scale})" fill="#000" shape-rendering="crispEdges" d="{path}"/>"##,
+            "{text}",
+            "</svg>"
+        ),
+        w = fmt_number(width, 4),
+        h = fmt_number(height, 4),
+        scale = fmt_number(module, 6),
+        path = path,
+        text = text,
+    )
+}
+
+/// Render a barcode as PNG at the given resolution. Bars are a whole number
+/// of pixels wide so that every module keeps the same width.
+pub fn render_barcode_png(barcode: &Barcode, layout: &BarLayout, dpi: u32) -> Result<Vec<u8>, String> {
+    if !(72..=1200).contains(&dpi) {
+        return Err("DPI must be between 72 and 1200".to_string());
+    }
+
+    let px_per_mm = dpi as f64 / 25.4;
+    let module = ((layout.bar_width_mm * px_per_mm).round() as u32).max(1);
+    let modules = (barcode.bars.len() + 2 * barcode.kind.quiet_zone()) as u32;
+    let bar_height = ((layout.height_mm * px_per_mm).round() as u32).max(1);
+    let gap = ((layout.text_gap_mm() * px_per_mm).round() as u32).max(1);
+    let text_height = (layout.text_size_mm() * px_per_mm).round() as u32;
+    let height = if layout.show_text { bar_height + gap + text_height } else { bar_height };
+
+    let mut img = GrayImage::from_pixel(modules * module, height, Luma([255]));
+    for (x, w) in bar_runs(barcode) {
+        for px in x as u32 * module..(x + w) as u32 * module {
+            for py in 0..bar_height {
+                img.put_pixel(px, py, Luma([0]));
+            }
+        }
+    }
+    if layout.show_text {
+        // Glyphs fill about three quarters of the line, like capitals
+        let scale = (text_height * 3 / 4 / GLYPH_HEIGHT).max(1);
+        let center = img.width() / 2;
+        font::draw_text(&mut img, &barcode.text, center, bar_height + gap, scale);
+    }
+
+    encode_png(&img)
+}
+
+/// Render a barcode as a one-page PDF the size of the barcode, with vector
+/// bars and the text in the built-in Courier font
+pub fn render_barcode_pdf(barcode: &Barcode, layout: &BarLayout) -> Vec<u8> {
+    let (width, height) = layout.size_mm(barcode);
+    let pt = |mm: f64| fmt_number(mm * PT_PER_MM, 3);
+    let module = layout.bar_width_mm;
+
+    let mut content = String::from("0 g\n");
+    for (x, w) in bar_runs(barcode) {
+        content.push_str(&format!(
+            "{} {} {} {} re\n",
+            pt(x as f64 * module),
+            pt(height - layout.height_mm),
+            pt(w as f64 * module),
+            pt(layout.height_mm)
+        ));
+    }
+    content.push_str("f\n");
+
+    if layout.show_text {
+        let size = layout.text_size_mm();
+        let escaped: String = barcode
+            .text
+            .chars()
+            .map(|c| match c {
+                '(' | ')' | '\\' => format!("\\{}", c),
+                ' '..='~' => c.to_string(),
+                _ => "?".to_string(),
+            })
+            .collect();
+        // Courier glyphs are all 0.6 em wide
+        let text_width = barcode.text.chars().count() as f64 * size * 0.6;
+        content.push_str(&format!(
+            "BT /F1 {} Tf {} {} Td ({}) Tj ET\n",
+            pt(size),
+            pt((width - text_width) / 2.0),
+            pt(size * 0.2),
+            escaped
+        ));
+    }
+
+    let objects = [
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R >>",
+            pt(width),
+            pt(height)
+        )
+        .into_bytes(),
+        pdf::stream("", content.as_bytes()),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_vec(),
+    ];
+    pdf::write_pdf(&objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::barcode::{self, BarcodeType};
+
+    fn ean() -> Barcode {
+        barcode::encode(BarcodeType::Ean13, "400638133393", false).unwrap()
+    }
+
+    #[test]
+    fn test_barcode_svg_size() {
+        let svg = render_barcode_svg(&ean(), &BarLayout::default());
+        // (95 + 2 * 11) modules of 0.33 mm; 22.85 mm bars plus 0.66 + 2.97 mm text
+        assert!(svg.contains(r#"width="38.61mm" height="26.48mm""#), "{}", svg);
+        assert!(svg.contains(">4006381333931</text>"));
+
+        let layout = BarLayout {
+            show_text: false,
+            ..Default::default()
+        };
+        assert!(!render_barcode_svg(&ean(), &layout).contains("<text"));
+    }
+
+    #[test]
+    fn test_barcode_png_reads_back() {
+        let png = render_barcode_png(&ean(), &BarLayout::default(), 300).unwrap();
+        let img = image::load_from_memory(&png).unwrap().to_luma8();
+        // 0.33 mm at 300 dpi rounds to 4 px per module
+        assert_eq!(img.width(), 117 * 4);
+        assert_eq!(crate::symbology::read(&img), vec![("ean-13", "4006381333931".to_string())]);
+        assert!(render_barcode_png(&ean(), &BarLayout::default(), 20).is_err());
+    }
+
+    #[test]
+    fn test_barcode_pdf_structure() {
+        let pdf = String::from_utf8(render_barcode_pdf(&ean(), &BarLayout::default())).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("(4006381333931) Tj"));
+        // The cross-reference table points at each object
+        let xref: usize = pdf.split("startxref\n").nth(1).unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n0 6\n"));
+        let first = pdf[xref..].lines().nth(3).unwrap()[..10].parse::<usize>().unwrap();
+        assert!(pdf[first..].starts_with("1 0 obj"));
+    }
+}
@@ -0,0 +1,216 @@
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{ExtendedColorType, GrayImage, ImageEncoder};
+use qrcode::{Color, EcLevel, QrCode};
+
+pub mod fingerprint;
+mod font;
+pub mod linear;
+pub mod pdf;
+pub mod swiss;
+
+pub use fingerprint::*;
+pub use linear::*;
+pub use swiss::*;
+
+/// PNG settings are pinned rather than left to the image crate's defaults,
+/// so the same modules always give the same bytes
+const PNG_COMPRESSION: CompressionType = CompressionType::Best;
+const PNG_FILTER: FilterType = FilterType::Adaptive;
+
+/// A grid of dark and light modules, independent of how it was encoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleMatrix {
+    width: usize,
+    height: usize,
+    modules: Vec<bool>,
+}
+
+impl ModuleMatrix {
+    pub fn new(width: usize, height: usize, modules: Vec<bool>) -> Self {
+        assert_eq!(modules.len(), width * height, "module count must match dimensions");
+        Self {
+            width,
+            height,
+            modules,
+        }
+    }
+
+    pub fn from_qr(code: &QrCode) -> Self {
+        let width = code.width();
+        let modules = code.to_colors().into_iter().map(|c| c == Color::Dark).collect();
+        Self::new(width, width, modules)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.modules[y * self.width + x]
+    }
+}
+
+/// Parse an error correction level ("L", "M", "Q" or "H")
+pub fn parse_ec_level(level: &str) -> Result<EcLevel, String> {
+    match level.trim().to_uppercase().as_str() {
+        "L" => Ok(EcLevel::L),
+        "M" => Ok(EcLevel::M),
+        "Q" => Ok(EcLevel::Q),
+        "H" => Ok(EcLevel::H),
+        other => Err(format!("Unknown error correction level '{}'", other)),
+    }
+}
+
+/// The letter `parse_ec_level` reads for an error correction level
+pub fn ec_level_name(level: EcLevel) -> &'static str {
+    match level {
+        EcLevel::L => "L",
+        EcLevel::M => "M",
+        EcLevel::Q => "Q",
+        EcLevel::H => "H",
+    }
+}
+
+/// Encode content as a QR code at the given error correction level, with
+/// UTF-8 byte data and no ECI header
+pub fn encode_qr(content: &str, ec_level: EcLevel) -> Result<QrCode, String> {
+    let bits = crate::qr::encode_bits(content, ec_level, &crate::qr::QrOptions::default())?;
+    QrCode::with_bits(bits, ec_level).map_err(|e| format!("Failed to encode QR code: {}", e))
+}
+
+/// SVG path data covering every dark module, in module units.
+/// Adjacent dark modules in a row are merged into one rectangle.
+pub fn svg_path_data(matrix: &ModuleMatrix) -> String {
+    let mut data = String::new();
+
+    for y in 0..matrix.height() {
+        let mut x = 0;
+        while x < matrix.width() {
+            if !matrix.is_dark(x, y) {
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < matrix.width() && matrix.is_dark(x, y) {
+                x += 1;
+            }
+            data.push_str(&format!("M{} {}h{}v1h-{}z", start, y, x - start, x - start));
+        }
+    }
+
+    data
+}
+
+/// Render a module matrix as a plain black-on-white SVG, one unit per module,
+/// with a quiet zone of `quiet_zone` modules on every side. Elements and path
+/// commands always come out in the same order, row by row.
+pub fn render_matrix_svg(matrix: &ModuleMatrix, quiet_zone: usize) -> String {
+    let width = matrix.width() + 2 * quiet_zone;
+    let height = matrix.height() + 2 * quiet_zone;
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" shape-rendering="crispEdges">"#,
+            r##"<rect width="{w}" height="{h}" fill="#fff"/>"##,
+            r##"<path transform="translate({q} {q})" fill="#000" d="{path}"/>"##,
+            "</svg>"
+        ),
+        w = width,
+        h = height,
+        q = quiet_zone,
+        path = svg_path_data(matrix),
+    )
+}
+
+/// Render a module matrix as a black-on-white image with `module_px` pixels
+/// per module and a quiet zone of `quiet_zone` modules
+pub fn render_matrix_image(matrix: &ModuleMatrix, quiet_zone: usize, module_px: u32) -> GrayImage {
+    let width = (matrix.width() + 2 * quiet_zone) as u32 * module_px;
+    let height = (matrix.height() + 2 * quiet_zone) as u32 * module_px;
+
+    GrayImage::from_fn(width, height, |px, py| {
+        let (mx, my) = ((px / module_px) as usize, (py / module_px) as usize);
+        let dark = mx >= quiet_zone && my >= quiet_zone && matrix.is_dark(mx - quiet_zone, my - quiet_zone);
+        image::Luma([if dark { 0 } else { 255 }])
+    })
+}
+
+/// Encode a grayscale image as PNG bytes. Identical images give identical
+/// bytes: compression is fixed and no timestamp or metadata chunks are written.
+pub fn encode_png(img: &GrayImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    PngEncoder::new_with_quality(&mut bytes, PNG_COMPRESSION, PNG_FILTER)
+        .write_image(img.as_raw(), img.width(), img.height(), ExtendedColorType::L8)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(bytes)
+}
+
+/// Format a number for SVG output with fixed precision and no trailing zeros
+fn fmt_number(value: f64, precision: usize) -> String {
+    let rounded = format!("{:.*}", precision, value);
+    if !rounded.contains('.') {
+        return rounded;
+    }
+    rounded
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ec_level() {
+        assert_eq!(parse_ec_level("l").unwrap(), EcLevel::L);
+        assert_eq!(parse_ec_level("M").unwrap(), EcLevel::M);
+        assert_eq!(parse_ec_level(" q ").unwrap(), EcLevel::Q);
+        assert_eq!(parse_ec_level("H").unwrap(), EcLevel::H);
+        assert!(parse_ec_level("X").is_err());
+    }
+
+    #[test]
+    fn test_module_matrix_from_qr() {
+        let code = encode_qr("hello", EcLevel::M).unwrap();
+        let matrix = ModuleMatrix::from_qr(&code);
+
+        assert_eq!(matrix.width(), 21);
+        assert_eq!(matrix.height(), 21);
+        // Finder pattern corners are always dark
+        assert!(matrix.is_dark(0, 0));
+        assert!(matrix.is_dark(20, 0));
+        assert!(matrix.is_dark(0, 20));
+        // Out of bounds reads as light
+        assert!(!matrix.is_dark(21, 0));
+    }
+
+    #[test]
+    fn test_svg_path_data_merges_runs() {
+        let matrix = ModuleMatrix::new(3, 2, vec![true, true, false, false, true, true]);
+        assert_eq!(svg_path_data(&matrix), "M0 0h2v1h-2zM1 1h2v1h-2z");
+    }
+
+    #[test]
+    fn test_png_output_is_reproducible() {
+        let matrix = ModuleMatrix::from_qr(&encode_qr("https://example.com", EcLevel::M).unwrap());
+        let png = encode_png(&render_matrix_image(&matrix, 4, 8)).unwrap();
+        assert_eq!(png, encode_png(&render_matrix_image(&matrix, 4, 8)).unwrap());
+        assert!(!png.windows(4).any(|chunk| chunk == b"tIME"));
+        assert_eq!(render_matrix_svg(&matrix, 4), render_matrix_svg(&matrix, 4));
+    }
+
+    #[test]
+    fn test_fmt_number() {
+        assert_eq!(fmt_number(5.0, 4), "5");
+        assert_eq!(fmt_number(50.0, 0), "50");
+        assert_eq!(fmt_number(24.5, 4), "24.5");
+        assert_eq!(fmt_number(46.0 / 3.0, 4), "15.3333");
+        assert_eq!(fmt_number(46.0 / 57.0, 6), "0.807018");
+    }
+}
@@ -0,0 +1,64 @@
+/// Assemble a PDF file from its objects, numbered from 1 in order. Object 1
+/// must be the document catalog. The cross-reference table is built from
+/// where each object lands in the output.
+pub fn write_pdf(objects: &[Vec<u8>]) -> Vec<u8> {
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+/// A stream object: `entries` go in its dictionary next to the length
+pub fn stream(entries: &str, data: &[u8]) -> Vec<u8> {
+    let separator = if entries.is_empty() { "" } else { " " };
+    [
+        format!("<< {}{}/Length {} >>\nstream\n", entries, separator, data.len()).as_bytes(),
+        data,
+        b"\nendstream",
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xref_points_at_objects() {
+        let objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [] /Count 0 >>".to_vec(),
+            stream("", b"0 g"),
+        ];
+        let pdf = String::from_utf8(write_pdf(&objects)).unwrap();
+
+        let startxref: usize = pdf.lines().rev().nth(1).unwrap().parse().unwrap();
+        let table: Vec<&str> = pdf[startxref..].lines().collect();
+        assert_eq!(&table[..3], ["xref", "0 4", "0000000000 65535 f "]);
+        for (i, entry) in table[3..6].iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)), "object {}", i + 1);
+        }
+        assert!(pdf.contains("<< /Length 3 >>\nstream\n0 g\nendstream"));
+        assert!(pdf.ends_with("%%EOF\n"));
+    }
+}
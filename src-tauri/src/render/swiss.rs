@@ -0,0 +1,211 @@
+use super::{encode_png, encode_qr, fmt_number, svg_path_data, ModuleMatrix};
+use crate::payload::{validate_swiss_qr_bill, SWISS_MAX_VERSION};
+use image::{GrayImage, Luma};
+use qrcode::{EcLevel, Version};
+
+/// Printed size of the Swiss QR Code symbol, excluding the quiet zone
+pub const SWISS_SYMBOL_SIZE_MM: f64 = 46.0;
+
+/// Quiet zone that must be kept free around the symbol
+pub const SWISS_QUIET_ZONE_MM: f64 = 5.0;
+
+/// Size of the Swiss cross overlay, including its white border
+pub const SWISS_CROSS_SIZE_MM: f64 = 7.0;
+
+/// Width of the white border around the black cross square
+const CROSS_BORDER_MM: f64 = 0.5;
+
+/// Cross span and arm width as fractions of the black square,
+/// following the proportions of the Swiss coat of arms (20/32 and 6/32)
+const CROSS_SPAN: f64 = 20.0 / 32.0;
+const CROSS_ARM: f64 = 6.0 / 32.0;
+
+/// Total rendered size: symbol plus quiet zone on every side
+fn canvas_size_mm() -> f64 {
+    SWISS_SYMBOL_SIZE_MM + 2.0 * SWISS_QUIET_ZONE_MM
+}
+
+/// Validate a QR-bill payload and encode it with the mandated parameters
+pub fn encode_swiss_qr(content: &str) -> Result<ModuleMatrix, String> {
+    validate_swiss_qr_bill(content)?;
+
+    let code = encode_qr(content, EcLevel::M)?;
+    if let Version::Normal(version) = code.version() {
+        if version > SWISS_MAX_VERSION {
+            return Err(format!(
+                "QR-bill needs version {}, maximum allowed is {}",
+                version, SWISS_MAX_VERSION
+            ));
+        }
+    }
+
+    Ok(ModuleMatrix::from_qr(&code))
+}
+
+/// Render a QR-bill code as SVG with a 46x46 mm symbol, quiet zone and Swiss cross
+pub fn render_swiss_svg(content: &str) -> Result<String, String> {
+    let matrix = encode_swiss_qr(content)?;
+    let canvas = canvas_size_mm();
+    let scale = SWISS_SYMBOL_SIZE_MM / matrix.width() as f64;
+
+    let center = canvas / 2.0;
+    let black = SWISS_CROSS_SIZE_MM - 2.0 * CROSS_BORDER_MM;
+    let span = black * CROSS_SPAN;
+    let arm = black * CROSS_ARM;
+
+    let rect = |size: f64, fill: &str| {
+        format!(
+            r#"<rect x="{x}" y="{x}" width="{s}" height="{s}" fill="{fill}"/>"#,
+            x = fmt_number(center - size / 2.0, 4),
+            s = fmt_number(size, 4),
+        )
+    };
+    let bar = |width: f64, height: f64| {
+        format!(
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#fff"/>"##,
+            fmt_number(center - width / 2.0, 4),
+            fmt_number(center - height / 2.0, 4),
+            fmt_number(width, 4),
+            fmt_number(height, 4),
+        )
+    };
+
+    Ok(format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}mm" height="{size}mm" viewBox="0 0 {size} {size}">"#,
+            r##"<rect width="{size}" height="{size}" fill="#fff"/>"##,
+            r##"<path transform="translate({quiet} {quiet}) scale({scale})" fill="#000" d="{path}"/>"##,
+            "{outer}{inner}{horizontal}{vertical}",
+            "</svg>"
+        ),
+        size = fmt_number(canvas, 4),
+        quiet = fmt_number(SWISS_QUIET_ZONE_MM, 4),
+        scale = fmt_number(scale, 6),
+        path = svg_path_data(&matrix),
+        outer = rect(SWISS_CROSS_SIZE_MM, "#fff"),
+        inner = rect(black, "#000"),
+        horizontal = bar(span, arm),
+        vertical = bar(arm, span),
+    ))
+}
+
+/// Render a QR-bill code as PNG at the given resolution, keeping the 46x46 mm geometry
+pub fn render_swiss_png(content: &str, dpi: u32) -> Result<Vec<u8>, String> {
+    if !(72..=1200).contains(&dpi) {
+        return Err("DPI must be between 72 and 1200".to_string());
+    }
+
+    let matrix = encode_swiss_qr(content)?;
+    let canvas = canvas_size_mm();
+    let pixels = (canvas / 25.4 * dpi as f64).round() as u32;
+    let mm_per_pixel = canvas / pixels as f64;
+    let modules = matrix.width() as f64;
+
+    let img = GrayImage::from_fn(pixels, pixels, |px, py| {
+        let x = (px as f64 + 0.5) * mm_per_pixel;
+        let y = (py as f64 + 0.5) * mm_per_pixel;
+
+        let dark = match cross_pixel(x - canvas / 2.0, y - canvas / 2.0) {
+            Some(dark) => dark,
+            None => {
+                let sx = (x - SWISS_QUIET_ZONE_MM) / SWISS_SYMBOL_SIZE_MM * modules;
+                let sy = (y - SWISS_QUIET_ZONE_MM) / SWISS_SYMBOL_SIZE_MM * modules;
+                sx >= 0.0 && sy >= 0.0 && matrix.is_dark(sx as usize, sy as usize)
+            }
+        };
+
+        Luma([if dark { 0 } else { 255 }])
+    });
+
+    encode_png(&img)
+}
+
+/// Color of the Swiss cross at an offset from the center, or None outside the cross
+fn cross_pixel(dx: f64, dy: f64) -> Option<bool> {
+    let (dx, dy) = (dx.abs(), dy.abs());
+    let black = SWISS_CROSS_SIZE_MM - 2.0 * CROSS_BORDER_MM;
+
+    if dx > SWISS_CROSS_SIZE_MM / 2.0 || dy > SWISS_CROSS_SIZE_MM / 2.0 {
+        return None;
+    }
+    if dx > black / 2.0 || dy > black / 2.0 {
+        return Some(false);
+    }
+
+    let span = black * CROSS_SPAN / 2.0;
+    let arm = black * CROSS_ARM / 2.0;
+    let in_cross = (dx <= span && dy <= arm) || (dx <= arm && dy <= span);
+
+    Some(!in_cross)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::{build_swiss_qr_bill, SwissAddress, SwissQrBill};
+
+    fn sample_payload() -> String {
+        build_swiss_qr_bill(&SwissQrBill {
+            iban: "CH4431999123000889012".to_string(),
+            creditor: SwissAddress {
+                name: "Robert Schneider AG".to_string(),
+                street: Some("Rue du Lac".to_string()),
+                building_number: Some("1268".to_string()),
+                postal_code: "2501".to_string(),
+                town: "Biel".to_string(),
+                country: "CH".to_string(),
+            },
+            amount: Some("1949.75".to_string()),
+            currency: "CHF".to_string(),
+            reference: Some("210000000003139471430009017".to_string()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encode_swiss_qr_rejects_invalid_payload() {
+        assert!(encode_swiss_qr("SPC\n0200\n1").is_err());
+        assert!(encode_swiss_qr("https://example.com").is_err());
+    }
+
+    #[test]
+    fn test_render_swiss_svg_geometry() {
+        let svg = render_swiss_svg(&sample_payload()).unwrap();
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="56mm" height="56mm" viewBox="0 0 56 56">"#));
+        assert!(svg.contains("translate(5 5)"));
+        // 7 mm white cross background centered on the symbol
+        assert!(svg.contains(r##"<rect x="24.5" y="24.5" width="7" height="7" fill="#fff"/>"##));
+        assert!(svg.contains(r##"<rect x="25" y="25" width="6" height="6" fill="#000"/>"##));
+    }
+
+    #[test]
+    fn test_render_swiss_png_decodes() {
+        let payload = sample_payload();
+        let png = render_swiss_png(&payload, 300).unwrap();
+
+        let img = image::load_from_memory(&png).unwrap().to_luma8();
+        assert_eq!(img.width(), 661); // 56 mm at 300 dpi
+
+        let mut prepared = rqrr::PreparedImage::prepare(img);
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(content, payload);
+    }
+
+    #[test]
+    fn test_render_swiss_png_rejects_bad_dpi() {
+        assert!(render_swiss_png(&sample_payload(), 10).is_err());
+        assert!(render_swiss_png(&sample_payload(), 5000).is_err());
+    }
+
+    #[test]
+    fn test_cross_pixel() {
+        assert_eq!(cross_pixel(10.0, 0.0), None);
+        assert_eq!(cross_pixel(3.4, 0.0), Some(false)); // white border
+        assert_eq!(cross_pixel(2.9, 2.9), Some(true)); // black square corner
+        assert_eq!(cross_pixel(0.0, 0.0), Some(false)); // white cross
+    }
+}
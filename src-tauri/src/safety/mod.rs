@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use unicode_script::{Script, UnicodeScript};
+use url::{Host, Url};
+
+/// Domains of public URL shorteners, which hide the final destination
+const SHORTENER_DOMAINS: &[&str] = &[
+    "bit.ly", "bitly.com", "tinyurl.com", "t.co", "goo.gl", "ow.ly", "is.gd", "v.gd",
+    "buff.ly", "rebrand.ly", "cutt.ly", "shorturl.at", "rb.gy", "tiny.cc", "bl.ink",
+    "s.id", "lnkd.in", "t.ly", "qrco.de", "qr.net", "short.io", "shorturl.com",
+    "soo.gd", "clck.ru", "u.to", "x.co", "tr.im", "adf.ly", "shorte.st", "v.ht",
+];
+
+/// Schemes that run code or embed content instead of navigating somewhere
+const DANGEROUS_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:"];
+
+/// A single problem found in a link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetyFinding {
+    pub code: String,     // stable identifier, e.g. "insecure-http"
+    pub severity: String, // "info" | "warn" | "danger"
+    pub message: String,
+}
+
+/// Result of checking a link without any network access
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlSafetyReport {
+    pub level: String, // "safe" | "caution" | "danger"
+    pub host: Option<String>, // Unicode form, as a person would read it
+    pub findings: Vec<SafetyFinding>,
+}
+
+impl UrlSafetyReport {
+    fn new(host: Option<String>) -> Self {
+        Self {
+            level: "safe".to_string(),
+            host,
+            findings: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, code: &str, severity: &str, message: String) {
+        self.findings.push(SafetyFinding {
+            code: code.to_string(),
+            severity: severity.to_string(),
+            message,
+        });
+
+        let level = match severity {
+            "danger" => "danger",
+            "warn" if self.level != "danger" => "caution",
+            _ => return,
+        };
+        self.level = level.to_string();
+    }
+}
+
+/// Check content that is a link. Returns `None` for anything that isn't one.
+pub fn analyze_url(content: &str) -> Option<UrlSafetyReport> {
+    let content = content.trim();
+    let lower = content.to_lowercase();
+
+    if let Some(scheme) = DANGEROUS_SCHEMES.iter().find(|s| lower.starts_with(*s)) {
+        let mut report = UrlSafetyReport::new(None);
+        report.add(
+            "dangerous-scheme",
+            "danger",
+            format!("'{}' links run code or embed content in the browser", scheme),
+        );
+        return Some(report);
+    }
+
+    if !lower.starts_with("http://") && !lower.starts_with("https://") {
+        return None;
+    }
+
+    let url = match Url::parse(content) {
+        Ok(url) => url,
+        Err(e) => {
+            let mut report = UrlSafetyReport::new(None);
+            report.add("malformed", "warn", format!("Link could not be parsed: {}", e));
+            return Some(report);
+        }
+    };
+
+    let host = url.host().map(|host| match host {
+        Host::Domain(domain) => idna::domain_to_unicode(domain).0,
+        other => other.to_string(),
+    });
+    let mut report = UrlSafetyReport::new(host.clone());
+
+    if url.scheme() == "http" {
+        report.add(
+            "insecure-http",
+            "warn",
+            "Link uses http instead of https".to_string(),
+        );
+    }
+
+    if !url.username().is_empty() || url.password().is_some() {
+        report.add(
+            "credentials",
+            "danger",
+            "Link contains a user name or password, which can disguise the real destination"
+                .to_string(),
+        );
+    }
+
+    match url.host() {
+        Some(Host::Ipv4(ip)) => {
+            // The URL parser accepts forms like http://3232235777/ or http://0xC0.0xA8.1.1/
+            if raw_host(content) != ip.to_string() {
+                report.add(
+                    "obfuscated-ip",
+                    "danger",
+                    format!("Host is an IP address ({}) written in an unusual form", ip),
+                );
+            } else {
+                report.add("ip-host", "warn", format!("Host is a raw IP address ({})", ip));
+            }
+        }
+        Some(Host::Ipv6(ip)) => {
+            report.add("ip-host", "warn", format!("Host is a raw IP address ({})", ip));
+        }
+        Some(Host::Domain(domain)) => {
+            check_domain(&mut report, domain, host.as_deref().unwrap_or(domain));
+        }
+        None => {}
+    }
+
+    check_redirects(&mut report, &url);
+
+    Some(report)
+}
+
+/// Host exactly as written, before the URL parser normalised it
+fn raw_host(content: &str) -> String {
+    let rest = content.split_once("://").map_or(content, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#', '\\']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    host.split(':').next().unwrap_or("").to_lowercase()
+}
+
+fn check_domain(report: &mut UrlSafetyReport, ascii: &str, unicode: &str) {
+    if let Some(shortener) = SHORTENER_DOMAINS
+        .iter()
+        .find(|d| ascii == **d || ascii.ends_with(&format!(".{}", d)))
+    {
+        report.add(
+            "url-shortener",
+            "warn",
+            format!("{} is a URL shortener, so the final destination is hidden", shortener),
+        );
+    }
+
+    if ascii == unicode {
+        return;
+    }
+
+    let mut flagged = false;
+    for label in unicode.split('.') {
+        let scripts = label_scripts(label);
+        if is_mixed_script(&scripts) {
+            let names: Vec<&str> = scripts.iter().map(|s| s.full_name()).collect();
+            report.add(
+                "mixed-script",
+                "danger",
+                format!("'{}' mixes {} characters", label, names.join(" and ")),
+            );
+            flagged = true;
+        } else if let Some(lookalike) = latin_skeleton(label) {
+            report.add(
+                "homoglyph",
+                "danger",
+                format!("'{}' is not Latin text but looks like '{}'", label, lookalike),
+            );
+            flagged = true;
+        }
+    }
+
+    if !flagged {
+        report.add(
+            "idn-host",
+            "info",
+            format!("Host uses international characters ({} is shown as {})", ascii, unicode),
+        );
+    }
+}
+
+/// Scripts used in a label, ignoring digits, hyphens and combining marks
+fn label_scripts(label: &str) -> Vec<Script> {
+    let mut scripts = Vec::new();
+    for script in label.chars().map(|c| c.script()) {
+        if !matches!(script, Script::Common | Script::Inherited | Script::Unknown)
+            && !scripts.contains(&script)
+        {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+/// Whether a label mixes scripts beyond the combinations normal in CJK names
+fn is_mixed_script(scripts: &[Script]) -> bool {
+    let is_cjk = |s: &Script| {
+        matches!(
+            s,
+            Script::Han | Script::Hiragana | Script::Katakana | Script::Bopomofo | Script::Hangul
+        )
+    };
+    let others: Vec<&Script> = scripts.iter().filter(|s| !is_cjk(s)).collect();
+
+    others.len() > 1 || (others.len() < scripts.len() && others.iter().any(|s| **s != Script::Latin))
+}
+
+/// If every character of a non-ASCII label has a Latin lookalike, the label it imitates
+fn latin_skeleton(label: &str) -> Option<String> {
+    if label.is_ascii() {
+        return None;
+    }
+    label
+        .chars()
+        .map(|c| if c.is_ascii() { Some(c) } else { latin_lookalike(c) })
+        .collect()
+}
+
+/// Cyrillic and Greek letters that render like Latin ones in common fonts
+fn latin_lookalike(c: char) -> Option<char> {
+    let latin = match c {
+        'а' => 'a', 'в' => 'b', 'с' | 'ϲ' => 'c', 'ԁ' => 'd', 'е' | 'ҽ' => 'e', 'һ' => 'h',
+        'і' | 'ι' => 'i', 'ј' | 'ϳ' => 'j', 'к' | 'κ' => 'k', 'ӏ' => 'l', 'м' => 'm',
+        'п' | 'η' => 'n', 'о' | 'ο' | 'σ' => 'o', 'р' | 'ρ' => 'p', 'ԛ' => 'q', 'г' => 'r',
+        'ѕ' => 's', 'т' | 'τ' => 't', 'υ' => 'u', 'ѵ' | 'ν' => 'v', 'ԝ' | 'ω' => 'w',
+        'х' | 'χ' => 'x', 'у' | 'γ' => 'y', 'ᴢ' => 'z',
+        _ => return None,
+    };
+    Some(latin)
+}
+
+/// Flag links that bounce through other URLs passed as query parameters
+fn check_redirects(report: &mut UrlSafetyReport, url: &Url) {
+    let Some(query) = url.query() else {
+        return;
+    };
+
+    // Nested redirects are often encoded more than once
+    let mut decoded = query.to_string();
+    for _ in 0..3 {
+        match urlencoding::decode(&decoded) {
+            Ok(next) if next != decoded => decoded = next.into_owned(),
+            _ => break,
+        }
+    }
+
+    let lower = decoded.to_lowercase();
+    let embedded = lower.matches("http://").count() + lower.matches("https://").count();
+
+    match embedded {
+        0 => {}
+        1 => report.add(
+            "redirect-parameter",
+            "warn",
+            "Link passes another URL as a parameter and may redirect there".to_string(),
+        ),
+        n => report.add(
+            "excessive-redirects",
+            "danger",
+            format!("Link embeds {} other URLs and may redirect through each of them", n),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(content: &str) -> Vec<String> {
+        analyze_url(content)
+            .unwrap()
+            .findings
+            .into_iter()
+            .map(|f| f.code)
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze_url_safe() {
+        let report = analyze_url("https://example.com/menu?table=4").unwrap();
+        assert_eq!(report.level, "safe");
+        assert_eq!(report.host, Some("example.com".to_string()));
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_url_ignores_non_links() {
+        assert!(analyze_url("Hello World").is_none());
+        assert!(analyze_url("WIFI:S:Net;;").is_none());
+        assert!(analyze_url("mailto:alice@example.com").is_none());
+    }
+
+    #[test]
+    fn test_analyze_url_dangerous_schemes() {
+        let report = analyze_url("JavaScript:alert(document.cookie)").unwrap();
+        assert_eq!(report.level, "danger");
+        assert_eq!(codes("data:text/html;base64,PHNjcmlwdD4="), vec!["dangerous-scheme"]);
+    }
+
+    #[test]
+    fn test_analyze_url_insecure_and_credentials() {
+        let report = analyze_url("http://example.com").unwrap();
+        assert_eq!(report.level, "caution");
+        assert_eq!(codes("http://example.com"), vec!["insecure-http"]);
+
+        let report = analyze_url("https://paypal.com@evil.example/login").unwrap();
+        assert_eq!(report.level, "danger");
+        assert_eq!(report.host, Some("evil.example".to_string()));
+        assert_eq!(codes("https://user:pw@example.com"), vec!["credentials"]);
+    }
+
+    #[test]
+    fn test_analyze_url_ip_hosts() {
+        assert_eq!(codes("https://192.168.1.1/admin"), vec!["ip-host"]);
+        assert_eq!(codes("https://[2001:db8::1]/"), vec!["ip-host"]);
+        assert_eq!(codes("https://3232235777/"), vec!["obfuscated-ip"]);
+        assert_eq!(codes("https://0xC0.0xA8.1.1/"), vec!["obfuscated-ip"]);
+    }
+
+    #[test]
+    fn test_analyze_url_shorteners() {
+        assert_eq!(codes("https://bit.ly/3abcDEF"), vec!["url-shortener"]);
+        assert_eq!(codes("https://www.tinyurl.com/xyz"), vec!["url-shortener"]);
+        assert!(codes("https://notbit.ly/x").is_empty());
+    }
+
+    #[test]
+    fn test_analyze_url_homoglyphs() {
+        // "аррӏе" in Cyrillic, written both as Unicode and as punycode
+        let report = analyze_url("https://xn--80ak6aa92e.com/").unwrap();
+        assert_eq!(report.level, "danger");
+        assert_eq!(report.host, Some("аррӏе.com".to_string()));
+        assert_eq!(codes("https://аррӏе.com/"), vec!["homoglyph"]);
+
+        // Latin with a single Cyrillic "а"
+        assert_eq!(codes("https://pаypal.com/"), vec!["mixed-script"]);
+    }
+
+    #[test]
+    fn test_analyze_url_legitimate_idn() {
+        let report = analyze_url("https://münchen.de/").unwrap();
+        assert_eq!(report.level, "safe");
+        assert_eq!(codes("https://münchen.de/"), vec!["idn-host"]);
+
+        // Japanese names legitimately mix kanji and kana
+        assert_eq!(analyze_url("https://日本語ドメイン.jp/").unwrap().level, "safe");
+        assert_eq!(analyze_url("https://пример.рф/").unwrap().level, "safe");
+    }
+
+    #[test]
+    fn test_analyze_url_redirects() {
+        assert_eq!(
+            codes("https://example.com/out?url=https%3A%2F%2Fother.example%2F"),
+            vec!["redirect-parameter"]
+        );
+        // Double-encoded chain through two more sites
+        assert_eq!(
+            codes("https://example.com/r?next=https%253A%252F%252Fa.example%252F%253Fto%253Dhttps%25253A%25252F%25252Fb.example"),
+            vec!["excessive-redirects"]
+        );
+        assert!(codes("https://example.com/?q=hello").is_empty());
+    }
+}
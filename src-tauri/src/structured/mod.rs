@@ -0,0 +1,389 @@
+use crate::preprocess;
+use crate::render::ModuleMatrix;
+use image::{DynamicImage, GrayImage};
+use qrcode::bits::Bits;
+use qrcode::canvas::Canvas;
+use qrcode::{ec, Color, EcLevel, Version};
+use rxing::common::HybridBinarizer;
+use rxing::multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader};
+use rxing::qrcode::QRCodeReader;
+use rxing::{
+    BinaryBitmap, DecodeHints, Luma8LuminanceSource, RXingResultMetadataType,
+    RXingResultMetadataValue,
+};
+use std::collections::BTreeMap;
+
+/// Most symbols a Structured Append set can have (ISO/IEC 18004 section 8)
+pub const MAX_SYMBOLS: usize = 16;
+
+const MODE_STRUCTURED_APPEND: u32 = 0b0011;
+const MODE_BYTE: u32 = 0b0100;
+
+/// One symbol of a Structured Append set
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub index: u8, // 0-based position in the set
+    pub total: u8,
+    pub parity: u8,
+    pub content: String,
+}
+
+/// The parts of one set found so far, keyed by index
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assembly {
+    pub total: u8,
+    pub parity: u8,
+    pub parts: BTreeMap<u8, usize>, // part index -> position in the scanned list
+    pub missing: Vec<u8>,
+    pub parity_ok: Option<bool>, // known once every part is present
+    pub content: Option<String>, // set when complete and the parity matches
+}
+
+/// XOR of every byte of the content, shared by all symbols of a set
+pub fn parity(data: &[u8]) -> u8 {
+    data.iter().fold(0, |acc, b| acc ^ b)
+}
+
+/// Split content across as few Structured Append symbols as possible, none
+/// larger than `max_version`. All symbols share the smallest version that
+/// fits the largest part so they print at the same size.
+pub fn encode(content: &str, ec_level: EcLevel, max_version: i16) -> Result<Vec<ModuleMatrix>, String> {
+    if content.is_empty() {
+        return Err("Content is empty".to_string());
+    }
+    if !(1..=40).contains(&max_version) {
+        return Err("Version must be between 1 and 40".to_string());
+    }
+
+    let largest = byte_capacity(max_version, ec_level)?;
+    let fewest = content.len().div_ceil(largest.max(1)).max(1);
+
+    for count in fewest..=MAX_SYMBOLS {
+        let chunks = split_on_char_boundaries(content, count);
+        let longest = chunks.iter().map(|c| c.len()).max().unwrap_or(0);
+        if longest > largest {
+            continue;
+        }
+
+        let mut version = 1;
+        while byte_capacity(version, ec_level)? < longest {
+            version += 1;
+        }
+
+        let parity = parity(content.as_bytes());
+        return chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                encode_part(chunk.as_bytes(), index as u8, chunks.len() as u8, parity, version, ec_level)
+            })
+            .collect();
+    }
+
+    Err(format!(
+        "Content is too long for {} symbols at version {}",
+        MAX_SYMBOLS, max_version
+    ))
+}
+
+/// Bytes of content that fit in one symbol after the Structured Append
+/// header and the byte mode segment header
+fn byte_capacity(version: i16, ec_level: EcLevel) -> Result<usize, String> {
+    let bits = Bits::new(Version::Normal(version))
+        .max_len(ec_level)
+        .map_err(|e| format!("Unsupported version: {}", e))?;
+    let overhead = 20 + 4 + count_bits(version);
+    Ok(bits.saturating_sub(overhead) / 8)
+}
+
+/// Width of the byte mode character count for a version
+fn count_bits(version: i16) -> usize {
+    if version <= 9 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Split into `count` pieces of similar byte length without splitting a
+/// character, so scanners that read each symbol alone still see valid text
+fn split_on_char_boundaries(content: &str, count: usize) -> Vec<&str> {
+    let mut chunks = Vec::with_capacity(count);
+    let mut start = 0;
+
+    for remaining in (1..=count).rev() {
+        if start >= content.len() {
+            break;
+        }
+        let mut end = if remaining == 1 {
+            content.len()
+        } else {
+            start + (content.len() - start).div_ceil(remaining)
+        };
+        while !content.is_char_boundary(end) {
+            end += 1;
+        }
+        chunks.push(&content[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+/// Build one symbol: Structured Append header, a byte segment, terminator and padding
+fn encode_part(
+    data: &[u8],
+    index: u8,
+    total: u8,
+    parity: u8,
+    version: i16,
+    ec_level: EcLevel,
+) -> Result<ModuleMatrix, String> {
+    let number = version;
+    let version = Version::Normal(number);
+    let capacity = Bits::new(version)
+        .max_len(ec_level)
+        .map_err(|e| format!("Unsupported version: {}", e))?;
+
+    let mut bits = BitWriter::default();
+    bits.push(4, MODE_STRUCTURED_APPEND);
+    bits.push(4, index as u32);
+    bits.push(4, (total - 1) as u32);
+    bits.push(8, parity as u32);
+    bits.push(4, MODE_BYTE);
+    bits.push(count_bits(number), data.len() as u32);
+    for &byte in data {
+        bits.push(8, byte as u32);
+    }
+    if bits.len > capacity {
+        return Err("Part does not fit in the chosen version".to_string());
+    }
+    let codewords = bits.finish(capacity, 4);
+
+    let (data_codewords, ec_codewords) = ec::construct_codewords(&codewords, version, ec_level)
+        .map_err(|e| format!("Failed to encode QR code: {}", e))?;
+    let mut canvas = Canvas::new(version, ec_level);
+    canvas.draw_all_functional_patterns();
+    canvas.draw_data(&data_codewords, &ec_codewords);
+
+    let width = version.width() as usize;
+    let modules = canvas
+        .apply_best_mask()
+        .into_colors()
+        .into_iter()
+        .map(|c| c == Color::Dark)
+        .collect();
+    Ok(ModuleMatrix::new(width, width, modules))
+}
+
+/// Big-endian bit buffer for the data codewords
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    pub len: usize,
+}
+
+impl BitWriter {
+    pub fn push(&mut self, count: usize, value: u32) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Terminator of up to `terminator` bits, zero bits to the next byte,
+    /// then alternating pad codewords. A capacity that ends mid-byte (the
+    /// 4-bit last codeword of Micro QR M1 and M3) is filled with zeros.
+    pub fn finish(mut self, capacity: usize, terminator: usize) -> Vec<u8> {
+        let terminator = (capacity - self.len).min(terminator);
+        self.push(terminator, 0);
+        let boundary = self.len.next_multiple_of(8).min(capacity);
+        self.push(boundary - self.len, 0);
+        for pad in [0xEC, 0x11].into_iter().cycle().take((capacity - self.len) / 8) {
+            self.push(8, pad);
+        }
+        self.push(capacity - self.len, 0);
+        self.bytes
+    }
+}
+
+/// Read every Structured Append symbol in an image. Preprocessing
+/// strategies are tried in turn until one finds any.
+pub fn detect_parts(img: &DynamicImage) -> Vec<Part> {
+    for strategy in preprocess::STRATEGIES {
+        for gray in preprocess::apply(strategy, img) {
+            let parts = read_parts(gray);
+            if !parts.is_empty() {
+                return parts;
+            }
+        }
+    }
+    vec![]
+}
+
+fn read_parts(gray: GrayImage) -> Vec<Part> {
+    let (width, height) = gray.dimensions();
+    let Ok(source) = Luma8LuminanceSource::new(gray.into_raw(), width, height) else {
+        return vec![];
+    };
+    let mut bitmap = BinaryBitmap::new(HybridBinarizer::new(source));
+    let hints = DecodeHints {
+        TryHarder: Some(true),
+        CharacterSet: Some("UTF-8".to_string()),
+        ..Default::default()
+    };
+
+    let results = GenericMultipleBarcodeReader::new(QRCodeReader)
+        .decode_multiple_with_hints(&mut bitmap, &hints)
+        .unwrap_or_default();
+
+    let mut parts: Vec<Part> = Vec::new();
+    for result in results {
+        let metadata = result.getRXingResultMetadata();
+        let sequence = metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE);
+        let parity = metadata.get(&RXingResultMetadataType::STRUCTURED_APPEND_PARITY);
+        let (
+            Some(RXingResultMetadataValue::StructuredAppendSequence(sequence)),
+            Some(RXingResultMetadataValue::StructuredAppendParity(parity)),
+        ) = (sequence, parity)
+        else {
+            continue;
+        };
+
+        // High nibble is the position, low nibble the count minus one
+        let part = Part {
+            index: (*sequence >> 4) as u8 & 0x0F,
+            total: (*sequence & 0x0F) as u8 + 1,
+            parity: *parity as u8,
+            content: result.getText().to_string(),
+        };
+        // The reader may find the same symbol from several sub-regions
+        if !parts.contains(&part) {
+            parts.push(part);
+        }
+    }
+    parts
+}
+
+/// Group parts into sets by count and parity, and join the complete ones
+pub fn reassemble(parts: &[Part]) -> Vec<Assembly> {
+    let mut sets: BTreeMap<(u8, u8), BTreeMap<u8, usize>> = BTreeMap::new();
+    for (position, part) in parts.iter().enumerate() {
+        if part.index < part.total {
+            sets.entry((part.total, part.parity))
+                .or_default()
+                .entry(part.index)
+                .or_insert(position);
+        }
+    }
+
+    sets.into_iter()
+        .map(|((total, parity), found)| {
+            let missing: Vec<u8> = (0..total).filter(|i| !found.contains_key(i)).collect();
+            let joined = missing.is_empty().then(|| {
+                found
+                    .values()
+                    .map(|&position| parts[position].content.as_str())
+                    .collect::<String>()
+            });
+            let parity_ok = joined.as_ref().map(|c| self::parity(c.as_bytes()) == parity);
+
+            Assembly {
+                total,
+                parity,
+                parts: found,
+                missing,
+                parity_ok,
+                content: joined.filter(|_| parity_ok == Some(true)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_matrix_image;
+
+    fn part(index: u8, total: u8, parity: u8, content: &str) -> Part {
+        Part {
+            index,
+            total,
+            parity,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_split_keeps_characters_whole() {
+        let chunks = split_on_char_boundaries("ääää", 3);
+        assert_eq!(chunks.concat(), "ääää");
+        assert!(chunks.len() <= 3);
+        assert_eq!(split_on_char_boundaries("abcdefg", 3), vec!["abc", "de", "fg"]);
+    }
+
+    #[test]
+    fn test_encode_rejects_oversized_content() {
+        let huge = "x".repeat(20_000);
+        assert!(encode(&huge, EcLevel::M, 10).is_err());
+        assert!(encode("", EcLevel::M, 10).is_err());
+        assert!(encode("abc", EcLevel::M, 41).is_err());
+    }
+
+    #[test]
+    fn test_encode_and_scan_round_trip() {
+        let content = "BEGIN:VCARD\nVERSION:3.0\nFN:Zoë Example\nNOTE:".to_string() + &"long note ".repeat(20) + "\nEND:VCARD";
+        let symbols = encode(&content, EcLevel::M, 5).unwrap();
+        assert!(symbols.len() > 1 && symbols.len() <= MAX_SYMBOLS);
+        assert!(symbols.iter().all(|s| s.width() == symbols[0].width()));
+
+        // Lay every symbol out side by side in one image
+        let images: Vec<GrayImage> = symbols.iter().map(|s| render_matrix_image(s, 4, 4)).collect();
+        let side = images[0].width();
+        let mut sheet = GrayImage::from_pixel(side * images.len() as u32, side, image::Luma([255]));
+        for (i, img) in images.iter().enumerate() {
+            image::imageops::overlay(&mut sheet, img, (i as u32 * side) as i64, 0);
+        }
+
+        let parts = detect_parts(&DynamicImage::ImageLuma8(sheet));
+        assert_eq!(parts.len(), symbols.len());
+        assert!(parts.iter().all(|p| p.parity == parity(content.as_bytes())));
+
+        let sets = reassemble(&parts);
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].parity_ok, Some(true));
+        assert_eq!(sets[0].content.as_deref(), Some(content.as_str()));
+    }
+
+    #[test]
+    fn test_reassemble_reports_missing_and_bad_parity() {
+        let good = parity(b"abcdef");
+        let sets = reassemble(&[part(2, 3, good, "ef"), part(0, 3, good, "ab")]);
+        assert_eq!(sets[0].missing, vec![1]);
+        assert_eq!(sets[0].parity_ok, None);
+        assert_eq!(sets[0].content, None);
+
+        let sets = reassemble(&[part(0, 2, good, "abc"), part(1, 2, good, "xyz")]);
+        assert_eq!(sets[0].parity_ok, Some(false));
+        assert_eq!(sets[0].content, None);
+
+        // Two sets in one scan are kept apart by their parity
+        let sets = reassemble(&[
+            part(1, 2, good, "def"),
+            part(0, 2, parity(b"12"), "1"),
+            part(0, 2, good, "abc"),
+            part(1, 2, parity(b"12"), "2"),
+        ]);
+        assert_eq!(sets.len(), 2);
+        assert!(sets.iter().any(|s| s.content.as_deref() == Some("abcdef")));
+        assert!(sets.iter().any(|s| s.content.as_deref() == Some("12")));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::barcode::{self, BarcodeType};
+use crate::micro::{self, Fit, Kind};
+use crate::qr::{self, QrOptions};
+use crate::render::{self, ModuleMatrix};
+use image::GrayImage;
+use qrcode::EcLevel;
+use rqrr::PreparedImage;
+use rxing::aztec::AztecWriter;
+use rxing::common::{BitMatrix, HybridBinarizer};
+use rxing::datamatrix::DataMatrixWriter;
+use rxing::multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader};
+use rxing::pdf417::PDF417Writer;
+use rxing::{
+    BarcodeFormat, BinaryBitmap, DecodeHints, EncodeHints, Luma8LuminanceSource, MultiFormatReader,
+    Writer,
+};
+use std::collections::HashSet;
+
+/// Names accepted wherever a symbology can be chosen
+pub const SYMBOLOGIES: &[&str] = &["qr", "micro-qr", "rmqr", "data-matrix", "aztec", "pdf417"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symbology {
+    Qr,
+    MicroQr,
+    Rmqr,
+    DataMatrix,
+    Aztec,
+    Pdf417,
+}
+
+impl Symbology {
+    /// Parse one of `SYMBOLOGIES`
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "qr" => Ok(Self::Qr),
+            "micro-qr" | "micro" => Ok(Self::MicroQr),
+            "rmqr" => Ok(Self::Rmqr),
+            "data-matrix" | "datamatrix" => Ok(Self::DataMatrix),
+            "aztec" => Ok(Self::Aztec),
+            "pdf417" => Ok(Self::Pdf417),
+            other => Err(format!("Unknown symbology '{}'", other)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Qr => "qr",
+            Self::MicroQr => "micro-qr",
+            Self::Rmqr => "rmqr",
+            Self::DataMatrix => "data-matrix",
+            Self::Aztec => "aztec",
+            Self::Pdf417 => "pdf417",
+        }
+    }
+
+    /// Name for messages, e.g. "Data Matrix"
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Qr => "QR code",
+            Self::MicroQr => "Micro QR",
+            Self::Rmqr => "rMQR",
+            Self::DataMatrix => "Data Matrix",
+            Self::Aztec => "Aztec",
+            Self::Pdf417 => "PDF417",
+        }
+    }
+
+    /// Quiet zone drawn around rendered symbols, in modules. At least what
+    /// each specification requires.
+    pub fn quiet_zone(self) -> usize {
+        match self {
+            Self::Qr => 4,
+            Self::MicroQr | Self::Rmqr => micro::QUIET_ZONE,
+            Self::DataMatrix | Self::Aztec | Self::Pdf417 => 2,
+        }
+    }
+}
+
+/// Choices that only some symbologies use
+#[derive(Debug, Clone, Default)]
+pub struct EncodeOptions {
+    pub ec_level: Option<String>, // "L" | "M" | "Q" | "H"; ignored by Data Matrix
+    pub version: Option<String>,  // QR, Micro QR and rMQR only, e.g. "7", "M3" or "R11x43"
+    pub gs1: bool,                // Data Matrix only: GS1 element strings separated by GS (0x1D)
+    pub qr: QrOptions,            // QR only: segment mode, character set, ECI, mask and ECC boost
+}
+
+/// An encoded symbol, with the version fit for QR, Micro QR and rMQR
+#[derive(Debug, Clone)]
+pub struct Encoded {
+    pub matrix: ModuleMatrix,
+    pub fit: Option<Fit>,
+    pub ec_level: Option<EcLevel>, // QR only, after any boost
+    pub mask: Option<u8>,          // QR only
+}
+
+/// Encode content in any supported symbology
+pub fn encode(symbology: Symbology, content: &str, options: &EncodeOptions) -> Result<Encoded, String> {
+    if content.is_empty() {
+        return Err("Content is empty".to_string());
+    }
+    if options.gs1 && symbology != Symbology::DataMatrix {
+        return Err("GS1 encoding is only available for Data Matrix".to_string());
+    }
+    if options.version.is_some() && !matches!(symbology, Symbology::Qr | Symbology::MicroQr | Symbology::Rmqr) {
+        return Err(format!("A version can't be chosen for {}", symbology.label()));
+    }
+    if options.qr != QrOptions::default() && symbology != Symbology::Qr {
+        return Err("Segment modes, ECI, masks and ECC boost are only available for QR codes".to_string());
+    }
+    let ec_level = render::parse_ec_level(options.ec_level.as_deref().unwrap_or("M"))?;
+
+    let matrix = match symbology {
+        Symbology::Qr => {
+            let symbol = qr::encode(content, ec_level, options.version.as_deref(), &options.qr)?;
+            return Ok(Encoded {
+                matrix: symbol.matrix,
+                fit: Some(symbol.fit),
+                ec_level: Some(symbol.ec_level),
+                mask: Some(symbol.mask),
+            });
+        }
+        Symbology::MicroQr | Symbology::Rmqr => {
+            let kind = if symbology == Symbology::MicroQr { Kind::Micro } else { Kind::Rmqr };
+            let symbol = micro::encode(kind, content, ec_level, options.version.as_deref())?;
+            return Ok(Encoded {
+                matrix: symbol.matrix,
+                fit: Some(symbol.fit),
+                ec_level: None,
+                mask: None,
+            });
+        }
+        Symbology::DataMatrix => {
+            let hints = EncodeHints {
+                Gs1Format: Some(options.gs1),
+                // Only the minimal encoder understands GS1
+                DataMatrixCompact: Some(options.gs1),
+                ..writer_hints(content)
+            };
+            write(&DataMatrixWriter, content, BarcodeFormat::DATA_MATRIX, &hints)?
+        }
+        Symbology::Aztec => {
+            let percent = match ec_level {
+                EcLevel::L => 10,
+                EcLevel::M => 23,
+                EcLevel::Q => 36,
+                EcLevel::H => 50,
+            };
+            let hints = EncodeHints {
+                ErrorCorrection: Some(percent.to_string()),
+                ..writer_hints(content)
+            };
+            write(&AztecWriter, content, BarcodeFormat::AZTEC, &hints)?
+        }
+        Symbology::Pdf417 => {
+            // PDF417 error correction runs from level 0 to 8
+            let level = match ec_level {
+                EcLevel::L => 2,
+                EcLevel::M => 4,
+                EcLevel::Q => 5,
+                EcLevel::H => 6,
+            };
+            let hints = EncodeHints {
+                ErrorCorrection: Some(level.to_string()),
+                ..writer_hints(content)
+            };
+            write(&PDF417Writer, content, BarcodeFormat::PDF_417, &hints)?
+        }
+    };
+
+    Ok(Encoded {
+        matrix,
+        fit: None,
+        ec_level: None,
+        mask: None,
+    })
+}
+
+/// No margin, and an ECI for content that isn't plain ASCII
+fn writer_hints(content: &str) -> EncodeHints {
+    EncodeHints {
+        Margin: Some("0".to_string()),
+        CharacterSet: (!content.is_ascii()).then(|| "UTF-8".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Run an rxing writer at one pixel per module
+fn write(writer: &impl Writer, content: &str, format: BarcodeFormat, hints: &EncodeHints) -> Result<ModuleMatrix, String> {
+    let bits = writer
+        .encode_with_hints(content, &format, 0, 0, hints)
+        .map_err(|e| format!("Failed to encode {:?}: {}", format, e))?;
+    Ok(matrix_from_bits(&bits))
+}
+
+fn matrix_from_bits(bits: &BitMatrix) -> ModuleMatrix {
+    let (width, height) = (bits.getWidth() as usize, bits.getHeight() as usize);
+    let modules = (0..width * height)
+        .map(|i| bits.get((i % width) as u32, (i / width) as u32))
+        .collect();
+    ModuleMatrix::new(width, height, modules)
+}
+
+/// Decode every symbol other than a full-size QR code in an image, as
+/// (symbology, content) pairs. Linear barcodes are named by their type.
+pub fn read(gray: &GrayImage) -> Vec<(&'static str, String)> {
+    let (width, height) = gray.dimensions();
+    let Ok(source) = Luma8LuminanceSource::new(gray.as_raw().clone(), width, height) else {
+        return vec![];
+    };
+    let mut bitmap = BinaryBitmap::new(HybridBinarizer::new(source));
+    let hints = DecodeHints {
+        TryHarder: Some(true),
+        CharacterSet: Some("UTF-8".to_string()),
+        PossibleFormats: Some(HashSet::from([
+            BarcodeFormat::MICRO_QR_CODE,
+            BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+            BarcodeFormat::DATA_MATRIX,
+            BarcodeFormat::AZTEC,
+            BarcodeFormat::PDF_417,
+            BarcodeFormat::EAN_13,
+            BarcodeFormat::UPC_A,
+            BarcodeFormat::CODE_128,
+            BarcodeFormat::CODE_39,
+            BarcodeFormat::ITF,
+        ])),
+        ..Default::default()
+    };
+
+    let results = GenericMultipleBarcodeReader::new(MultiFormatReader::default())
+        .decode_multiple_with_hints(&mut bitmap, &hints)
+        .unwrap_or_default();
+
+    let mut found: Vec<(&'static str, String)> = Vec::new();
+    for result in results {
+        let text = result.getText();
+        let name = match result.getBarcodeFormat() {
+            BarcodeFormat::MICRO_QR_CODE => Symbology::MicroQr.name(),
+            BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => Symbology::Rmqr.name(),
+            BarcodeFormat::DATA_MATRIX => Symbology::DataMatrix.name(),
+            BarcodeFormat::AZTEC => Symbology::Aztec.name(),
+            BarcodeFormat::PDF_417 => Symbology::Pdf417.name(),
+            BarcodeFormat::EAN_13 => BarcodeType::Ean13.name(),
+            BarcodeFormat::UPC_A => BarcodeType::UpcA.name(),
+            BarcodeFormat::CODE_128 => BarcodeType::Code128.name(),
+            BarcodeFormat::CODE_39 => BarcodeType::Code39.name(),
+            // Interleaved 2 of 5 is only accepted as a valid ITF-14
+            BarcodeFormat::ITF if text.len() == 14 && barcode::complete_gtin(text, 14, BarcodeType::Itf14).is_ok() => {
+                BarcodeType::Itf14.name()
+            }
+            _ => continue,
+        };
+        let code = (name, text.to_string());
+        // The reader may find the same symbol from several sub-regions
+        if !found.contains(&code) {
+            found.push(code);
+        }
+    }
+    found
+}
+
+/// Canonical name and label of any 2D symbology or linear barcode type
+pub fn lookup(name: &str) -> Result<(&'static str, &'static str), String> {
+    if let Ok(symbology) = Symbology::parse(name) {
+        return Ok((symbology.name(), symbology.label()));
+    }
+    BarcodeType::parse(name)
+        .map(|kind| (kind.name(), kind.label()))
+        .map_err(|_| format!("Unknown symbology '{}'", name.trim()))
+}
+
+/// The first code in an image, as (symbology, content). A QR code that was
+/// located but couldn't be decoded is only reported when nothing else reads.
+pub fn read_first(gray: &GrayImage) -> Option<(&'static str, Result<String, String>)> {
+    let mut prepared = PreparedImage::prepare(gray.clone());
+    let qr = prepared
+        .detect_grids()
+        .first()
+        .map(|grid| qr::decode_grid(grid).map(|text| text.content));
+    if let Some(Ok(content)) = qr {
+        return Some((Symbology::Qr.name(), Ok(content)));
+    }
+    if let Some((symbology, content)) = read(gray).into_iter().next() {
+        return Some((symbology, Ok(content)));
+    }
+    qr.map(|result| (Symbology::Qr.name(), result))
+}
+
+/// Error correction level of the first QR code in an image
+pub fn read_qr_ec_level(gray: &GrayImage) -> Option<EcLevel> {
+    let mut prepared = PreparedImage::prepare(gray.clone());
+    prepared.detect_grids().first().and_then(qr::grid_ec_level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_matrix_image;
+
+    fn round_trip(symbology: Symbology, content: &str, options: &EncodeOptions) -> Vec<(&'static str, String)> {
+        let encoded = encode(symbology, content, options).unwrap();
+        read(&render_matrix_image(&encoded.matrix, symbology.quiet_zone() + 2, 4))
+    }
+
+    #[test]
+    fn test_parse_symbology_names() {
+        for name in SYMBOLOGIES {
+            assert_eq!(Symbology::parse(name).unwrap().name(), *name);
+        }
+        assert_eq!(Symbology::parse("DataMatrix").unwrap(), Symbology::DataMatrix);
+        assert!(Symbology::parse("maxicode").is_err());
+        assert_eq!(lookup("EAN13").unwrap(), ("ean-13", "EAN-13"));
+        assert!(lookup("maxicode").is_err());
+    }
+
+    #[test]
+    fn test_2d_symbologies_round_trip() {
+        let options = EncodeOptions::default();
+        for symbology in [Symbology::DataMatrix, Symbology::Aztec, Symbology::Pdf417] {
+            for content in ["https://example.com/track/123", "Größe: 42"] {
+                assert_eq!(
+                    round_trip(symbology, content, &options),
+                    vec![(symbology.name(), content.to_string())],
+                    "{}",
+                    symbology.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gs1_data_matrix_keeps_separators() {
+        let options = EncodeOptions {
+            gs1: true,
+            ..Default::default()
+        };
+        let content = "0109506000134352\u{1d}10ABC123";
+        let found = round_trip(Symbology::DataMatrix, content, &options);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1, content);
+
+        assert!(encode(Symbology::Aztec, content, &options).is_err());
+    }
+
+    #[test]
+    fn test_version_only_for_qr_kinds() {
+        let options = EncodeOptions {
+            version: Some("M2".to_string()),
+            ..Default::default()
+        };
+        assert!(encode(Symbology::MicroQr, "12345", &options).unwrap().fit.is_some());
+        assert!(encode(Symbology::DataMatrix, "12345", &options).is_err());
+        assert!(encode(Symbology::Qr, "12345", &options).is_err());
+
+        let options = EncodeOptions {
+            version: Some("5".to_string()),
+            ..Default::default()
+        };
+        let encoded = encode(Symbology::Qr, "12345", &options).unwrap();
+        assert_eq!(encoded.matrix.width(), 37);
+        assert_eq!(encoded.fit.unwrap().version, "5");
+    }
+
+    #[test]
+    fn test_qr_options_only_for_qr() {
+        let options = EncodeOptions {
+            qr: QrOptions {
+                mode: Some("kanji".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(encode(Symbology::Qr, "東京", &options).is_ok());
+        assert!(encode(Symbology::Aztec, "東京", &options).is_err());
+    }
+}
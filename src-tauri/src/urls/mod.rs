@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// The UTM parameters that can be set, in the order they are appended
+const UTM_KEYS: [&str; 4] = ["utm_source", "utm_medium", "utm_campaign", "utm_content"];
+
+/// How to rewrite http(s) links in a batch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlOptions {
+    #[serde(default)]
+    pub normalize: bool,
+    #[serde(default)]
+    pub trailing_slash: Option<String>, // "keep" (default) | "add" | "remove"
+    #[serde(default)]
+    pub utm: UtmTags,
+    #[serde(default)]
+    pub override_existing: bool, // replace UTM values already in the link
+}
+
+/// UTM values to apply. Unset tags are left alone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UtmTags {
+    pub source: Option<UtmValue>,
+    pub medium: Option<UtmValue>,
+    pub campaign: Option<UtmValue>,
+    pub content: Option<UtmValue>,
+}
+
+/// A fixed value, or a CSV column to read it from. The column wins when the
+/// row has a value for it; the fixed value is the fallback.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UtmValue {
+    pub value: Option<String>,
+    pub column: Option<String>,
+}
+
+impl UrlOptions {
+    /// Whether these options change anything at all
+    pub fn is_active(&self) -> bool {
+        self.normalize
+            || self.trailing_slash.as_deref().is_some_and(|p| p != "keep")
+            || self.utm.fields().iter().any(|(_, value)| value.is_some())
+    }
+}
+
+impl UtmTags {
+    fn fields(&self) -> [(&'static str, Option<&UtmValue>); 4] {
+        [
+            (UTM_KEYS[0], self.source.as_ref()),
+            (UTM_KEYS[1], self.medium.as_ref()),
+            (UTM_KEYS[2], self.campaign.as_ref()),
+            (UTM_KEYS[3], self.content.as_ref()),
+        ]
+    }
+
+    /// Resolve each tag for one row, looking columns up with `column`
+    pub fn resolve(&self, column: impl Fn(&str) -> Option<String>) -> Vec<(&'static str, String)> {
+        self.fields()
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = value?;
+                let resolved = value
+                    .column
+                    .as_deref()
+                    .and_then(&column)
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .or_else(|| value.value.as_deref().map(str::trim).map(str::to_string))
+                    .filter(|v| !v.is_empty())?;
+                Some((key, resolved))
+            })
+            .collect()
+    }
+}
+
+/// Whether content is a link these tools apply to
+pub fn is_web_url(content: &str) -> bool {
+    let lower = content.trim_start().to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Apply the options to one link. `column` looks up a CSV column for this row.
+pub fn process_url(
+    content: &str,
+    options: &UrlOptions,
+    column: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut url = parse_web_url(content)?;
+
+    if options.normalize {
+        normalize_in_place(&mut url);
+    }
+
+    match options.trailing_slash.as_deref().unwrap_or("keep") {
+        "keep" => {}
+        policy @ ("add" | "remove") => apply_trailing_slash(&mut url, policy == "add"),
+        other => return Err(format!("Unknown trailing slash policy '{}'", other)),
+    }
+
+    let tags = options.utm.resolve(column);
+    if !tags.is_empty() {
+        apply_utm(&mut url, &tags, options.override_existing);
+    }
+
+    Ok(url.to_string())
+}
+
+/// Normalize a link: punycode and lowercase host, default port removed,
+/// percent-encoding made consistent
+pub fn normalize_url(content: &str) -> Result<String, String> {
+    let mut url = parse_web_url(content)?;
+    normalize_in_place(&mut url);
+    Ok(url.to_string())
+}
+
+/// Whether two links are the same once normalized
+pub fn same_url(a: &str, b: &str) -> bool {
+    match (normalize_url(a), normalize_url(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn parse_web_url(content: &str) -> Result<Url, String> {
+    if !is_web_url(content) {
+        return Err("Not an http(s) link".to_string());
+    }
+    let url = Url::parse(content.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err("URL has no host".to_string());
+    }
+    Ok(url)
+}
+
+/// The URL parser already converts hosts to lowercase punycode and drops
+/// default ports; this also tidies the percent-encoding it leaves alone
+fn normalize_in_place(url: &mut Url) {
+    let path = normalize_percent_encoding(url.path());
+    url.set_path(&path);
+
+    if let Some(query) = url.query().map(normalize_percent_encoding) {
+        url.set_query(Some(&query).filter(|q| !q.is_empty()).map(|q| q.as_str()));
+    }
+    if let Some(fragment) = url.fragment().map(normalize_percent_encoding) {
+        url.set_fragment(Some(&fragment));
+    }
+}
+
+/// Uppercase escape hex digits and decode escaped unreserved characters (RFC 3986 6.2.2)
+fn normalize_percent_encoding(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = String::with_capacity(value.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escape {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                out.push(byte as char);
+                i += 3;
+            }
+            Some(byte) => {
+                out.push_str(&format!("%{:02X}", byte));
+                i += 3;
+            }
+            None => {
+                // Multi-byte characters were already percent-encoded by the parser
+                out.push(bytes[i] as char);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Add or remove the slash at the end of the path. Paths whose last segment
+/// looks like a file ("/menu.pdf") never gain one, and the root path stays "/".
+fn apply_trailing_slash(url: &mut Url, add: bool) {
+    let path = url.path().to_string();
+    let last = path.rsplit('/').next().unwrap_or("");
+
+    if add && !path.ends_with('/') && !last.contains('.') {
+        url.set_path(&format!("{}/", path));
+    } else if !add && path.len() > 1 && path.ends_with('/') {
+        url.set_path(path.trim_end_matches('/'));
+    }
+}
+
+/// Set UTM parameters, leaving every other parameter exactly as written
+fn apply_utm(url: &mut Url, tags: &[(&str, String)], override_existing: bool) {
+    let mut params: Vec<String> = url
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    for (key, value) in tags {
+        let encoded = format!("{}={}", key, urlencoding::encode(value));
+        let existing = params.iter().position(|p| {
+            let name = p.split('=').next().unwrap_or("");
+            urlencoding::decode(name).is_ok_and(|n| n.eq_ignore_ascii_case(key))
+        });
+
+        match existing {
+            Some(i) if override_existing => params[i] = encoded,
+            Some(_) => {}
+            None => params.push(encoded),
+        }
+    }
+
+    url.set_query(Some(&params.join("&")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utm(value: &str) -> Option<UtmValue> {
+        Some(UtmValue {
+            value: Some(value.to_string()),
+            column: None,
+        })
+    }
+
+    fn no_columns(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("HTTPS://Example.COM:443/a%7eb/%c3%a9?q=%2fx").unwrap(),
+            "https://example.com/a~b/%C3%A9?q=%2Fx"
+        );
+        assert_eq!(normalize_url("https://münchen.de/stadt plan").unwrap(), "https://xn--mnchen-3ya.de/stadt%20plan");
+        assert!(normalize_url("mailto:a@example.com").is_err());
+        assert!(normalize_url("https://").is_err());
+    }
+
+    #[test]
+    fn test_trailing_slash_policy() {
+        let add = UrlOptions {
+            trailing_slash: Some("add".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(process_url("https://example.com/menu", &add, no_columns).unwrap(), "https://example.com/menu/");
+        assert_eq!(process_url("https://example.com/menu.pdf", &add, no_columns).unwrap(), "https://example.com/menu.pdf");
+
+        let remove = UrlOptions {
+            trailing_slash: Some("remove".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(process_url("https://example.com/menu/?a=1", &remove, no_columns).unwrap(), "https://example.com/menu?a=1");
+        assert_eq!(process_url("https://example.com", &remove, no_columns).unwrap(), "https://example.com/");
+
+        let bad = UrlOptions {
+            trailing_slash: Some("sometimes".to_string()),
+            ..Default::default()
+        };
+        assert!(process_url("https://example.com", &bad, no_columns).is_err());
+    }
+
+    #[test]
+    fn test_utm_append_and_override() {
+        let mut options = UrlOptions {
+            utm: UtmTags {
+                source: utm("flyer"),
+                medium: utm("qr"),
+                campaign: utm("spring sale"),
+                content: None,
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            process_url("https://example.com/?ref=a+b&utm_source=web", &options, no_columns).unwrap(),
+            "https://example.com/?ref=a+b&utm_source=web&utm_medium=qr&utm_campaign=spring%20sale"
+        );
+
+        options.override_existing = true;
+        assert_eq!(
+            process_url("https://example.com/?UTM_SOURCE=web", &options, no_columns).unwrap(),
+            "https://example.com/?utm_source=flyer&utm_medium=qr&utm_campaign=spring%20sale"
+        );
+    }
+
+    #[test]
+    fn test_utm_from_columns() {
+        let options = UrlOptions {
+            utm: UtmTags {
+                source: utm("print"),
+                content: Some(UtmValue {
+                    value: Some("default".to_string()),
+                    column: Some("store".to_string()),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let row = |column: &str| (column == "store").then(|| "Store 12".to_string());
+        assert_eq!(
+            process_url("https://example.com/", &options, row).unwrap(),
+            "https://example.com/?utm_source=print&utm_content=Store%2012"
+        );
+        // Empty column falls back to the fixed value
+        assert_eq!(
+            process_url("https://example.com/", &options, |_| Some(String::new())).unwrap(),
+            "https://example.com/?utm_source=print&utm_content=default"
+        );
+    }
+
+    #[test]
+    fn test_same_url() {
+        assert!(same_url("https://EXAMPLE.com/%7euser", "https://example.com/~user"));
+        assert!(!same_url("https://example.com/a", "https://example.com/b"));
+        assert!(!same_url("hello", "hello"));
+    }
+
+    #[test]
+    fn test_options_is_active() {
+        assert!(!UrlOptions::default().is_active());
+        assert!(UrlOptions {
+            normalize: true,
+            ..Default::default()
+        }
+        .is_active());
+    }
+}